@@ -0,0 +1,471 @@
+use crate::{
+  content::ContentManager,
+  gfx::Gfx,
+  input::InputManager,
+  log,
+  pdf::{self, PdfManager},
+  session::Session,
+  spaces::SpaceManager,
+  stroke::StrokeManager,
+  tools::ToolManager,
+  ui::{self, Ui},
+  util,
+};
+
+use instant::{Duration, Instant};
+use winit::{
+  event::WindowEvent,
+  event_loop::ControlFlow,
+  window::{Window, WindowId},
+};
+
+pub type CustomEvent = ();
+pub type Event<'a> = winit::event::Event<'a, CustomEvent>;
+pub type EventLoop = winit::event_loop::EventLoop<CustomEvent>;
+
+pub struct Application {
+  event_loop: Option<EventLoop>,
+  input_manager: InputManager,
+  window: Window,
+  gfx: Gfx,
+
+  egui_ctx: egui::Context,
+  egui_winit: egui_winit::State,
+  egui_shapes: Option<Vec<egui::epaint::ClippedShape>>,
+  egui_textures_delta: Option<egui::TexturesDelta>,
+  ui: Ui,
+
+  content_manager: ContentManager,
+  tool_manager: ToolManager,
+  pdf_manager: PdfManager,
+  stroke_manager: StrokeManager,
+
+  space_manager: SpaceManager,
+
+  session: Session,
+  #[cfg(not(target_arch = "wasm32"))]
+  autosaver: crate::file::autosave::Autosaver,
+}
+
+impl Application {
+  pub async fn init() -> Application {
+    log::init_log();
+
+    let event_loop = EventLoop::default();
+    let window = winit::window::WindowBuilder::default()
+      .with_title(util::APP_NAME)
+      .build(&event_loop)
+      .expect("Fatal error: Failed to create winit window.");
+
+    #[cfg(target_arch = "wasm32")]
+    wasm::init(&window);
+
+    let input_manager = InputManager::default();
+    let gfx = Gfx::init(&window).await;
+
+    let egui_ctx = egui::Context::default();
+    let mut egui_winit = egui_winit::State::new(&event_loop);
+    egui_winit.set_pixels_per_point(window.scale_factor() as f32);
+    let egui_shapes = None;
+    let egui_textures_delta = None;
+    let ui = Ui::default();
+
+    let content_manager = ContentManager::default();
+    let tool_manager = ToolManager::default();
+    let pdf_manager = PdfManager::default();
+    let stroke_manager = StrokeManager::default();
+    let space_manager = SpaceManager::default();
+
+    #[cfg(not(target_arch = "wasm32"))]
+    let autosaver = crate::file::autosave::Autosaver::spawn();
+    #[cfg(not(target_arch = "wasm32"))]
+    let content_manager = content_manager.with_journal(autosaver.journal());
+
+    //home/luis/dl/grid.pdf
+
+    Self {
+      event_loop: Some(event_loop),
+      window,
+      input_manager,
+      gfx,
+
+      egui_ctx,
+      egui_winit,
+      egui_shapes,
+      egui_textures_delta,
+      ui,
+
+      content_manager,
+      tool_manager,
+      pdf_manager,
+      stroke_manager,
+
+      space_manager,
+
+      session: Session::default(),
+      #[cfg(not(target_arch = "wasm32"))]
+      autosaver,
+    }
+  }
+
+  /// Reopens the document and puts back the view and tools of the last run.
+  #[cfg(not(target_arch = "wasm32"))]
+  fn restore_session(&mut self) {
+    let mut session = match crate::file::load_session() {
+      Ok(session) => session.unwrap_or_default(),
+      Err(error) => {
+        tracing::warn!("failed to load the session: {error}");
+        Session::default()
+      }
+    };
+    *self.space_manager.camera_mut() = session.camera.clone();
+    self.tool_manager.selected = session.tool;
+    self.tool_manager.configs.pen = session.pen.clone();
+
+    if let Some(document) = &session.document {
+      match crate::file::load(document) {
+        Ok(mut savefile) => {
+          if let Err(error) = savefile.extract_pdfs() {
+            tracing::warn!(
+              "failed to extract the pdfs of {}: {error}",
+              document.display()
+            );
+          }
+          // restoring happens unasked, so a document with missing pdfs stays closed
+          let missing = savefile
+            .content
+            .pages()
+            .sources()
+            .iter()
+            .find(|source| !source.is_file());
+          if let Some(source) = missing {
+            tracing::warn!(
+              "not reopening {}, {} is missing",
+              document.display(),
+              source.display()
+            );
+            session.document = None;
+          } else {
+            self
+              .content_manager
+              .replace(savefile.content, savefile.protocol);
+          }
+        }
+        Err(error) => {
+          tracing::warn!("failed to reopen {}: {error}", document.display());
+          session.document = None;
+        }
+      }
+    } else if let Some(pdf) = session.pdf.clone().filter(|pdf| pdf.is_file()) {
+      let npages = self
+        .pdf_manager
+        .load_source(pdf.clone())
+        .map_or(0, |info| info.npages());
+      let mut pages = pdf::PageList::default();
+      pages.insert_pdf(0, pdf, 0..npages);
+      self.content_manager.replace(
+        crate::content::Content::new(pages, []),
+        crate::content::protocol::Protocol::default(),
+      );
+    }
+    self.content_manager.mark_saved();
+    self.session = session;
+  }
+
+  /// Opens the savefile, with the pages of the pdfs appended.
+  /// Without savefile the pdfs make up a new document.
+  #[cfg(not(target_arch = "wasm32"))]
+  fn open_files(&mut self, files: OpenFiles) {
+    let mut pages = pdf::PageList::default();
+    let mut has_document = false;
+    if let Some(document) = files.document {
+      match crate::file::load(&document) {
+        Ok(mut savefile) => {
+          if let Err(error) = savefile.extract_pdfs() {
+            tracing::error!(
+              "failed to extract the pdfs of {}: {error}",
+              document.display()
+            );
+          }
+          self
+            .content_manager
+            .replace(savefile.content, savefile.protocol);
+          self.content_manager.mark_saved();
+          self.session.set_document(document);
+          pages = self.content_manager.access().pages().clone();
+          has_document = true;
+        }
+        Err(error) => tracing::error!("failed to open {}: {error}", document.display()),
+      }
+    }
+    if files.pdfs.is_empty() {
+      return;
+    }
+
+    for pdf in files.pdfs {
+      if !pdf.is_file() {
+        tracing::error!("failed to open {}: not a file", pdf.display());
+        continue;
+      }
+      let Ok(info) = self.pdf_manager.load_source(pdf.clone()) else {
+        continue;
+      };
+      let npages = info.npages();
+      pages.insert_pdf(pages.npages(), pdf, 0..npages);
+    }
+    if has_document {
+      // appended pages move none of the others, so all strokes stay on their pages
+      let command = crate::content::command::SetPagesCommand::new(pages);
+      self.content_manager.run_cmd(command);
+    } else {
+      self.session.document = None;
+      self.session.pdf = pages.sources().first().cloned();
+      self.content_manager.replace(
+        crate::content::Content::new(pages, []),
+        crate::content::protocol::Protocol::default(),
+      );
+      self.content_manager.mark_saved();
+    }
+  }
+
+  /// Remembers the view and tools for the next run.
+  #[cfg(not(target_arch = "wasm32"))]
+  fn save_session(&mut self) {
+    self.session.camera = self.space_manager.camera().clone();
+    self.session.tool = self.tool_manager.selected;
+    self.session.pen = self.tool_manager.configs.pen.clone();
+    if let Err(error) = crate::file::save_session(&self.session) {
+      tracing::error!("failed to save the session: {error}");
+    }
+  }
+
+  pub fn run(self) {
+    #[cfg(not(target_arch = "wasm32"))]
+    self.run_with(OpenFiles::default());
+    #[cfg(target_arch = "wasm32")]
+    self.run_event_loop();
+  }
+
+  /// Opens the files instead of the document of the last session.
+  #[cfg(not(target_arch = "wasm32"))]
+  pub fn run_with(mut self, files: OpenFiles) {
+    self.restore_session();
+    self.open_files(files);
+    self.run_event_loop();
+  }
+
+  fn run_event_loop(mut self) {
+    self
+      .event_loop
+      .take()
+      .unwrap()
+      .run(move |event, _, control_flow| {
+        self.handle_event(event, control_flow);
+      });
+  }
+
+  fn handle_event(&mut self, event: Event<'_>, control_flow: &mut ControlFlow) {
+    match event {
+      Event::NewEvents(_) => self.reset(),
+      Event::WindowEvent { window_id, event } => {
+        self.handle_window_event(event, window_id, control_flow)
+      }
+      Event::MainEventsCleared => self.update(control_flow),
+      Event::RedrawRequested(_) => self.render(),
+      Event::RedrawEventsCleared => {}
+      Event::Suspended => {}
+      Event::Resumed => {}
+      Event::LoopDestroyed => {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+          self.autosaver.finish(&self.content_manager);
+          self.save_session();
+        }
+      }
+      _ => {}
+    }
+  }
+
+  fn handle_window_event(
+    &mut self,
+    event: WindowEvent,
+    window_id: WindowId,
+    control_flow: &mut ControlFlow,
+  ) {
+    assert_eq!(window_id, self.window.id());
+    match event {
+      WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
+      WindowEvent::Resized(new_size) => self.gfx.resize(new_size.width, new_size.height),
+      WindowEvent::ScaleFactorChanged {
+        ref new_inner_size, ..
+      } => {
+        self.gfx.resize(new_inner_size.width, new_inner_size.height);
+      }
+      #[cfg(not(target_arch = "wasm32"))]
+      WindowEvent::HoveredFile(ref path) => self.ui.file_drop_mut().hover(path.clone()),
+      #[cfg(not(target_arch = "wasm32"))]
+      WindowEvent::HoveredFileCancelled => self.ui.file_drop_mut().cancel_hover(),
+      #[cfg(not(target_arch = "wasm32"))]
+      WindowEvent::DroppedFile(ref path) => {
+        let cursor_pos_screen_logical = self
+          .input_manager
+          .curr
+          .cursor_pos_screen_logical
+          .filter(|_| self.ui.canvas().has_focus());
+        let file = ui::file_drop::DroppedFile {
+          path: path.clone(),
+          cursor_pos_screen_logical,
+        };
+        self.ui.file_drop_mut().drop_file(file);
+      }
+      _ => {}
+    }
+
+    let is_exclusive = self.egui_winit.on_event(&self.egui_ctx, &event).consumed;
+    if is_exclusive && !self.ui.canvas().has_focus() {
+      return;
+    }
+
+    self.input_manager.handle_event(&event, &self.space_manager);
+  }
+
+  fn reset(&mut self) {
+    self.input_manager.reset();
+    self.content_manager.reset_delta();
+  }
+
+  fn update(&mut self, control_flow: &mut ControlFlow) {
+    self.input_manager.update();
+
+    self.tool_manager.update(
+      &mut self.space_manager,
+      &self.input_manager,
+      &mut self.content_manager,
+      &self.stroke_manager,
+      &mut self.pdf_manager,
+    );
+
+    let egui_input: egui::RawInput = self.egui_winit.take_egui_input(&self.window);
+    let egui_output = self.egui_ctx.run(egui_input, |ctx| {
+      self.ui.run(
+        ctx,
+        ui::UiAccess {
+          spaces: &mut self.space_manager,
+          content_manager: &mut self.content_manager,
+          tool_manager: &mut self.tool_manager,
+          pdf_manager: &mut self.pdf_manager,
+          stroke_manager: &mut self.stroke_manager,
+          session: &mut self.session,
+        },
+      );
+    });
+    self.egui_winit.handle_platform_output(
+      &self.window,
+      &self.egui_ctx,
+      egui_output.platform_output,
+    );
+
+    self.egui_shapes = Some(egui_output.shapes);
+    self.egui_textures_delta = Some(egui_output.textures_delta);
+
+    // TODO: compare to eframe implementation
+    let repaint_after = egui_output.repaint_after;
+    if repaint_after.is_zero() {
+      *control_flow = ControlFlow::Poll;
+    } else if repaint_after == Duration::MAX {
+      *control_flow = ControlFlow::Wait;
+    } else {
+      let repaint_at = Instant::now() + repaint_after;
+      *control_flow = ControlFlow::WaitUntil(repaint_at);
+    }
+    self.window.request_redraw();
+
+    let access = self.content_manager.access();
+    let delta = self.content_manager.delta();
+    self
+      .stroke_manager
+      .update_strokes(access, &delta.strokes, self.gfx.wgpu().device());
+    if delta.pages_changed {
+      let access = self.content_manager.access();
+      self.pdf_manager.set_pages(access.pages());
+      self.pdf_manager.set_layout(access.layout().clone());
+    }
+
+    self
+      .space_manager
+      .update_camera_controller(&self.input_manager);
+    pdf::navigation::update(
+      &self.pdf_manager,
+      &mut self.space_manager,
+      &self.input_manager,
+    );
+    if self.space_manager.is_camera_animating() {
+      *control_flow = ControlFlow::Poll;
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(due) = self.autosaver.update(&self.content_manager) {
+      match control_flow {
+        ControlFlow::Wait => *control_flow = ControlFlow::WaitUntil(due),
+        ControlFlow::WaitUntil(repaint_at) if due < *repaint_at => *repaint_at = due,
+        _ => {}
+      }
+    }
+    self
+      .space_manager
+      .update_scale_factor(self.window.scale_factor() as f32);
+    self
+      .space_manager
+      .update_screen_rect(self.ui.canvas().screen_rect());
+  }
+
+  fn render(&mut self) {
+    self.gfx.prepare(
+      &self.window,
+      &self.egui_ctx,
+      self.egui_shapes.take().unwrap(),
+      self.egui_textures_delta.take().unwrap(),
+      &mut self.pdf_manager,
+      &self.space_manager,
+    );
+
+    self.gfx.render(&self.space_manager, &self.stroke_manager);
+  }
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen::prelude::wasm_bindgen(start))]
+pub async fn run() {
+  let app = Application::init().await;
+  app.run();
+}
+
+/// The files given on the command line.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Default)]
+pub struct OpenFiles {
+  /// a savefile
+  pub document: Option<std::path::PathBuf>,
+  /// their pages are appended to the document
+  pub pdfs: Vec<std::path::PathBuf>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn run_with(files: OpenFiles) {
+  let app = Application::init().await;
+  app.run_with(files);
+}
+
+#[cfg(target_arch = "wasm32")]
+mod wasm {
+  use winit::{dpi::PhysicalSize, platform::web::WindowExtWebSys, window::Window};
+  pub fn init(winit_window: &Window) {
+    let web_window = web_sys::window().unwrap();
+    winit_window.set_inner_size(PhysicalSize::new(
+      web_window.inner_width().unwrap().as_f64().unwrap(),
+      web_window.inner_height().unwrap().as_f64().unwrap(),
+    ));
+
+    let document = web_window.document().unwrap();
+    let body = document.body().unwrap();
+    body.append_child(&winit_window.canvas()).unwrap();
+  }
+}
@@ -9,7 +9,10 @@ use self::{
   command::ProtocolCommand,
   protocol::{Protocol, ProtocolNode, ProtocolNodeId},
 };
-use super::stroke::Stroke;
+use super::{
+  pdf::{PageList, PdfHighlight, PdfLayout},
+  stroke::Stroke,
+};
 
 use serde::{Deserialize, Serialize};
 
@@ -18,10 +21,15 @@ pub struct ContentManager {
   content: Content,
   protocol: Protocol,
   delta: ContentDelta,
+  /// counts the changes, to tell whether the document changed since it was saved
+  revision: u64,
+  saved_revision: u64,
+  journal: Option<&'static dyn Journal>,
 }
 
 impl ContentManager {
   pub fn run_cmd(&mut self, mut cmd: ProtocolCommand) {
+    let recorded = self.journal.map(|_| cmd.clone());
     cmd.execute(self.access_mut());
     let new = ProtocolNode::new(cmd, self.protocol.head);
     let new_id = ProtocolNodeId(u32::try_from(self.protocol.nodes.len()).unwrap());
@@ -30,6 +38,10 @@ impl ContentManager {
     old_head.children.push(new_id);
     old_head.selected_child = Some(old_head.children.len() - 1);
     self.protocol.head = new_id;
+    self.revision += 1;
+    if let Some(cmd) = recorded {
+      self.record(JournalEntry::Run(cmd));
+    }
   }
 
   /// If there is nothing to undo then it does nothing.
@@ -39,7 +51,12 @@ impl ContentManager {
       delta: &mut self.delta,
     };
     self.protocol.head_node_mut().command.rollback(access_mut);
-    self.protocol.head = self.protocol.head_node().parent;
+    let parent = self.protocol.head_node().parent;
+    if parent != self.protocol.head {
+      self.protocol.head = parent;
+      self.revision += 1;
+      self.record(JournalEntry::Undo);
+    }
   }
 
   /// If there is nothing to redo then it does nothing.
@@ -57,13 +74,17 @@ impl ContentManager {
         .command
         .execute(access_mut);
       self.protocol.head = selected_child;
+      self.revision += 1;
+      self.record(JournalEntry::Redo);
     }
   }
 
+  #[cfg(feature = "app")]
   pub fn switch_protocol_branch(&mut self, child_index: usize) {
     let head = self.protocol.head_node_mut();
     assert!(child_index < head.children.len());
     head.selected_child = Some(child_index);
+    self.record(JournalEntry::SwitchBranch(child_index));
   }
 
   pub fn undoable(&self) -> bool {
@@ -73,6 +94,33 @@ impl ContentManager {
   pub fn redoable(&self) -> bool {
     !self.protocol.head_node().children.is_empty()
   }
+
+  /// Has every change from now on recorded by `journal`.
+  #[cfg(feature = "app")]
+  #[cfg(not(target_arch = "wasm32"))]
+  pub fn with_journal(mut self, journal: &'static dyn Journal) -> Self {
+    self.journal = Some(journal);
+    self
+  }
+
+  /// Makes a change a journal recorded, on the content it was recorded on.
+  #[cfg(feature = "app")]
+  #[cfg(not(target_arch = "wasm32"))]
+  pub fn replay(&mut self, entry: JournalEntry) {
+    match entry {
+      JournalEntry::Run(cmd) => self.run_cmd(cmd),
+      JournalEntry::Undo => self.undo_cmd(),
+      JournalEntry::Redo => self.redo_cmd(),
+      JournalEntry::SwitchBranch(child_index) => self.switch_protocol_branch(child_index),
+      JournalEntry::Replace(content, protocol) => self.replace(*content, *protocol),
+    }
+  }
+
+  fn record(&self, entry: JournalEntry) {
+    if let Some(journal) = self.journal {
+      journal.record(entry);
+    }
+  }
 }
 impl ContentManager {
   pub fn access(&self) -> ContentAccess {
@@ -88,6 +136,7 @@ impl ContentManager {
     }
   }
 
+  #[cfg(feature = "app")]
   pub fn delta(&self) -> &ContentDelta {
     &self.delta
   }
@@ -97,6 +146,10 @@ impl ContentManager {
   }
 
   pub fn replace(&mut self, content: Content, protocol: Protocol) {
+    if self.journal.is_some() {
+      let entry = JournalEntry::Replace(Box::new(content.clone()), Box::new(protocol.clone()));
+      self.record(entry);
+    }
     self.delta.strokes.removed = self
       .content
       .strokes
@@ -104,9 +157,32 @@ impl ContentManager {
       .map(|(id, _)| StrokeId(id))
       .collect();
     self.delta.strokes.added = content.strokes.iter().map(|(id, _)| StrokeId(id)).collect();
+    self.delta.pages_changed = true;
 
     self.content = content;
     self.protocol = protocol;
+    self.revision += 1;
+  }
+
+  #[cfg(feature = "app")]
+  #[cfg(not(target_arch = "wasm32"))]
+  pub fn revision(&self) -> u64 {
+    self.revision
+  }
+
+  /// whether there are changes since the document was last saved or loaded
+  #[cfg(not(target_arch = "wasm32"))]
+  pub fn is_modified(&self) -> bool {
+    self.revision != self.saved_revision
+  }
+
+  #[cfg(not(target_arch = "wasm32"))]
+  pub fn protocol(&self) -> &Protocol {
+    &self.protocol
+  }
+
+  pub fn mark_saved(&mut self) {
+    self.saved_revision = self.revision;
   }
 
   pub fn clone(&self) -> (Content, Protocol) {
@@ -114,10 +190,64 @@ impl ContentManager {
   }
 }
 
+/// A change of the content. Replaying them in order on the content they were recorded on
+/// gives the content they lead to.
+#[derive(Clone, Serialize, Deserialize)]
+pub enum JournalEntry {
+  /// the command as it was before it ran
+  Run(ProtocolCommand),
+  Undo,
+  Redo,
+  SwitchBranch(usize),
+  Replace(Box<Content>, Box<Protocol>),
+}
+
+/// Receives every change of the content, see `ContentManager::with_journal`.
+pub trait Journal: Sync {
+  fn record(&self, entry: JournalEntry);
+}
+
 #[derive(Default, Clone, Serialize, Deserialize)]
 pub struct Content {
   strokes: Arena<Stroke>,
+  #[serde(default)]
+  highlights: Arena<PdfHighlight>,
+  #[serde(default)]
+  pages: PageList,
+  #[serde(default)]
+  layout: PdfLayout,
+}
+
+impl Content {
+  /// Content built from scratch, e.g. by an importer.
+  #[cfg(not(target_arch = "wasm32"))]
+  pub fn new(pages: PageList, strokes: impl IntoIterator<Item = Stroke>) -> Self {
+    let mut content = Self {
+      pages,
+      ..Self::default()
+    };
+    for stroke in strokes {
+      content.strokes.insert(stroke);
+    }
+    content
+  }
+
+  pub fn pages(&self) -> &PageList {
+    &self.pages
+  }
+
+  /// Points the pages to other pdfs, `rename` returns the new source if any.
+  #[cfg(not(target_arch = "wasm32"))]
+  pub fn rename_pdf_sources(
+    &mut self,
+    rename: &impl Fn(&crate::pdf::PdfSource) -> Option<crate::pdf::PdfSource>,
+  ) {
+    self.pages.rename_sources(rename);
+  }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct StrokeId(pub ArenaIndex);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct HighlightId(pub ArenaIndex);
@@ -1,6 +1,9 @@
-use super::{Content, StrokeId};
+use super::{Content, HighlightId, StrokeId};
 
-use crate::stroke::Stroke;
+use crate::{
+  pdf::{PageList, PdfHighlight, PdfLayout},
+  stroke::Stroke,
+};
 
 pub struct ContentAccess<'a> {
   pub(super) content: &'a Content,
@@ -17,6 +20,22 @@ impl<'a> ContentAccess<'a> {
       .iter()
       .map(|(id, stroke)| (StrokeId(id), stroke))
   }
+
+  pub fn highlights(&self) -> impl Iterator<Item = (HighlightId, &PdfHighlight)> {
+    self
+      .content
+      .highlights
+      .iter()
+      .map(|(id, highlight)| (HighlightId(id), highlight))
+  }
+
+  pub fn pages(&self) -> &'a PageList {
+    &self.content.pages
+  }
+
+  pub fn layout(&self) -> &'a PdfLayout {
+    &self.content.layout
+  }
 }
 
 pub struct ContentAccessMut<'a> {
@@ -34,6 +53,14 @@ impl<'a> ContentAccessMut<'a> {
 
 /// Methods for content module
 impl<'a> ContentAccessMut<'a> {
+  /// for commands made of other commands
+  pub(super) fn reborrow(&mut self) -> ContentAccessMut<'_> {
+    ContentAccessMut {
+      content: self.content,
+      delta: self.delta,
+    }
+  }
+
   pub(super) fn add_stroke(&mut self, stroke: Stroke) -> StrokeId {
     let id = self.content.strokes.insert(stroke);
     let id = StrokeId(id);
@@ -63,15 +90,46 @@ impl<'a> ContentAccessMut<'a> {
       }
     }
   }
+
+  pub(super) fn add_highlight(&mut self, highlight: PdfHighlight) -> HighlightId {
+    HighlightId(self.content.highlights.insert(highlight))
+  }
+
+  pub(super) fn remove_highlight(&mut self, id: HighlightId) -> PdfHighlight {
+    self.content.highlights.remove(id.0).unwrap()
+  }
+
+  pub(super) fn swap_pages(&mut self, pages: &mut PageList) {
+    std::mem::swap(&mut self.content.pages, pages);
+    self.delta.pages_changed = true;
+  }
+
+  pub(super) fn swap_layout(&mut self, layout: &mut PdfLayout) {
+    std::mem::swap(&mut self.content.layout, layout);
+    self.delta.pages_changed = true;
+  }
+
+  /// Like `remove_stroke` it falls back to the stroke with the index, if the generation is outdated.
+  pub(super) fn translate_stroke(&mut self, id: StrokeId, translation: na::Vector2<f32>) {
+    let strokes = &mut self.content.strokes;
+    let id = match strokes.get(id.0) {
+      Some(_) => id,
+      None => StrokeId(strokes.get_unknown_gen(id.0.index()).unwrap().1),
+    };
+    self.modify_stroke(id).translate(translation);
+  }
 }
 
 #[derive(Default)]
 pub struct ContentDelta {
   pub strokes: StrokeDelta,
+  /// the page list or the layout
+  pub pages_changed: bool,
 }
 impl ContentDelta {
   pub fn clear(&mut self) {
     self.strokes.clear();
+    self.pages_changed = false;
   }
 }
 
@@ -2,9 +2,15 @@
 
 use std::mem;
 
-use crate::stroke::Stroke;
+use crate::{
+  pdf::{PageList, PdfHighlight, PdfLayout, PdfManager},
+  stroke::Stroke,
+};
 
-use super::{access::ContentAccessMut, StrokeId};
+use super::{
+  access::{ContentAccess, ContentAccessMut},
+  HighlightId, StrokeId,
+};
 
 use serde::{Deserialize, Serialize};
 
@@ -13,6 +19,11 @@ pub enum ProtocolCommand {
   Sentinel,
   AddStrokeCommand(AddStrokeCommand),
   RemoveStrokesCommand(RemoveStrokesCommand),
+  AddStrokesCommand(AddStrokesCommand),
+  AddHighlightCommand(AddHighlightCommand),
+  SetPagesCommand(SetPagesCommand),
+  ArrangePagesCommand(ArrangePagesCommand),
+  RemoveHighlightCommand(RemoveHighlightCommand),
 }
 impl ProtocolCommand {
   pub fn execute(&mut self, content: ContentAccessMut) {
@@ -20,6 +31,11 @@ impl ProtocolCommand {
       ProtocolCommand::Sentinel => {}
       ProtocolCommand::AddStrokeCommand(cmd) => cmd.execute(content),
       ProtocolCommand::RemoveStrokesCommand(cmd) => cmd.execute(content),
+      ProtocolCommand::AddStrokesCommand(cmd) => cmd.execute(content),
+      ProtocolCommand::AddHighlightCommand(cmd) => cmd.execute(content),
+      ProtocolCommand::SetPagesCommand(cmd) => cmd.execute(content),
+      ProtocolCommand::ArrangePagesCommand(cmd) => cmd.execute(content),
+      ProtocolCommand::RemoveHighlightCommand(cmd) => cmd.execute(content),
     }
   }
 
@@ -28,6 +44,11 @@ impl ProtocolCommand {
       ProtocolCommand::Sentinel => {}
       ProtocolCommand::AddStrokeCommand(cmd) => cmd.rollback(content),
       ProtocolCommand::RemoveStrokesCommand(cmd) => cmd.rollback(content),
+      ProtocolCommand::AddStrokesCommand(cmd) => cmd.rollback(content),
+      ProtocolCommand::AddHighlightCommand(cmd) => cmd.rollback(content),
+      ProtocolCommand::SetPagesCommand(cmd) => cmd.rollback(content),
+      ProtocolCommand::ArrangePagesCommand(cmd) => cmd.rollback(content),
+      ProtocolCommand::RemoveHighlightCommand(cmd) => cmd.rollback(content),
     }
   }
 }
@@ -102,3 +123,248 @@ impl RemoveStrokesCommand {
     }
   }
 }
+
+/// Adds many strokes in one undo step, e.g. imported ink.
+#[derive(Clone, Serialize, Deserialize)]
+pub enum AddStrokesCommand {
+  Invalid,
+  Before(Vec<Stroke>),
+  After(Vec<StrokeId>),
+}
+impl AddStrokesCommand {
+  #[cfg(not(target_arch = "wasm32"))]
+  pub fn new(strokes: Vec<Stroke>) -> ProtocolCommand {
+    ProtocolCommand::AddStrokesCommand(Self::Before(strokes))
+  }
+
+  pub fn execute(&mut self, mut content: ContentAccessMut) {
+    match mem::replace(self, Self::Invalid) {
+      Self::Before(strokes) => {
+        let ids = strokes.into_iter().map(|s| content.add_stroke(s)).collect();
+        *self = Self::After(ids);
+      }
+      _ => unreachable!(),
+    }
+  }
+
+  pub fn rollback(&mut self, mut content: ContentAccessMut) {
+    match mem::replace(self, Self::Invalid) {
+      Self::After(ids) => {
+        let strokes = ids
+          .into_iter()
+          .map(|id| content.remove_stroke(id))
+          .collect();
+        *self = Self::Before(strokes);
+      }
+      _ => unreachable!(),
+    }
+  }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub enum AddHighlightCommand {
+  Invalid,
+  Before(Box<PdfHighlight>),
+  After(HighlightId),
+}
+impl AddHighlightCommand {
+  pub fn new(highlight: PdfHighlight) -> ProtocolCommand {
+    ProtocolCommand::AddHighlightCommand(Self::Before(Box::new(highlight)))
+  }
+
+  pub fn execute(&mut self, mut content: ContentAccessMut) {
+    match mem::replace(self, Self::Invalid) {
+      Self::Before(highlight) => {
+        let id = content.add_highlight(*highlight);
+        *self = Self::After(id);
+      }
+      _ => unreachable!(),
+    }
+  }
+
+  pub fn rollback(&mut self, mut content: ContentAccessMut) {
+    match mem::replace(self, Self::Invalid) {
+      Self::After(id) => {
+        let highlight = content.remove_highlight(id);
+        *self = Self::Before(Box::new(highlight));
+      }
+      _ => unreachable!(),
+    }
+  }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub enum RemoveHighlightCommand {
+  Invalid,
+  Before(HighlightId),
+  After(Box<PdfHighlight>),
+}
+impl RemoveHighlightCommand {
+  pub fn new(id: HighlightId) -> ProtocolCommand {
+    ProtocolCommand::RemoveHighlightCommand(Self::Before(id))
+  }
+
+  pub fn execute(&mut self, mut content: ContentAccessMut) {
+    match mem::replace(self, Self::Invalid) {
+      Self::Before(id) => {
+        let highlight = content.remove_highlight(id);
+        *self = Self::After(Box::new(highlight));
+      }
+      _ => unreachable!(),
+    }
+  }
+
+  pub fn rollback(&mut self, mut content: ContentAccessMut) {
+    match mem::replace(self, Self::Invalid) {
+      Self::After(highlight) => {
+        let id = content.add_highlight(*highlight);
+        *self = Self::Before(id);
+      }
+      _ => unreachable!(),
+    }
+  }
+}
+
+/// Replaces the page list, leaving the strokes where they are.
+/// Holds the list that is not in the content, so executing and rolling back both swap.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SetPagesCommand {
+  pages: Box<PageList>,
+}
+impl SetPagesCommand {
+  #[cfg(not(target_arch = "wasm32"))]
+  pub fn new(pages: PageList) -> ProtocolCommand {
+    ProtocolCommand::SetPagesCommand(Self {
+      pages: Box::new(pages),
+    })
+  }
+
+  pub fn execute(&mut self, mut content: ContentAccessMut) {
+    content.swap_pages(&mut self.pages);
+  }
+
+  pub fn rollback(&mut self, mut content: ContentAccessMut) {
+    content.swap_pages(&mut self.pages);
+  }
+
+  #[cfg(not(target_arch = "wasm32"))]
+  pub fn pages_mut(&mut self) -> &mut PageList {
+    &mut self.pages
+  }
+}
+
+/// Replaces the page list and the layout, moving the strokes on the pages along with them
+/// and removing the strokes and highlights on removed pages.
+/// Holds what is not in the content, so executing and rolling back both swap.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ArrangePagesCommand {
+  pages: Box<PageList>,
+  layout: PdfLayout,
+  /// how far the strokes move on execute, rolling back moves them back
+  moved_strokes: Vec<(StrokeId, na::Vector2<f32>)>,
+  removed_strokes: RemoveStrokesCommand,
+  removed_highlights: Vec<RemoveHighlightCommand>,
+}
+impl ArrangePagesCommand {
+  /// A stroke is on the topmost page containing the center of its bounds.
+  /// Strokes next to the pages stay where they are.
+  /// The page sizes are the ones of the pdfs loaded by `pdf_manager`.
+  pub fn new(
+    content: ContentAccess,
+    pages: PageList,
+    layout: PdfLayout,
+    pdf_manager: &PdfManager,
+  ) -> ProtocolCommand {
+    let old_rects = pdf_manager.arranged_page_rects_canvas(content.pages(), content.layout());
+    let new_rects = pdf_manager.arranged_page_rects_canvas(&pages, &layout);
+    // the translation of each old page, none if it was removed
+    let translations: Vec<_> = content
+      .pages()
+      .pages()
+      .iter()
+      .zip(&old_rects)
+      .map(|(entry, old_rect)| {
+        let new = pages.pages().iter().position(|new| new.id == entry.id)?;
+        Some(new_rects[new].center - old_rect.center)
+      })
+      .collect();
+    let mut moved_strokes = Vec::new();
+    let mut removed_strokes = Vec::new();
+    for (id, stroke) in content.strokes() {
+      let center = stroke.bounds_canvas().center;
+      let Some(page) = old_rects.iter().rposition(|rect| rect.contains_point(center)) else {
+        continue;
+      };
+      match translations[page] {
+        Some(translation) if translation != na::Vector2::zeros() => {
+          moved_strokes.push((id, translation))
+        }
+        Some(_) => {}
+        None => removed_strokes.push(id),
+      }
+    }
+
+    let removed_highlights = content
+      .highlights()
+      .filter(|(_, highlight)| !pages.pages().iter().any(|new| new.id == highlight.page))
+      .map(|(id, _)| RemoveHighlightCommand::Before(id))
+      .collect();
+
+    ProtocolCommand::ArrangePagesCommand(Self {
+      pages: Box::new(pages),
+      layout,
+      moved_strokes,
+      removed_strokes: RemoveStrokesCommand::Before(removed_strokes),
+      removed_highlights,
+    })
+  }
+
+  /// Whether strokes or highlights are on each page, a stroke being on the page `new` takes.
+  #[cfg(not(target_arch = "wasm32"))]
+  pub fn annotated_pages(content: ContentAccess, pdf_manager: &PdfManager) -> Vec<bool> {
+    let rects = pdf_manager.arranged_page_rects_canvas(content.pages(), content.layout());
+    let mut is_annotated = vec![false; rects.len()];
+    for (_, stroke) in content.strokes() {
+      let center = stroke.bounds_canvas().center;
+      if let Some(page) = rects.iter().rposition(|rect| rect.contains_point(center)) {
+        is_annotated[page] = true;
+      }
+    }
+    let pages = content.pages().pages();
+    for (_, highlight) in content.highlights() {
+      if let Some(page) = pages.iter().position(|entry| entry.id == highlight.page) {
+        is_annotated[page] = true;
+      }
+    }
+    is_annotated
+  }
+
+  pub fn execute(&mut self, mut content: ContentAccessMut) {
+    self.removed_strokes.execute(content.reborrow());
+    for removed in &mut self.removed_highlights {
+      removed.execute(content.reborrow());
+    }
+    content.swap_pages(&mut self.pages);
+    content.swap_layout(&mut self.layout);
+    for &(id, translation) in &self.moved_strokes {
+      content.translate_stroke(id, translation);
+    }
+  }
+
+  pub fn rollback(&mut self, mut content: ContentAccessMut) {
+    content.swap_pages(&mut self.pages);
+    content.swap_layout(&mut self.layout);
+    for &(id, translation) in &self.moved_strokes {
+      content.translate_stroke(id, -translation);
+    }
+    for removed in self.removed_highlights.iter_mut().rev() {
+      removed.rollback(content.reborrow());
+    }
+    self.removed_strokes.rollback(content);
+  }
+
+  #[cfg(not(target_arch = "wasm32"))]
+  pub fn pages_mut(&mut self) -> &mut PageList {
+    &mut self.pages
+  }
+}
@@ -1,6 +1,9 @@
-use super::{command::ProtocolCommand, ContentManager};
+use super::command::ProtocolCommand;
+#[cfg(feature = "app")]
+use super::ContentManager;
 
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "app")]
 use std::f32::consts::TAU;
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -20,6 +23,27 @@ impl Default for Protocol {
   }
 }
 impl Protocol {
+  /// the commands in the history, including undone ones
+  #[cfg(not(target_arch = "wasm32"))]
+  pub fn nsteps(&self) -> usize {
+    self.nodes.len() - 1
+  }
+
+  /// Points the page lists of the history to other pdfs, like `Content::rename_pdf_sources`.
+  #[cfg(not(target_arch = "wasm32"))]
+  pub fn rename_pdf_sources(
+    &mut self,
+    rename: &impl Fn(&crate::pdf::PdfSource) -> Option<crate::pdf::PdfSource>,
+  ) {
+    for node in &mut self.nodes {
+      match &mut node.command {
+        ProtocolCommand::SetPagesCommand(command) => command.pages_mut().rename_sources(rename),
+        ProtocolCommand::ArrangePagesCommand(command) => command.pages_mut().rename_sources(rename),
+        _ => {}
+      }
+    }
+  }
+
   pub(super) fn node_mut(&mut self, id: ProtocolNodeId) -> &mut ProtocolNode {
     self.nodes.get_mut(usize::try_from(id.0).unwrap()).unwrap()
   }
@@ -78,8 +102,10 @@ impl ProtocolNode {
   }
 }
 
+#[cfg(feature = "app")]
 #[derive(Default)]
 pub struct ProtocolUi {}
+#[cfg(feature = "app")]
 impl ProtocolUi {
   pub fn ui(&mut self, ui: &mut egui::Ui, manager: &mut ContentManager) {
     let size = egui::Vec2::splat(300.0);
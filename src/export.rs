@@ -0,0 +1,106 @@
+mod inkml;
+mod png;
+mod svg;
+
+pub use self::{
+  inkml::export_inkml,
+  png::{export_png, PngOptions},
+  svg::{export_svg, SvgOptions},
+};
+
+use crate::{
+  content::{access::ContentAccess, StrokeId},
+  gfx,
+  math::Rect,
+  pdf::PdfManager,
+  stroke::Stroke,
+};
+
+/// The part of the canvas that gets exported.
+#[derive(Debug, Clone)]
+pub enum ExportRegion {
+  /// all strokes and pages
+  All,
+  /// only these strokes, e.g. the selection
+  #[cfg(feature = "app")]
+  Strokes(Vec<StrokeId>),
+  /// the page with this index and everything on it
+  #[cfg(feature = "app")]
+  Page(usize),
+  /// everything inside the rect on the canvas
+  #[cfg(feature = "app")]
+  Rect(Rect),
+}
+
+impl ExportRegion {
+  /// The exported area on the canvas, `None` if there is nothing to export.
+  pub fn bounds_canvas(&self, content: &ContentAccess, pdf_manager: &PdfManager) -> Option<Rect> {
+    match self {
+      ExportRegion::All => {
+        let strokes = content
+          .strokes()
+          .map(|(_, stroke)| stroke_bounds_canvas(stroke));
+        strokes
+          .chain(pdf_manager.page_rects_canvas())
+          .reduce(|a, b| union(&a, &b))
+      }
+      #[cfg(feature = "app")]
+      ExportRegion::Strokes(ids) => content
+        .strokes()
+        .filter(|(id, _)| ids.contains(id))
+        .map(|(_, stroke)| stroke_bounds_canvas(stroke))
+        .reduce(|a, b| union(&a, &b)),
+      #[cfg(feature = "app")]
+      ExportRegion::Page(page) => pdf_manager.page_rects_canvas().get(*page).copied(),
+      #[cfg(feature = "app")]
+      ExportRegion::Rect(rect) => Some(*rect),
+    }
+  }
+
+  /// whether the stroke is exported, given it lies within the bounds
+  #[cfg_attr(not(feature = "app"), allow(unused_variables))]
+  pub fn includes_stroke(&self, id: StrokeId) -> bool {
+    match self {
+      #[cfg(feature = "app")]
+      ExportRegion::Strokes(ids) => ids.contains(&id),
+      _ => true,
+    }
+  }
+
+  /// whether highlights and pages are exported
+  pub fn includes_pages(&self) -> bool {
+    match self {
+      #[cfg(feature = "app")]
+      ExportRegion::Strokes(_) => false,
+      _ => true,
+    }
+  }
+}
+
+/// encodes rgba bytes as png
+pub fn encode_png(size: [usize; 2], rgba: &[u8]) -> Vec<u8> {
+  let mut data = Vec::new();
+  let mut encoder = ::png::Encoder::new(&mut data, size[0] as u32, size[1] as u32);
+  encoder.set_color(::png::ColorType::Rgba);
+  encoder.set_depth(::png::BitDepth::Eight);
+  let mut writer = encoder.write_header().unwrap();
+  writer.write_image_data(rgba).unwrap();
+  writer.finish().unwrap();
+  data
+}
+
+/// the bounds of the stroke including its width
+pub fn stroke_bounds_canvas(stroke: &Stroke) -> Rect {
+  let bounds = stroke.bounds_canvas();
+  let margin = gfx::stroke::stroke_width_canvas(stroke) / 2.0;
+  Rect::from_extents_half_center(bounds.extents_half.add_scalar(margin), bounds.center)
+}
+
+/// the axis aligned rect around both rects
+fn union(a: &Rect, b: &Rect) -> Rect {
+  let (a_min, a_max) = a.aabb();
+  let (b_min, b_max) = b.aabb();
+  let min = a_min.inf(&b_min);
+  let max = a_max.sup(&b_max);
+  Rect::from_size_min(max - min, min)
+}
@@ -0,0 +1,109 @@
+use super::{stroke_bounds_canvas, ExportRegion};
+
+use crate::{
+  content::access::ContentAccess,
+  gfx::stroke,
+  pdf::{PdfManager, CANVAS_UNITS_PER_PDF_POINT},
+  util,
+};
+
+use std::fmt::Write;
+
+const MM_PER_POINT: f32 = 25.4 / 72.0;
+
+/// Writes the strokes of the region as W3C InkML traces.
+///
+/// The coordinates are the canvas positions in millimeters, so the ink lands in the same place
+/// when imported again. Consecutive strokes with the same color and width share a trace group.
+/// A stroke has one width, that of its brush, so the pressure is full at every point.
+/// There is no timing channel, strokes do not keep when they were drawn.
+/// `None` if there are no strokes in the region.
+pub fn export_inkml(
+  region: &ExportRegion,
+  content: ContentAccess,
+  pdf_manager: &PdfManager,
+) -> Option<String> {
+  let bounds_canvas = region.bounds_canvas(&content, pdf_manager)?;
+  let to_mm = |length_canvas: f32| length_canvas / CANVAS_UNITS_PER_PDF_POINT * MM_PER_POINT;
+
+  let mut brushes: Vec<([u8; 4], f32)> = Vec::new();
+  let mut groups: Vec<(usize, String)> = Vec::new();
+  for (id, stroke) in content.strokes() {
+    if !region.includes_stroke(id) || !stroke_bounds_canvas(stroke).intersects(&bounds_canvas) {
+      continue;
+    }
+    let brush = (
+      util::rgba_palette2srgb8(stroke.color()),
+      to_mm(stroke::stroke_width_canvas(stroke)),
+    );
+    let ibrush = match brushes.iter().position(|b| *b == brush) {
+      Some(ibrush) => ibrush,
+      None => {
+        brushes.push(brush);
+        brushes.len() - 1
+      }
+    };
+    if groups.last().map_or(true, |(last, _)| *last != ibrush) {
+      groups.push((ibrush, String::new()));
+    }
+    let (_, traces) = groups.last_mut().unwrap();
+
+    let points: Vec<_> = stroke
+      .points()
+      .iter()
+      .map(|point| format!("{:.3} {:.3} 1", to_mm(point.x), to_mm(point.y)))
+      .collect();
+    writeln!(traces, "    <trace>{}</trace>", points.join(", ")).unwrap();
+  }
+  if groups.is_empty() {
+    return None;
+  }
+
+  let mut inkml = String::new();
+  writeln!(inkml, r#"<?xml version="1.0" encoding="UTF-8"?>"#).unwrap();
+  writeln!(inkml, r#"<ink xmlns="http://www.w3.org/2003/InkML">"#).unwrap();
+  writeln!(inkml, "  <definitions>").unwrap();
+  for (ibrush, ([r, g, b, a], width)) in brushes.iter().enumerate() {
+    writeln!(inkml, r#"    <brush xml:id="brush{ibrush}">"#).unwrap();
+    writeln!(
+      inkml,
+      r##"      <brushProperty name="color" value="#{r:02x}{g:02x}{b:02x}"/>"##
+    )
+    .unwrap();
+    writeln!(
+      inkml,
+      r#"      <brushProperty name="width" value="{width:.3}" units="mm"/>"#
+    )
+    .unwrap();
+    writeln!(
+      inkml,
+      r#"      <brushProperty name="transparency" value="{}"/>"#,
+      255 - a
+    )
+    .unwrap();
+    writeln!(inkml, "    </brush>").unwrap();
+  }
+  writeln!(inkml, "  </definitions>").unwrap();
+  writeln!(inkml, "  <context>").unwrap();
+  writeln!(inkml, "    <traceFormat>").unwrap();
+  writeln!(
+    inkml,
+    r#"      <channel name="X" type="decimal" units="mm"/>"#
+  )
+  .unwrap();
+  writeln!(
+    inkml,
+    r#"      <channel name="Y" type="decimal" units="mm"/>"#
+  )
+  .unwrap();
+  writeln!(inkml, r#"      <channel name="F" type="decimal" max="1"/>"#).unwrap();
+  writeln!(inkml, "    </traceFormat>").unwrap();
+  writeln!(inkml, "  </context>").unwrap();
+  for (ibrush, traces) in groups {
+    writeln!(inkml, r##"  <traceGroup brushRef="#brush{ibrush}">"##).unwrap();
+    inkml.push_str(&traces);
+    writeln!(inkml, "  </traceGroup>").unwrap();
+  }
+  writeln!(inkml, "</ink>").unwrap();
+  Some(inkml)
+}
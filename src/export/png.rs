@@ -0,0 +1,172 @@
+use super::{stroke_bounds_canvas, ExportRegion};
+
+use crate::{
+  content::access::ContentAccess,
+  gfx::stroke::StrokeTessellator,
+  pdf::{PdfManager, CANVAS_UNITS_PER_PDF_POINT},
+  util,
+};
+
+use tiny_skia::{
+  BlendMode, FillRule, Paint, PathBuilder, Pixmap, PixmapPaint, PixmapRef, Transform,
+};
+
+const POINTS_PER_INCH: f32 = 72.0;
+
+#[derive(Debug, Clone)]
+pub struct PngOptions {
+  pub region: ExportRegion,
+  pub dpi: f32,
+  /// draws the pdf pages below the strokes, otherwise the background is transparent
+  pub pdf_background: bool,
+}
+
+/// Rasterizes the region on the cpu and encodes it as png.
+/// `None` if there is nothing to export or the image would be too large.
+pub fn export_png(
+  options: &PngOptions,
+  content: ContentAccess,
+  pdf_manager: &mut PdfManager,
+) -> Option<Vec<u8>> {
+  rasterize(options, content, pdf_manager)?.encode_png().ok()
+}
+
+/// Software renderer independent of the gpu and a window.
+///
+/// The strokes are filled from the same tessellation as on the gpu. All triangles of a stroke
+/// are one anti aliased path, so there are no seams between them and overlaps of translucent
+/// strokes are blended once.
+pub fn rasterize(
+  options: &PngOptions,
+  content: ContentAccess,
+  pdf_manager: &mut PdfManager,
+) -> Option<Pixmap> {
+  let region = &options.region;
+  let bounds_canvas = region.bounds_canvas(&content, pdf_manager)?;
+  let (min_canvas, max_canvas) = bounds_canvas.aabb();
+  let pixels_per_canvas_unit = options.dpi / POINTS_PER_INCH / CANVAS_UNITS_PER_PDF_POINT;
+  let to_pixels = |point_canvas: na::Point2<f32>| {
+    na::Point2::from((point_canvas - min_canvas) * pixels_per_canvas_unit)
+  };
+  let size = ((max_canvas - min_canvas) * pixels_per_canvas_unit).map(|e| e.ceil() as u32);
+  let mut pixmap = Pixmap::new(size.x.max(1), size.y.max(1))?;
+
+  let page_rects_canvas = pdf_manager.page_rects_canvas();
+  if options.pdf_background && region.includes_pages() {
+    for (page, page_rect_canvas) in page_rects_canvas.iter().enumerate() {
+      let is_exported = match region {
+        #[cfg(feature = "app")]
+        ExportRegion::Page(exported) => page == *exported,
+        _ => page_rect_canvas.intersects(&bounds_canvas),
+      };
+      if !is_exported {
+        continue;
+      }
+      let (page_min, page_max) = page_rect_canvas.aabb();
+      let (page_min, page_max) = (to_pixels(page_min), to_pixels(page_max));
+      let page_size = page_max - page_min;
+      let width_pixels = page_size.x.ceil().clamp(1.0, u16::MAX.into()) as u16;
+      match pdf_manager.render_page(page, width_pixels) {
+        Some(([width, height], rgba)) => {
+          // pdfium renders opaque, so the bytes are premultiplied already
+          let Some(image) = PixmapRef::from_bytes(&rgba, width as u32, height as u32) else {
+            continue;
+          };
+          let scale = page_size.component_div(&na::vector![width as f32, height as f32]);
+          let transform = Transform::from_row(scale.x, 0.0, 0.0, scale.y, page_min.x, page_min.y);
+          pixmap.draw_pixmap(0, 0, image, &PixmapPaint::default(), transform, None);
+        }
+        // blank pages are plain paper
+        None => {
+          let mut paint = Paint::default();
+          paint.set_color_rgba8(255, 255, 255, 255);
+          if let Some(rect) =
+            tiny_skia::Rect::from_ltrb(page_min.x, page_min.y, page_max.x, page_max.y)
+          {
+            pixmap.fill_rect(rect, &paint, Transform::identity(), None);
+          }
+        }
+      }
+    }
+  }
+
+  if region.includes_pages() {
+    for (_, highlight) in content.highlights() {
+      let Some(page_rect_canvas) = pdf_manager
+        .page_index(highlight.page)
+        .and_then(|page| page_rects_canvas.get(page))
+      else {
+        continue;
+      };
+      let [r, g, b, a] = util::rgba_palette2srgb8(highlight.color);
+      let mut paint = Paint::default();
+      paint.set_color_rgba8(r, g, b, a);
+      paint.blend_mode = BlendMode::Multiply;
+      paint.anti_alias = true;
+      for rect_canvas in highlight.rects_canvas(page_rect_canvas) {
+        let vertices = rect_canvas.vertices().map(to_pixels);
+        fill_polygon(&mut pixmap, &vertices, &paint);
+      }
+    }
+  }
+
+  let mut tessellator = StrokeTessellator::default();
+  for (id, stroke) in content.strokes() {
+    if !region.includes_stroke(id) || !stroke_bounds_canvas(stroke).intersects(&bounds_canvas) {
+      continue;
+    }
+    let [r, g, b, a] = util::rgba_palette2srgb8(stroke.color());
+    let mut paint = Paint::default();
+    paint.set_color_rgba8(r, g, b, a);
+    paint.anti_alias = true;
+
+    let mesh = tessellator.tessellate(stroke);
+    let vertices: Vec<_> = mesh
+      .vertices()
+      .iter()
+      .map(|vertex| {
+        // offset like the stroke shader does
+        let position = na::Point2::from(vertex.position)
+          + na::Vector2::from(vertex.normal) * vertex.stroke_width;
+        to_pixels(position)
+      })
+      .collect();
+    let mut builder = PathBuilder::new();
+    for triangle in mesh.indices().chunks_exact(3) {
+      let [p0, mut p1, mut p2] = [0, 1, 2].map(|i| vertices[triangle[i] as usize]);
+      // wind all triangles the same way, so overlapping ones do not cancel out
+      if (p1 - p0).perp(&(p2 - p0)) < 0.0 {
+        std::mem::swap(&mut p1, &mut p2);
+      }
+      builder.move_to(p0.x, p0.y);
+      builder.line_to(p1.x, p1.y);
+      builder.line_to(p2.x, p2.y);
+      builder.close();
+    }
+    // degenerate strokes have no path
+    if let Some(path) = builder.finish() {
+      pixmap.fill_path(
+        &path,
+        &paint,
+        FillRule::Winding,
+        Transform::identity(),
+        None,
+      );
+    }
+  }
+
+  Some(pixmap)
+}
+
+fn fill_polygon(pixmap: &mut Pixmap, vertices: &[na::Point2<f32>], paint: &Paint) {
+  let mut builder = PathBuilder::new();
+  builder.move_to(vertices[0].x, vertices[0].y);
+  for vertex in &vertices[1..] {
+    builder.line_to(vertex.x, vertex.y);
+  }
+  builder.close();
+  // degenerate polygons have no path
+  if let Some(path) = builder.finish() {
+    pixmap.fill_path(&path, paint, FillRule::Winding, Transform::identity(), None);
+  }
+}
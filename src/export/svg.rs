@@ -0,0 +1,132 @@
+use super::{encode_png, stroke_bounds_canvas, ExportRegion};
+
+use crate::{
+  content::access::ContentAccess,
+  gfx::stroke,
+  pdf::{PdfManager, CANVAS_UNITS_PER_PDF_POINT},
+  util,
+};
+
+use std::fmt::Write;
+
+/// resolution of the embedded pdf pages
+const BACKGROUND_PIXELS_PER_POINT: f32 = 2.0;
+
+#[derive(Debug, Clone)]
+pub struct SvgOptions {
+  pub region: ExportRegion,
+  /// embeds the pdf pages as images below the strokes
+  pub pdf_background: bool,
+}
+
+/// Writes the region as svg document in pdf points, with every stroke as a path.
+/// `None` if there is nothing to export.
+pub fn export_svg(
+  options: &SvgOptions,
+  content: ContentAccess,
+  pdf_manager: &mut PdfManager,
+) -> Option<String> {
+  let region = &options.region;
+  let bounds_canvas = region.bounds_canvas(&content, pdf_manager)?;
+  let (min_canvas, max_canvas) = bounds_canvas.aabb();
+  let to_points = |point_canvas: na::Point2<f32>| {
+    na::Point2::from((point_canvas - min_canvas) / CANVAS_UNITS_PER_PDF_POINT)
+  };
+  let size = (max_canvas - min_canvas) / CANVAS_UNITS_PER_PDF_POINT;
+
+  let mut svg = String::new();
+  writeln!(svg, r#"<?xml version="1.0" encoding="UTF-8"?>"#).unwrap();
+  writeln!(
+    svg,
+    r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w:.2}pt" height="{h:.2}pt" viewBox="0 0 {w:.2} {h:.2}">"#,
+    w = size.x,
+    h = size.y,
+  )
+  .unwrap();
+
+  let page_rects_canvas = pdf_manager.page_rects_canvas();
+  if options.pdf_background && region.includes_pages() {
+    for (page, page_rect_canvas) in page_rects_canvas.iter().enumerate() {
+      let is_exported = match region {
+        #[cfg(feature = "app")]
+        ExportRegion::Page(exported) => page == *exported,
+        _ => page_rect_canvas.intersects(&bounds_canvas),
+      };
+      if !is_exported {
+        continue;
+      }
+      let (page_min, page_max) = page_rect_canvas.aabb();
+      let (page_min, page_max) = (to_points(page_min), to_points(page_max));
+      let page_size = page_max - page_min;
+      let width_pixels = (page_size.x * BACKGROUND_PIXELS_PER_POINT) as u16;
+      match pdf_manager.render_page(page, width_pixels) {
+        Some((size, rgba)) => writeln!(
+          svg,
+          r#"<image x="{:.2}" y="{:.2}" width="{:.2}" height="{:.2}" preserveAspectRatio="none" href="data:image/png;base64,{}"/>"#,
+          page_min.x,
+          page_min.y,
+          page_size.x,
+          page_size.y,
+          base64::encode(encode_png(size, &rgba)),
+        ),
+        // blank pages are plain paper
+        None => writeln!(
+          svg,
+          r#"<rect x="{:.2}" y="{:.2}" width="{:.2}" height="{:.2}" fill="white"/>"#,
+          page_min.x, page_min.y, page_size.x, page_size.y,
+        ),
+      }
+      .unwrap();
+    }
+  }
+
+  if region.includes_pages() {
+    for (_, highlight) in content.highlights() {
+      let Some(page_rect_canvas) = pdf_manager
+        .page_index(highlight.page)
+        .and_then(|page| page_rects_canvas.get(page))
+      else {
+        continue;
+      };
+      let [r, g, b, a] = util::rgba_palette2srgb8(highlight.color);
+      for rect_canvas in highlight.rects_canvas(page_rect_canvas) {
+        let mut points = String::new();
+        for vertex in rect_canvas.vertices() {
+          let vertex = to_points(vertex);
+          write!(points, "{:.2},{:.2} ", vertex.x, vertex.y).unwrap();
+        }
+        writeln!(
+          svg,
+          r##"<polygon points="{}" fill="#{r:02x}{g:02x}{b:02x}" fill-opacity="{:.3}" style="mix-blend-mode:multiply"/>"##,
+          points.trim_end(),
+          f32::from(a) / 255.0,
+        )
+        .unwrap();
+      }
+    }
+  }
+
+  for (id, stroke) in content.strokes() {
+    if !region.includes_stroke(id) || !stroke_bounds_canvas(stroke).intersects(&bounds_canvas) {
+      continue;
+    }
+    let mut path = String::new();
+    for (i, point) in stroke.points().iter().enumerate() {
+      let point = to_points(*point);
+      let command = if i == 0 { 'M' } else { 'L' };
+      write!(path, "{command}{:.2} {:.2} ", point.x, point.y).unwrap();
+    }
+    let [r, g, b, a] = util::rgba_palette2srgb8(stroke.color());
+    writeln!(
+      svg,
+      r##"<path d="{}" fill="none" stroke="#{r:02x}{g:02x}{b:02x}" stroke-opacity="{:.3}" stroke-width="{:.2}" stroke-linecap="round" stroke-linejoin="round"/>"##,
+      path.trim_end(),
+      f32::from(a) / 255.0,
+      stroke::stroke_width_canvas(stroke) / CANVAS_UNITS_PER_PDF_POINT,
+    )
+    .unwrap();
+  }
+
+  writeln!(svg, "</svg>").unwrap();
+  Some(svg)
+}
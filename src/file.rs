@@ -1,34 +1,300 @@
-use crate::content::{protocol::Protocol, Content};
+#[cfg(all(feature = "app", not(target_arch = "wasm32")))]
+pub mod autosave;
+mod binary;
+mod migration;
+mod tree;
+
+pub use self::binary::serde_points;
+
+use crate::{
+  content::{protocol::Protocol, Content},
+  pdf::PdfSource,
+};
 
 use serde::{Deserialize, Serialize};
 use std::{
   borrow::Cow,
   ffi::OsStr,
-  fs,
-  io::{Read, Write},
-  path::Path,
+  fmt, fs,
+  io::{self, Write},
+  path::{Path, PathBuf},
 };
 
+/// the savefile version written by this build
+pub const SAVEFILE_VERSION: u32 = 1;
+/// The first line of a savefile, followed by the version.
+/// A ron comment, so the savefile stays valid ron.
+const HEADER_PREFIX: &str = "// carveout savefile version ";
+
 #[derive(Serialize, Deserialize)]
 pub struct Savefile {
   pub content: Content,
   pub protocol: Protocol,
+  /// the pdfs of a compact savefile that are missing at their sources
+  #[serde(skip)]
+  pub embedded_pdfs: Vec<EmbeddedPdf>,
 }
 
-pub fn load(file_path: &Path) -> Savefile {
-  let mut file = fs::File::open(file_path).unwrap();
-  let mut data_string = String::new();
-  file.read_to_string(&mut data_string).unwrap();
-  ron::from_str(&data_string).unwrap()
+impl Savefile {
+  pub fn new(content: Content, protocol: Protocol) -> Self {
+    Self {
+      content,
+      protocol,
+      embedded_pdfs: Vec::new(),
+    }
+  }
+
+  /// Writes the embedded pdfs into the cache and points the pages to the copies,
+  /// which is needed before the pages are shown or exported.
+  pub fn extract_pdfs(&mut self) -> Result<(), FileError> {
+    binary::extract_pdfs(self)
+  }
 }
 
-pub fn save<'a>(savefile: &Savefile, file_path: impl Into<Cow<'a, Path>>) {
-  let mut file_path = file_path.into();
-  if file_path.extension() != Some(OsStr::new("co")) {
-    file_path.to_mut().set_extension("co");
+/// a pdf stored in a compact savefile
+#[derive(Clone)]
+pub struct EmbeddedPdf {
+  pub source: PdfSource,
+  pub data: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub enum FileError {
+  Io(io::Error),
+  /// the file is not a valid savefile
+  Parse {
+    message: String,
+    line: usize,
+    column: usize,
+  },
+  /// the file was written by a newer version
+  Version {
+    found: u32,
+    supported: u32,
+  },
+  Serialize(ron::Error),
+  /// the compact savefile is broken
+  Binary(binary::Error),
+}
+
+impl fmt::Display for FileError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      FileError::Io(error) => write!(f, "{error}"),
+      FileError::Parse {
+        message,
+        line,
+        column,
+      } => write!(
+        f,
+        "invalid savefile at line {line}, column {column}: {message}"
+      ),
+      FileError::Version { found, supported } => write!(
+        f,
+        "the savefile has version {found}, but only versions up to {supported} are supported"
+      ),
+      FileError::Serialize(error) => write!(f, "failed to serialize: {error}"),
+      FileError::Binary(error) => write!(f, "invalid compact savefile: {error}"),
+    }
+  }
+}
+
+impl std::error::Error for FileError {}
+
+impl From<io::Error> for FileError {
+  fn from(error: io::Error) -> Self {
+    Self::Io(error)
+  }
+}
+
+impl From<ron::error::SpannedError> for FileError {
+  fn from(error: ron::error::SpannedError) -> Self {
+    Self::Parse {
+      message: error.code.to_string(),
+      line: error.position.line,
+      column: error.position.col,
+    }
+  }
+}
+
+impl From<ron::Error> for FileError {
+  fn from(error: ron::Error) -> Self {
+    Self::Serialize(error)
   }
+}
+
+impl From<binary::Error> for FileError {
+  fn from(error: binary::Error) -> Self {
+    Self::Binary(error)
+  }
+}
+
+/// how a savefile is written, both are loaded
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SaveFormat {
+  /// readable and diffable
+  #[default]
+  Ron,
+  /// small and fast, with the pdfs embedded
+  Binary,
+}
+
+/// Loads a savefile of the current or any older version, in either format.
+pub fn load(file_path: &Path) -> Result<Savefile, FileError> {
+  let data = fs::read(file_path)?;
+  if binary::is_binary(&data) {
+    return binary::from_bytes(&data);
+  }
+  let data_string =
+    String::from_utf8(data).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+  let (version, ron) = match parse_header(&data_string) {
+    Some(header) => header,
+    // from before the header
+    None => (0, data_string.as_str()),
+  };
+  match version {
+    SAVEFILE_VERSION => Ok(ron::from_str(ron)?),
+    _ if version < SAVEFILE_VERSION => Ok(ron::from_str(&migration::migrate(ron, version)?)?),
+    _ => Err(FileError::Version {
+      found: version,
+      supported: SAVEFILE_VERSION,
+    }),
+  }
+}
+
+/// the format of the savefile, without loading it
+#[cfg(not(target_arch = "wasm32"))]
+pub fn format_of(file_path: &Path) -> Result<SaveFormat, FileError> {
+  use std::io::Read;
+
+  let mut magic = Vec::with_capacity(binary::MAGIC.len());
+  fs::File::open(file_path)?
+    .take(binary::MAGIC.len() as u64)
+    .read_to_end(&mut magic)?;
+  match binary::is_binary(&magic) {
+    true => Ok(SaveFormat::Binary),
+    false => Ok(SaveFormat::Ron),
+  }
+}
+
+/// Returns where it was saved, which has the extension added if it was missing.
+pub fn save<'a>(
+  savefile: &Savefile,
+  file_path: impl Into<Cow<'a, Path>>,
+  format: SaveFormat,
+) -> Result<PathBuf, FileError> {
+  let file_path = with_extension(file_path, "co");
+  let data = match format {
+    SaveFormat::Ron => {
+      let pretty_config = ron::ser::PrettyConfig::default();
+      let data_string = ron::ser::to_string_pretty(savefile, pretty_config)?;
+      format!("{HEADER_PREFIX}{SAVEFILE_VERSION}\n{data_string}").into_bytes()
+    }
+    SaveFormat::Binary => binary::to_bytes(savefile, true)?,
+  };
+  write_atomic(&file_path, &data)?;
+  Ok(file_path.into_owned())
+}
+
+/// the version and the ron after the header
+fn parse_header(data_string: &str) -> Option<(u32, &str)> {
+  let (header, ron) = data_string.split_once('\n')?;
+  let version = header
+    .trim_end()
+    .strip_prefix(HEADER_PREFIX)?
+    .parse()
+    .ok()?;
+  Some((version, ron))
+}
+
+/// The session of the last run, `None` if there is none.
+#[cfg(all(feature = "app", not(target_arch = "wasm32")))]
+pub fn load_session() -> Result<Option<crate::session::Session>, FileError> {
+  match fs::read_to_string(session_path()) {
+    Ok(data_string) => Ok(Some(ron::from_str(&data_string)?)),
+    Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+    Err(error) => Err(error.into()),
+  }
+}
+
+#[cfg(all(feature = "app", not(target_arch = "wasm32")))]
+pub fn save_session(session: &crate::session::Session) -> Result<(), FileError> {
+  let file_path = session_path();
+  fs::create_dir_all(file_path.parent().unwrap())?;
   let pretty_config = ron::ser::PrettyConfig::default();
-  let data_string = ron::ser::to_string_pretty(savefile, pretty_config).unwrap();
-  let mut file = fs::File::create(file_path).unwrap();
-  file.write_all(data_string.as_bytes()).unwrap();
+  let data_string = ron::ser::to_string_pretty(session, pretty_config)?;
+  write_atomic(&file_path, data_string.as_bytes())
+}
+
+#[cfg(all(feature = "app", not(target_arch = "wasm32")))]
+fn session_path() -> PathBuf {
+  crate::util::APP_DIRS.config_dir().join("session.ron")
+}
+
+pub fn save_pdf<'a>(data: &[u8], file_path: impl Into<Cow<'a, Path>>) -> Result<(), FileError> {
+  write_atomic(&with_extension(file_path, "pdf"), data)
+}
+
+pub fn save_svg<'a>(svg: &str, file_path: impl Into<Cow<'a, Path>>) -> Result<(), FileError> {
+  write_atomic(&with_extension(file_path, "svg"), svg.as_bytes())
+}
+
+pub fn save_inkml<'a>(inkml: &str, file_path: impl Into<Cow<'a, Path>>) -> Result<(), FileError> {
+  write_atomic(&with_extension(file_path, "inkml"), inkml.as_bytes())
+}
+
+pub fn save_png<'a>(data: &[u8], file_path: impl Into<Cow<'a, Path>>) -> Result<(), FileError> {
+  write_atomic(&with_extension(file_path, "png"), data)
+}
+
+/// Keeps a pdf in the cache and returns the path of the copy.
+/// The copy is named after the contents, so storing the same pdf again reuses it.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn cache_pdf(stem: &str, data: &[u8]) -> Result<PathBuf, FileError> {
+  use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+  };
+
+  let mut hasher = DefaultHasher::new();
+  data.hash(&mut hasher);
+  let dir = crate::util::APP_DIRS.cache_dir().join("pdfs");
+  let copy = dir.join(format!("{stem}-{:016x}.pdf", hasher.finish()));
+  if !copy.exists() {
+    fs::create_dir_all(&dir)?;
+    write_atomic(&copy, data)?;
+  }
+  Ok(copy)
+}
+
+/// Writes into a temporary file next to the target and renames it,
+/// so the target is never left half written.
+fn write_atomic(file_path: &Path, data: &[u8]) -> Result<(), FileError> {
+  let temp_path = temp_path(file_path);
+  let result = (|| {
+    let mut file = fs::File::create(&temp_path)?;
+    file.write_all(data)?;
+    file.sync_all()?;
+    fs::rename(&temp_path, file_path)
+  })();
+  if result.is_err() {
+    let _ = fs::remove_file(&temp_path);
+  }
+  Ok(result?)
+}
+
+/// `dir/.name.tmp` for `dir/name`
+fn temp_path(file_path: &Path) -> PathBuf {
+  let mut temp_name = OsStr::new(".").to_owned();
+  temp_name.push(file_path.file_name().unwrap_or_default());
+  temp_name.push(".tmp");
+  file_path.with_file_name(temp_name)
+}
+
+fn with_extension<'a>(file_path: impl Into<Cow<'a, Path>>, extension: &str) -> Cow<'a, Path> {
+  let mut file_path = file_path.into();
+  if file_path.extension() != Some(OsStr::new(extension)) {
+    file_path.to_mut().set_extension(extension);
+  }
+  file_path
 }
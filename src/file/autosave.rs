@@ -0,0 +1,390 @@
+//! Keeps a copy of the open document in the data dir until it is saved,
+//! so the changes survive crashes and quitting without saving.
+//!
+//! Every instance writes its own session file. The ones left over are the unsaved sessions,
+//! offered for restoring on the next start.
+//!
+//! A session file is a compact savefile followed by the changes made since, which are appended
+//! as they happen. Once they add up, or the document is replaced, it is written anew.
+
+use super::{binary, write_atomic, FileError, Savefile};
+
+use crate::{
+  content::{ContentManager, Journal, JournalEntry},
+  util,
+};
+
+use instant::{Duration, Instant};
+use once_cell::sync::Lazy;
+use std::{
+  fs,
+  io::{self, Write},
+  mem, panic,
+  path::{Path, PathBuf},
+  sync::{Condvar, Mutex, MutexGuard, PoisonError},
+  thread::{self, JoinHandle},
+};
+
+/// how long writing the session file anew waits for the last time
+const SNAPSHOT_INTERVAL: Duration = Duration::from_secs(10);
+/// the changes appended before the session file is written anew
+const MAX_JOURNAL_LEN: usize = 1000;
+const WORKER_NAME: &str = "autosave_worker";
+
+/// the session file of this instance
+static SESSION_PATH: Lazy<PathBuf> = Lazy::new(|| {
+  let name = format!(
+    "{}-{}.co",
+    chrono::Local::now().format("%Y%m%dT%H%M%S"),
+    std::process::id()
+  );
+  sessions_dir().join(name)
+});
+
+fn sessions_dir() -> PathBuf {
+  util::APP_DIRS.data_dir().join("sessions")
+}
+
+/// A session file another instance left behind.
+pub struct UnsavedSession {
+  pub path: PathBuf,
+  pub modified: chrono::DateTime<chrono::Local>,
+}
+
+/// The unsaved sessions, the newest first.
+/// Skips the ones of instances that are still running.
+pub fn unsaved_sessions() -> Vec<UnsavedSession> {
+  let Ok(entries) = fs::read_dir(sessions_dir()) else {
+    return Vec::new();
+  };
+  let mut sessions: Vec<_> = entries
+    .flatten()
+    .filter_map(|entry| {
+      let path = entry.path();
+      let is_session = path
+        .extension()
+        .map_or(false, |extension| extension == "co");
+      if !is_session || path == *SESSION_PATH || is_live(&path) {
+        return None;
+      }
+      let modified = entry.metadata().ok()?.modified().ok()?;
+      Some(UnsavedSession {
+        path,
+        modified: modified.into(),
+      })
+    })
+    .collect();
+  sessions.sort_by(|a, b| b.modified.cmp(&a.modified));
+  sessions
+}
+
+/// Loads the session, which becomes the session of this instance.
+pub fn restore(session: &UnsavedSession) -> Result<Savefile, FileError> {
+  if is_live(&session.path) {
+    let message = "the document is open in another window";
+    return Err(io::Error::new(io::ErrorKind::Other, message).into());
+  }
+  let data = fs::read(&session.path)?;
+  let (savefile, journal) = binary::from_bytes_with_journal::<Vec<JournalEntry>>(&data)?;
+  let mut content_manager = ContentManager::default();
+  content_manager.replace(savefile.content, savefile.protocol);
+  for entry in journal.into_iter().flatten() {
+    content_manager.replay(entry);
+  }
+
+  let _running = lock(&SHARED.running);
+  let mut queue = lock(&SHARED.queue);
+  fs::rename(&session.path, &*SESSION_PATH)?;
+  // what is waiting belongs to the session file that was just replaced
+  queue.job = None;
+  queue.journal.clear();
+  queue.has_session = true;
+  queue.needs_snapshot = false;
+  queue.journal_len = 0;
+
+  let (content, protocol) = content_manager.clone();
+  Ok(Savefile::new(content, protocol))
+}
+
+pub fn discard(session: &UnsavedSession) -> Result<(), FileError> {
+  Ok(fs::remove_file(&session.path)?)
+}
+
+/// Whether the instance that owns the session file is still running.
+/// Its process id is in the file name, see `SESSION_PATH`.
+/// The id may be reused by now, which hides the session until that process ends.
+fn is_live(path: &Path) -> bool {
+  path
+    .file_stem()
+    .and_then(|stem| stem.to_str()?.rsplit('-').next()?.parse().ok())
+    .map_or(false, is_running)
+}
+
+#[cfg(unix)]
+fn is_running(pid: u32) -> bool {
+  let Ok(pid) = libc::pid_t::try_from(pid) else {
+    return false;
+  };
+  // signal 0 checks whether the process exists without sending anything
+  let result = unsafe { libc::kill(pid, 0) };
+  // it exists, but belongs to someone else
+  result == 0 || io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+#[cfg(windows)]
+fn is_running(pid: u32) -> bool {
+  use windows_sys::Win32::{
+    Foundation::{CloseHandle, STILL_ACTIVE},
+    System::Threading::{GetExitCodeProcess, OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION},
+  };
+
+  unsafe {
+    let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+    if process == 0 {
+      return false;
+    }
+    let mut exit_code = 0;
+    let has_exit_code = GetExitCodeProcess(process, &mut exit_code) != 0;
+    CloseHandle(process);
+    has_exit_code && exit_code == STILL_ACTIVE as u32
+  }
+}
+
+/// Sessions are offered even if their instance may still run.
+#[cfg(not(any(unix, windows)))]
+fn is_running(_pid: u32) -> bool {
+  false
+}
+
+enum Job {
+  Write(Box<Savefile>),
+  /// the document was saved, so the session is not needed anymore
+  Remove,
+}
+
+struct Queue {
+  /// only the newest job matters, so it replaces the one still waiting
+  job: Option<Job>,
+  /// the changes to append after the job
+  journal: Vec<JournalEntry>,
+  /// whether the session file exists or is about to, so changes are appended to it
+  has_session: bool,
+  /// set when writing failed, the session file may miss changes
+  needs_snapshot: bool,
+  /// the changes appended since the session file was written
+  journal_len: usize,
+  /// set with the last job
+  is_finished: bool,
+}
+
+impl Queue {
+  fn write(&mut self, savefile: Savefile) {
+    self.job = Some(Job::Write(Box::new(savefile)));
+    self.journal.clear();
+    self.has_session = true;
+    self.needs_snapshot = false;
+    self.journal_len = 0;
+  }
+
+  fn remove(&mut self) {
+    self.job = Some(Job::Remove);
+    self.journal.clear();
+    self.has_session = false;
+  }
+}
+
+/// Shared by the worker, the ui thread and the panic hook.
+/// The changes reach it as they happen, so the panic hook writes all of them.
+struct Shared {
+  queue: Mutex<Queue>,
+  wake: Condvar,
+  /// held while running jobs, so the panic hook waits for the write in progress
+  running: Mutex<()>,
+}
+
+/// There is one session file per instance, so also one autosave.
+static SHARED: Shared = Shared {
+  queue: Mutex::new(Queue {
+    job: None,
+    journal: Vec::new(),
+    has_session: false,
+    needs_snapshot: false,
+    journal_len: 0,
+    is_finished: false,
+  }),
+  wake: Condvar::new(),
+  running: Mutex::new(()),
+};
+
+impl Shared {
+  /// Runs the waiting job, if any, and appends the waiting changes.
+  fn run_jobs(&self) {
+    let _running = lock(&self.running);
+    let (job, journal) = {
+      let mut queue = lock(&self.queue);
+      (queue.job.take(), mem::take(&mut queue.journal))
+    };
+    let result = job
+      .map_or(Ok(()), run_job)
+      .and_then(|_| match journal.is_empty() {
+        true => Ok(()),
+        false => append(&journal),
+      });
+    if let Err(error) = result {
+      tracing::error!("autosave failed: {error}");
+      let mut queue = lock(&self.queue);
+      if queue.has_session {
+        queue.needs_snapshot = true;
+        queue.journal.clear();
+      }
+    }
+  }
+}
+
+impl Journal for Shared {
+  fn record(&self, entry: JournalEntry) {
+    let mut queue = lock(&self.queue);
+    match entry {
+      JournalEntry::Replace(content, protocol) => queue.write(Savefile::new(*content, *protocol)),
+      // the next snapshot has them
+      _ if !queue.has_session || queue.needs_snapshot => return,
+      entry => {
+        queue.journal.push(entry);
+        queue.journal_len += 1;
+      }
+    }
+    drop(queue);
+    self.wake.notify_one();
+  }
+}
+
+fn run_job(job: Job) -> Result<(), FileError> {
+  match job {
+    Job::Write(savefile) => {
+      fs::create_dir_all(sessions_dir())?;
+      write_atomic(&SESSION_PATH, &binary::to_bytes(&savefile, false)?)
+    }
+    Job::Remove => match fs::remove_file(&*SESSION_PATH) {
+      Err(error) if error.kind() != std::io::ErrorKind::NotFound => Err(error.into()),
+      _ => Ok(()),
+    },
+  }
+}
+
+fn append(journal: &[JournalEntry]) -> Result<(), FileError> {
+  let chunk = binary::journal_chunk(&journal)?;
+  let mut file = fs::OpenOptions::new().append(true).open(&*SESSION_PATH)?;
+  file.write_all(&chunk)?;
+  file.sync_data()?;
+  Ok(())
+}
+
+/// Keeps the session file up to date on a worker thread.
+pub struct Autosaver {
+  worker: Option<JoinHandle<()>>,
+  /// `None` before the first since the session file was removed, which is taken right away
+  last_snapshot: Option<Instant>,
+}
+
+impl Autosaver {
+  /// Also installs a panic hook that writes the changes still waiting for the worker.
+  pub fn spawn() -> Self {
+    let worker = thread::Builder::new()
+      .name(WORKER_NAME.into())
+      .spawn(|| run(&SHARED))
+      .expect("Fatal error: Failed to spawn autosave worker thread.");
+
+    let previous_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+      // the worker would wait for itself
+      if thread::current().name() != Some(WORKER_NAME) {
+        SHARED.run_jobs();
+      }
+      previous_hook(info);
+    }));
+
+    Self {
+      worker: Some(worker),
+      last_snapshot: None,
+    }
+  }
+
+  /// Receives the changes of the document, to be passed to `ContentManager::with_journal`.
+  pub fn journal(&self) -> &'static dyn Journal {
+    &SHARED
+  }
+
+  /// Takes a snapshot if the session file is missing or has too many changes appended.
+  /// Returns when it wants to be called again, if a snapshot is waiting.
+  pub fn update(&mut self, content_manager: &ContentManager) -> Option<Instant> {
+    let mut queue = lock(&SHARED.queue);
+    if !content_manager.is_modified() {
+      if queue.has_session {
+        queue.remove();
+        drop(queue);
+        SHARED.wake.notify_one();
+        self.last_snapshot = None;
+      }
+      return None;
+    }
+    let needs_snapshot =
+      !queue.has_session || queue.needs_snapshot || queue.journal_len >= MAX_JOURNAL_LEN;
+    if !needs_snapshot {
+      return None;
+    }
+    if let Some(due) = self.last_snapshot.map(|last| last + SNAPSHOT_INTERVAL) {
+      if Instant::now() < due {
+        return Some(due);
+      }
+    }
+    let (content, protocol) = content_manager.clone();
+    queue.write(Savefile::new(content, protocol));
+    drop(queue);
+    SHARED.wake.notify_one();
+    self.last_snapshot = Some(Instant::now());
+    None
+  }
+
+  /// Writes what is waiting and stops the worker.
+  /// The session stays if the document is not saved.
+  pub fn finish(&mut self, content_manager: &ContentManager) {
+    {
+      let mut queue = lock(&SHARED.queue);
+      match content_manager.is_modified() {
+        true if !queue.has_session || queue.needs_snapshot => {
+          let (content, protocol) = content_manager.clone();
+          queue.write(Savefile::new(content, protocol));
+        }
+        true => {}
+        false if queue.has_session => queue.remove(),
+        false => {}
+      }
+      queue.is_finished = true;
+    }
+    SHARED.wake.notify_one();
+    if let Some(worker) = self.worker.take() {
+      let _ = worker.join();
+    }
+  }
+}
+
+fn run(shared: &Shared) {
+  loop {
+    let queue = shared
+      .wake
+      .wait_while(lock(&shared.queue), |queue| {
+        queue.job.is_none() && queue.journal.is_empty() && !queue.is_finished
+      })
+      .unwrap_or_else(PoisonError::into_inner);
+    if queue.job.is_none() && queue.journal.is_empty() {
+      return;
+    }
+    drop(queue);
+    shared.run_jobs();
+  }
+}
+
+/// A panic elsewhere does not stop the autosave.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<T> {
+  mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
@@ -0,0 +1,905 @@
+//! The compact savefile: a header followed by deflate compressed chunks.
+//!
+//! The header is `MAGIC` and the version as little endian u32.
+//! Each chunk is a four byte tag, the length of its payload as little endian u32
+//! and the compressed payload. Chunks with unknown tags are skipped.
+//!
+//! Content and protocol are serialized with a serde format like bincode,
+//! integers as varints and no field names, so the structs must keep their field order.
+//! It is not human readable, which has strokes store their points as quantized deltas.
+//! Changing a type written into a chunk means a new version that still reads its old form.
+//!
+//! Autosaved sessions append journal chunks, each with the changes since the one before.
+
+#[cfg(test)]
+mod tests;
+
+use super::{EmbeddedPdf, FileError, Savefile, SAVEFILE_VERSION};
+use crate::pdf::PdfSource;
+
+use flate2::{read::DeflateDecoder, write::DeflateEncoder, Compression};
+use serde::{de, ser, Deserialize, Serialize};
+use std::{
+  borrow::Cow,
+  collections::HashMap,
+  fmt,
+  io::{Read, Write},
+};
+
+/// The first bytes of a compact savefile.
+/// A ron savefile never starts like that.
+pub const MAGIC: &[u8; 8] = b"\x89CARVE\r\n";
+
+const CONTENT_TAG: &[u8; 4] = b"CONT";
+const PROTOCOL_TAG: &[u8; 4] = b"PROT";
+/// a pdf the pages are taken from: its source, then its bytes
+const PDF_TAG: &[u8; 4] = b"PDF ";
+/// changes of the content, only read back by `from_bytes_with_journal`
+const JOURNAL_TAG: &[u8; 4] = b"JRNL";
+
+pub fn is_binary(data: &[u8]) -> bool {
+  data.starts_with(MAGIC)
+}
+
+/// `embed_pdfs` puts the pdfs the pages are taken from into the savefile,
+/// so it opens on other machines.
+pub fn to_bytes(savefile: &Savefile, embed_pdfs: bool) -> Result<Vec<u8>, FileError> {
+  let mut data = MAGIC.to_vec();
+  data.extend_from_slice(&SAVEFILE_VERSION.to_le_bytes());
+  write_chunk(&mut data, CONTENT_TAG, &to_vec(&savefile.content)?)?;
+  write_chunk(&mut data, PROTOCOL_TAG, &to_vec(&savefile.protocol)?)?;
+
+  let sources = match embed_pdfs {
+    true => savefile.content.pages().sources(),
+    false => &[],
+  };
+  for source in sources {
+    let embedded = savefile
+      .embedded_pdfs
+      .iter()
+      .find(|embedded| embedded.source == *source);
+    let pdf = match embedded {
+      Some(embedded) => Cow::Borrowed(&embedded.data),
+      None => match std::fs::read(source) {
+        Ok(pdf) => Cow::Owned(pdf),
+        Err(_) => continue,
+      },
+    };
+    let mut payload = to_vec(&source)?;
+    payload.extend_from_slice(&pdf);
+    write_chunk(&mut data, PDF_TAG, &payload)?;
+  }
+  Ok(data)
+}
+
+pub fn from_bytes(data: &[u8]) -> Result<Savefile, FileError> {
+  read(data, None)
+}
+
+/// The savefile of an autosaved session and the entries of its journal chunks in order.
+/// A journal chunk that was cut off, by a crash while appending it, is dropped.
+#[cfg(all(feature = "app", not(target_arch = "wasm32")))]
+pub fn from_bytes_with_journal<T: de::DeserializeOwned>(
+  data: &[u8],
+) -> Result<(Savefile, Vec<T>), FileError> {
+  let mut journal = Vec::new();
+  let savefile = read(data, Some(&mut journal))?;
+  let entries = journal
+    .iter()
+    .map(|payload| from_slice(payload))
+    .collect::<Result<_, _>>()?;
+  Ok((savefile, entries))
+}
+
+/// A chunk to append to a savefile written by `to_bytes`.
+#[cfg(all(feature = "app", not(target_arch = "wasm32")))]
+pub fn journal_chunk<T: Serialize>(entries: &T) -> Result<Vec<u8>, FileError> {
+  let mut data = Vec::new();
+  write_chunk(&mut data, JOURNAL_TAG, &to_vec(entries)?)?;
+  Ok(data)
+}
+
+fn read(data: &[u8], mut journal: Option<&mut Vec<Vec<u8>>>) -> Result<Savefile, FileError> {
+  let mut input = data.strip_prefix(MAGIC.as_slice()).ok_or(Error::Eof)?;
+  let version = u32::from_le_bytes(take::<4>(&mut input)?);
+  if version > SAVEFILE_VERSION {
+    return Err(FileError::Version {
+      found: version,
+      supported: SAVEFILE_VERSION,
+    });
+  }
+  // compact savefiles came with version 1
+  if version < SAVEFILE_VERSION {
+    return Err(Error::Message(format!("unknown version {version}")).into());
+  }
+
+  let mut chunks = HashMap::new();
+  let mut embedded_pdfs = Vec::new();
+  while !input.is_empty() {
+    let (tag, compressed) = match next_chunk(&mut input) {
+      Ok(chunk) => chunk,
+      Err(_) if journal.is_some() => break,
+      Err(error) => return Err(error.into()),
+    };
+
+    let mut payload = Vec::new();
+    DeflateDecoder::new(compressed).read_to_end(&mut payload)?;
+    match &tag {
+      PDF_TAG => {
+        let mut deserializer = Deserializer { input: &payload };
+        let source = PdfSource::from(String::deserialize(&mut deserializer)?);
+        // the pdfs that are still around are read from there
+        if !is_available(&source) {
+          let data = deserializer.input.to_vec();
+          embedded_pdfs.push(EmbeddedPdf { source, data });
+        }
+      }
+      JOURNAL_TAG => {
+        if let Some(journal) = journal.as_mut() {
+          journal.push(payload);
+        }
+      }
+      _ => {
+        chunks.insert(tag, payload);
+      }
+    }
+  }
+
+  let mut savefile = Savefile::new(
+    from_slice(chunk(&chunks, CONTENT_TAG)?)?,
+    from_slice(chunk(&chunks, PROTOCOL_TAG)?)?,
+  );
+  savefile.embedded_pdfs = embedded_pdfs;
+  Ok(savefile)
+}
+
+/// Writes the embedded pdfs into the cache and points the pages to the copies.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn extract_pdfs(savefile: &mut Savefile) -> Result<(), FileError> {
+  let mut renamed = Vec::new();
+  for EmbeddedPdf { source, data } in &savefile.embedded_pdfs {
+    let stem = source.file_stem().unwrap_or_default().to_string_lossy();
+    renamed.push((source.clone(), super::cache_pdf(&stem, data)?));
+  }
+  savefile.embedded_pdfs.clear();
+  if !renamed.is_empty() {
+    let rename = |source: &PdfSource| {
+      renamed
+        .iter()
+        .find(|(from, _)| from == source)
+        .map(|(_, to)| to.clone())
+    };
+    savefile.content.rename_pdf_sources(&rename);
+    savefile.protocol.rename_pdf_sources(&rename);
+  }
+  Ok(())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn is_available(source: &PdfSource) -> bool {
+  source.exists()
+}
+
+/// Sources are urls on the web, which are not restored.
+#[cfg(target_arch = "wasm32")]
+fn is_available(_source: &PdfSource) -> bool {
+  true
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn extract_pdfs(_savefile: &mut Savefile) -> Result<(), FileError> {
+  Ok(())
+}
+
+fn chunk<'a>(chunks: &'a HashMap<[u8; 4], Vec<u8>>, tag: &[u8; 4]) -> Result<&'a [u8], Error> {
+  chunks.get(tag).map(Vec::as_slice).ok_or_else(|| {
+    let tag = String::from_utf8_lossy(tag);
+    Error::Message(format!("missing {} chunk", tag.trim_end()))
+  })
+}
+
+fn write_chunk(data: &mut Vec<u8>, tag: &[u8; 4], payload: &[u8]) -> Result<(), FileError> {
+  let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+  encoder.write_all(payload)?;
+  let compressed = encoder.finish()?;
+  let len =
+    u32::try_from(compressed.len()).map_err(|_| Error::Message("chunk too large".into()))?;
+  data.extend_from_slice(tag);
+  data.extend_from_slice(&len.to_le_bytes());
+  data.extend_from_slice(&compressed);
+  Ok(())
+}
+
+fn next_chunk<'a>(input: &mut &'a [u8]) -> Result<([u8; 4], &'a [u8]), Error> {
+  let tag = take::<4>(input)?;
+  let len = u32::from_le_bytes(take::<4>(input)?) as usize;
+  if input.len() < len {
+    return Err(Error::Eof);
+  }
+  let (compressed, rest) = input.split_at(len);
+  *input = rest;
+  Ok((tag, compressed))
+}
+
+fn take<const N: usize>(input: &mut &[u8]) -> Result<[u8; N], Error> {
+  if input.len() < N {
+    return Err(Error::Eof);
+  }
+  let (bytes, rest) = input.split_at(N);
+  *input = rest;
+  Ok(bytes.try_into().unwrap())
+}
+
+/// Writes and reads the points of a stroke.
+///
+/// Human readable formats get the points as they are.
+/// The compact format gets them as varints in steps of `QUANTUM`,
+/// the first point absolute and every other one relative to the previous.
+pub mod serde_points {
+  use super::{read_varint, unzigzag, write_varint, zigzag};
+
+  use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+  use std::fmt;
+
+  /// in canvas units, far below what can be drawn
+  const QUANTUM: f32 = 1e-5;
+
+  pub fn serialize<S: Serializer>(
+    points: &[na::Point2<f32>],
+    serializer: S,
+  ) -> Result<S::Ok, S::Error> {
+    if serializer.is_human_readable() {
+      return points.serialize(serializer);
+    }
+    let mut bytes = Vec::with_capacity(4 * points.len());
+    write_varint(&mut bytes, points.len() as u64);
+    let mut previous = [0i64; 2];
+    for point in points {
+      for (coord, previous) in point.coords.iter().zip(&mut previous) {
+        let quantized = (coord / QUANTUM).round() as i64;
+        write_varint(&mut bytes, zigzag(quantized - *previous));
+        *previous = quantized;
+      }
+    }
+    serializer.serialize_bytes(&bytes)
+  }
+
+  pub fn deserialize<'de, D: Deserializer<'de>>(
+    deserializer: D,
+  ) -> Result<Vec<na::Point2<f32>>, D::Error> {
+    if deserializer.is_human_readable() {
+      return Deserialize::deserialize(deserializer);
+    }
+    deserializer.deserialize_bytes(PointsVisitor)
+  }
+
+  struct PointsVisitor;
+  impl<'de> de::Visitor<'de> for PointsVisitor {
+    type Value = Vec<na::Point2<f32>>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+      f.write_str("quantized points")
+    }
+
+    fn visit_bytes<E: de::Error>(self, mut bytes: &[u8]) -> Result<Self::Value, E> {
+      let len = read_varint(&mut bytes).map_err(E::custom)? as usize;
+      let mut points = Vec::with_capacity(len.min(bytes.len()));
+      let mut previous = [0i64; 2];
+      for _ in 0..len {
+        for previous in &mut previous {
+          *previous += unzigzag(read_varint(&mut bytes).map_err(E::custom)?);
+        }
+        let [x, y] = previous.map(|quantized| quantized as f32 * QUANTUM);
+        points.push(na::Point2::new(x, y));
+      }
+      Ok(points)
+    }
+  }
+}
+
+#[derive(Debug)]
+pub enum Error {
+  Eof,
+  Message(String),
+}
+
+impl fmt::Display for Error {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Error::Eof => f.write_str("unexpected end"),
+      Error::Message(message) => f.write_str(message),
+    }
+  }
+}
+
+impl std::error::Error for Error {}
+
+impl ser::Error for Error {
+  fn custom<T: fmt::Display>(message: T) -> Self {
+    Self::Message(message.to_string())
+  }
+}
+
+impl de::Error for Error {
+  fn custom<T: fmt::Display>(message: T) -> Self {
+    Self::Message(message.to_string())
+  }
+}
+
+fn write_varint(output: &mut Vec<u8>, mut value: u64) {
+  while value >= 0x80 {
+    output.push(value as u8 | 0x80);
+    value >>= 7;
+  }
+  output.push(value as u8);
+}
+
+fn read_varint(input: &mut &[u8]) -> Result<u64, Error> {
+  let mut value = 0u64;
+  for shift in (0..64).step_by(7) {
+    let [byte] = take::<1>(input)?;
+    value |= u64::from(byte & 0x7f) << shift;
+    if byte & 0x80 == 0 {
+      return Ok(value);
+    }
+  }
+  Err(Error::Message("varint too long".into()))
+}
+
+/// maps small negative numbers to small varints
+fn zigzag(value: i64) -> u64 {
+  ((value << 1) ^ (value >> 63)) as u64
+}
+
+fn unzigzag(value: u64) -> i64 {
+  (value >> 1) as i64 ^ -((value & 1) as i64)
+}
+
+fn to_vec<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, Error> {
+  let mut serializer = Serializer { output: Vec::new() };
+  value.serialize(&mut serializer)?;
+  Ok(serializer.output)
+}
+
+fn from_slice<'de, T: Deserialize<'de>>(input: &'de [u8]) -> Result<T, Error> {
+  let mut deserializer = Deserializer { input };
+  let value = T::deserialize(&mut deserializer)?;
+  match deserializer.input.is_empty() {
+    true => Ok(value),
+    false => Err(Error::Message("trailing bytes".into())),
+  }
+}
+
+struct Serializer {
+  output: Vec<u8>,
+}
+
+impl Serializer {
+  fn varint(&mut self, value: u64) {
+    write_varint(&mut self.output, value);
+  }
+
+  fn len(&mut self, len: Option<usize>) -> Result<(), Error> {
+    let len = len.ok_or_else(|| Error::Message("length unknown".into()))?;
+    self.varint(len as u64);
+    Ok(())
+  }
+}
+
+impl<'a> ser::Serializer for &'a mut Serializer {
+  type Ok = ();
+  type Error = Error;
+  type SerializeSeq = Self;
+  type SerializeTuple = Self;
+  type SerializeTupleStruct = Self;
+  type SerializeTupleVariant = Self;
+  type SerializeMap = Self;
+  type SerializeStruct = Self;
+  type SerializeStructVariant = Self;
+
+  fn is_human_readable(&self) -> bool {
+    false
+  }
+
+  fn serialize_bool(self, v: bool) -> Result<(), Error> {
+    self.output.push(v as u8);
+    Ok(())
+  }
+  fn serialize_i8(self, v: i8) -> Result<(), Error> {
+    self.serialize_i64(v.into())
+  }
+  fn serialize_i16(self, v: i16) -> Result<(), Error> {
+    self.serialize_i64(v.into())
+  }
+  fn serialize_i32(self, v: i32) -> Result<(), Error> {
+    self.serialize_i64(v.into())
+  }
+  fn serialize_i64(self, v: i64) -> Result<(), Error> {
+    self.varint(zigzag(v));
+    Ok(())
+  }
+  fn serialize_u8(self, v: u8) -> Result<(), Error> {
+    self.output.push(v);
+    Ok(())
+  }
+  fn serialize_u16(self, v: u16) -> Result<(), Error> {
+    self.serialize_u64(v.into())
+  }
+  fn serialize_u32(self, v: u32) -> Result<(), Error> {
+    self.serialize_u64(v.into())
+  }
+  fn serialize_u64(self, v: u64) -> Result<(), Error> {
+    self.varint(v);
+    Ok(())
+  }
+  fn serialize_f32(self, v: f32) -> Result<(), Error> {
+    self.output.extend_from_slice(&v.to_le_bytes());
+    Ok(())
+  }
+  fn serialize_f64(self, v: f64) -> Result<(), Error> {
+    self.output.extend_from_slice(&v.to_le_bytes());
+    Ok(())
+  }
+  fn serialize_char(self, v: char) -> Result<(), Error> {
+    self.serialize_u64(v.into())
+  }
+  fn serialize_str(self, v: &str) -> Result<(), Error> {
+    self.serialize_bytes(v.as_bytes())
+  }
+  fn serialize_bytes(self, v: &[u8]) -> Result<(), Error> {
+    self.varint(v.len() as u64);
+    self.output.extend_from_slice(v);
+    Ok(())
+  }
+  fn serialize_none(self) -> Result<(), Error> {
+    self.serialize_bool(false)
+  }
+  fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), Error> {
+    self.serialize_bool(true)?;
+    value.serialize(self)
+  }
+  fn serialize_unit(self) -> Result<(), Error> {
+    Ok(())
+  }
+  fn serialize_unit_struct(self, _name: &'static str) -> Result<(), Error> {
+    Ok(())
+  }
+  fn serialize_unit_variant(
+    self,
+    _name: &'static str,
+    variant_index: u32,
+    _variant: &'static str,
+  ) -> Result<(), Error> {
+    self.serialize_u32(variant_index)
+  }
+  fn serialize_newtype_struct<T: Serialize + ?Sized>(
+    self,
+    _name: &'static str,
+    value: &T,
+  ) -> Result<(), Error> {
+    value.serialize(self)
+  }
+  fn serialize_newtype_variant<T: Serialize + ?Sized>(
+    self,
+    _name: &'static str,
+    variant_index: u32,
+    _variant: &'static str,
+    value: &T,
+  ) -> Result<(), Error> {
+    self.serialize_u32(variant_index)?;
+    value.serialize(self)
+  }
+  fn serialize_seq(self, len: Option<usize>) -> Result<Self, Error> {
+    self.len(len)?;
+    Ok(self)
+  }
+  fn serialize_tuple(self, _len: usize) -> Result<Self, Error> {
+    Ok(self)
+  }
+  fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Self, Error> {
+    Ok(self)
+  }
+  fn serialize_tuple_variant(
+    self,
+    _name: &'static str,
+    variant_index: u32,
+    _variant: &'static str,
+    _len: usize,
+  ) -> Result<Self, Error> {
+    self.serialize_u32(variant_index)?;
+    Ok(self)
+  }
+  fn serialize_map(self, len: Option<usize>) -> Result<Self, Error> {
+    self.len(len)?;
+    Ok(self)
+  }
+  fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self, Error> {
+    Ok(self)
+  }
+  fn serialize_struct_variant(
+    self,
+    _name: &'static str,
+    variant_index: u32,
+    _variant: &'static str,
+    _len: usize,
+  ) -> Result<Self, Error> {
+    self.serialize_u32(variant_index)?;
+    Ok(self)
+  }
+}
+
+impl<'a> ser::SerializeSeq for &'a mut Serializer {
+  type Ok = ();
+  type Error = Error;
+  fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+    value.serialize(&mut **self)
+  }
+  fn end(self) -> Result<(), Error> {
+    Ok(())
+  }
+}
+
+impl<'a> ser::SerializeTuple for &'a mut Serializer {
+  type Ok = ();
+  type Error = Error;
+  fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+    value.serialize(&mut **self)
+  }
+  fn end(self) -> Result<(), Error> {
+    Ok(())
+  }
+}
+
+impl<'a> ser::SerializeTupleStruct for &'a mut Serializer {
+  type Ok = ();
+  type Error = Error;
+  fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+    value.serialize(&mut **self)
+  }
+  fn end(self) -> Result<(), Error> {
+    Ok(())
+  }
+}
+
+impl<'a> ser::SerializeTupleVariant for &'a mut Serializer {
+  type Ok = ();
+  type Error = Error;
+  fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+    value.serialize(&mut **self)
+  }
+  fn end(self) -> Result<(), Error> {
+    Ok(())
+  }
+}
+
+impl<'a> ser::SerializeMap for &'a mut Serializer {
+  type Ok = ();
+  type Error = Error;
+  fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Error> {
+    key.serialize(&mut **self)
+  }
+  fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+    value.serialize(&mut **self)
+  }
+  fn end(self) -> Result<(), Error> {
+    Ok(())
+  }
+}
+
+impl<'a> ser::SerializeStruct for &'a mut Serializer {
+  type Ok = ();
+  type Error = Error;
+  fn serialize_field<T: Serialize + ?Sized>(
+    &mut self,
+    _key: &'static str,
+    value: &T,
+  ) -> Result<(), Error> {
+    value.serialize(&mut **self)
+  }
+  fn end(self) -> Result<(), Error> {
+    Ok(())
+  }
+}
+
+impl<'a> ser::SerializeStructVariant for &'a mut Serializer {
+  type Ok = ();
+  type Error = Error;
+  fn serialize_field<T: Serialize + ?Sized>(
+    &mut self,
+    _key: &'static str,
+    value: &T,
+  ) -> Result<(), Error> {
+    value.serialize(&mut **self)
+  }
+  fn end(self) -> Result<(), Error> {
+    Ok(())
+  }
+}
+
+struct Deserializer<'de> {
+  input: &'de [u8],
+}
+
+impl<'de> Deserializer<'de> {
+  fn varint(&mut self) -> Result<u64, Error> {
+    read_varint(&mut self.input)
+  }
+
+  fn len(&mut self) -> Result<usize, Error> {
+    let len = self.varint()? as usize;
+    // every element takes at least a byte, except units which are not in any savefile
+    match len <= self.input.len() {
+      true => Ok(len),
+      false => Err(Error::Eof),
+    }
+  }
+
+  fn bytes(&mut self) -> Result<&'de [u8], Error> {
+    let len = self.len()?;
+    let (bytes, rest) = self.input.split_at(len);
+    self.input = rest;
+    Ok(bytes)
+  }
+
+  fn bool(&mut self) -> Result<bool, Error> {
+    match take::<1>(&mut self.input)? {
+      [0] => Ok(false),
+      [1] => Ok(true),
+      _ => Err(Error::Message("invalid bool".into())),
+    }
+  }
+}
+
+macro_rules! deserialize_varint {
+  ($($method:ident $visit:ident $ty:ty, $decode:expr;)*) => {$(
+    fn $method<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+      let value = $decode(self.varint()?);
+      let value = <$ty>::try_from(value).map_err(|_| Error::Message("integer out of range".into()))?;
+      visitor.$visit(value)
+    }
+  )*};
+}
+
+impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
+  type Error = Error;
+
+  fn is_human_readable(&self) -> bool {
+    false
+  }
+
+  fn deserialize_any<V: de::Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Error> {
+    Err(Error::Message("the format is not self describing".into()))
+  }
+
+  fn deserialize_bool<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+    visitor.visit_bool(self.bool()?)
+  }
+
+  deserialize_varint! {
+    deserialize_i16 visit_i16 i16, unzigzag;
+    deserialize_i32 visit_i32 i32, unzigzag;
+    deserialize_i64 visit_i64 i64, unzigzag;
+    deserialize_u16 visit_u16 u16, std::convert::identity;
+    deserialize_u32 visit_u32 u32, std::convert::identity;
+    deserialize_u64 visit_u64 u64, std::convert::identity;
+  }
+
+  fn deserialize_i8<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+    let value = i8::try_from(unzigzag(self.varint()?))
+      .map_err(|_| Error::Message("integer out of range".into()))?;
+    visitor.visit_i8(value)
+  }
+
+  fn deserialize_u8<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+    let [value] = take::<1>(&mut self.input)?;
+    visitor.visit_u8(value)
+  }
+
+  fn deserialize_f32<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+    visitor.visit_f32(f32::from_le_bytes(take::<4>(&mut self.input)?))
+  }
+
+  fn deserialize_f64<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+    visitor.visit_f64(f64::from_le_bytes(take::<8>(&mut self.input)?))
+  }
+
+  fn deserialize_char<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+    let value = u32::try_from(self.varint()?)
+      .ok()
+      .and_then(char::from_u32)
+      .ok_or_else(|| Error::Message("invalid char".into()))?;
+    visitor.visit_char(value)
+  }
+
+  fn deserialize_str<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+    let value = std::str::from_utf8(self.bytes()?).map_err(de::Error::custom)?;
+    visitor.visit_borrowed_str(value)
+  }
+
+  fn deserialize_string<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+    self.deserialize_str(visitor)
+  }
+
+  fn deserialize_bytes<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+    visitor.visit_borrowed_bytes(self.bytes()?)
+  }
+
+  fn deserialize_byte_buf<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+    self.deserialize_bytes(visitor)
+  }
+
+  fn deserialize_option<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+    match self.bool()? {
+      true => visitor.visit_some(self),
+      false => visitor.visit_none(),
+    }
+  }
+
+  fn deserialize_unit<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+    visitor.visit_unit()
+  }
+
+  fn deserialize_unit_struct<V: de::Visitor<'de>>(
+    self,
+    _name: &'static str,
+    visitor: V,
+  ) -> Result<V::Value, Error> {
+    visitor.visit_unit()
+  }
+
+  fn deserialize_newtype_struct<V: de::Visitor<'de>>(
+    self,
+    _name: &'static str,
+    visitor: V,
+  ) -> Result<V::Value, Error> {
+    visitor.visit_newtype_struct(self)
+  }
+
+  fn deserialize_seq<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+    let len = self.len()?;
+    visitor.visit_seq(Elements {
+      deserializer: self,
+      len,
+    })
+  }
+
+  fn deserialize_tuple<V: de::Visitor<'de>>(
+    self,
+    len: usize,
+    visitor: V,
+  ) -> Result<V::Value, Error> {
+    visitor.visit_seq(Elements {
+      deserializer: self,
+      len,
+    })
+  }
+
+  fn deserialize_tuple_struct<V: de::Visitor<'de>>(
+    self,
+    _name: &'static str,
+    len: usize,
+    visitor: V,
+  ) -> Result<V::Value, Error> {
+    self.deserialize_tuple(len, visitor)
+  }
+
+  fn deserialize_map<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+    let len = self.len()?;
+    visitor.visit_map(Elements {
+      deserializer: self,
+      len,
+    })
+  }
+
+  fn deserialize_struct<V: de::Visitor<'de>>(
+    self,
+    _name: &'static str,
+    fields: &'static [&'static str],
+    visitor: V,
+  ) -> Result<V::Value, Error> {
+    self.deserialize_tuple(fields.len(), visitor)
+  }
+
+  fn deserialize_enum<V: de::Visitor<'de>>(
+    self,
+    _name: &'static str,
+    _variants: &'static [&'static str],
+    visitor: V,
+  ) -> Result<V::Value, Error> {
+    visitor.visit_enum(self)
+  }
+
+  fn deserialize_identifier<V: de::Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Error> {
+    Err(Error::Message("the format has no identifiers".into()))
+  }
+
+  fn deserialize_ignored_any<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+    self.deserialize_any(visitor)
+  }
+}
+
+/// the elements of sequences, tuples, structs and maps
+struct Elements<'a, 'de> {
+  deserializer: &'a mut Deserializer<'de>,
+  len: usize,
+}
+
+impl<'de, 'a> de::SeqAccess<'de> for Elements<'a, 'de> {
+  type Error = Error;
+
+  fn next_element_seed<T: de::DeserializeSeed<'de>>(
+    &mut self,
+    seed: T,
+  ) -> Result<Option<T::Value>, Error> {
+    if self.len == 0 {
+      return Ok(None);
+    }
+    self.len -= 1;
+    seed.deserialize(&mut *self.deserializer).map(Some)
+  }
+
+  fn size_hint(&self) -> Option<usize> {
+    Some(self.len)
+  }
+}
+
+impl<'de, 'a> de::MapAccess<'de> for Elements<'a, 'de> {
+  type Error = Error;
+
+  fn next_key_seed<K: de::DeserializeSeed<'de>>(
+    &mut self,
+    seed: K,
+  ) -> Result<Option<K::Value>, Error> {
+    if self.len == 0 {
+      return Ok(None);
+    }
+    self.len -= 1;
+    seed.deserialize(&mut *self.deserializer).map(Some)
+  }
+
+  fn next_value_seed<V: de::DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+    seed.deserialize(&mut *self.deserializer)
+  }
+
+  fn size_hint(&self) -> Option<usize> {
+    Some(self.len)
+  }
+}
+
+impl<'de, 'a> de::EnumAccess<'de> for &'a mut Deserializer<'de> {
+  type Error = Error;
+  type Variant = Self;
+
+  fn variant_seed<V: de::DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self), Error> {
+    let variant_index =
+      u32::try_from(self.varint()?).map_err(|_| Error::Message("invalid variant".into()))?;
+    let variant = seed.deserialize(de::IntoDeserializer::<Error>::into_deserializer(
+      variant_index,
+    ))?;
+    Ok((variant, self))
+  }
+}
+
+impl<'de, 'a> de::VariantAccess<'de> for &'a mut Deserializer<'de> {
+  type Error = Error;
+
+  fn unit_variant(self) -> Result<(), Error> {
+    Ok(())
+  }
+
+  fn newtype_variant_seed<T: de::DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Error> {
+    seed.deserialize(self)
+  }
+
+  fn tuple_variant<V: de::Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, Error> {
+    de::Deserializer::deserialize_tuple(self, len, visitor)
+  }
+
+  fn struct_variant<V: de::Visitor<'de>>(
+    self,
+    fields: &'static [&'static str],
+    visitor: V,
+  ) -> Result<V::Value, Error> {
+    de::Deserializer::deserialize_tuple(self, fields.len(), visitor)
+  }
+}
@@ -0,0 +1,363 @@
+extern crate test;
+
+use super::{from_bytes, from_slice, serde_points, to_bytes, to_vec, MAGIC};
+#[cfg(feature = "app")]
+use super::{from_bytes_with_journal, journal_chunk};
+use crate::{
+  content::{
+    command::{AddStrokeCommand, RemoveStrokesCommand, SetPagesCommand},
+    protocol::Protocol,
+    Content, ContentManager,
+  },
+  file::{FileError, Savefile, SAVEFILE_VERSION},
+  pdf::{PageList, PdfPageInfo},
+  stroke::Stroke,
+};
+
+#[cfg(feature = "app")]
+use crate::content::{Journal, JournalEntry};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{collections::BTreeMap, fmt::Debug};
+use test::Bencher;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct UnitStruct;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct NewtypeStruct(u32);
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct TupleStruct(i32, String);
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum Enum {
+  Unit,
+  Newtype(i8),
+  Tuple(u32, bool),
+  Struct { value: f64 },
+}
+
+/// every type of the serde data model
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Everything {
+  bool: bool,
+  i8: i8,
+  i16: i16,
+  i32: i32,
+  i64: i64,
+  u8: u8,
+  u16: u16,
+  u32: u32,
+  u64: u64,
+  f32: f32,
+  f64: f64,
+  char: char,
+  string: String,
+  none: Option<u32>,
+  some: Option<String>,
+  unit: (),
+  unit_struct: UnitStruct,
+  newtype_struct: NewtypeStruct,
+  tuple: (u8, i64, f32),
+  tuple_struct: TupleStruct,
+  seq: Vec<u16>,
+  map: BTreeMap<String, i32>,
+  enums: Vec<Enum>,
+}
+
+impl Everything {
+  fn extremes() -> Self {
+    Self {
+      bool: true,
+      i8: i8::MIN,
+      i16: i16::MIN,
+      i32: i32::MAX,
+      i64: i64::MIN,
+      u8: u8::MAX,
+      u16: u16::MAX,
+      u32: u32::MAX,
+      u64: u64::MAX,
+      f32: f32::INFINITY,
+      f64: -0.5,
+      char: '\u{10ffff}',
+      string: "ünïcode ✍".to_owned(),
+      none: None,
+      some: Some(String::new()),
+      unit: (),
+      unit_struct: UnitStruct,
+      newtype_struct: NewtypeStruct(300),
+      tuple: (0, -1, f32::MIN_POSITIVE),
+      tuple_struct: TupleStruct(-129, "tuple".to_owned()),
+      seq: vec![0, 127, 128, 16383, 16384],
+      map: [("a".to_owned(), -1), ("b".to_owned(), 64)].into(),
+      enums: vec![
+        Enum::Unit,
+        Enum::Newtype(-64),
+        Enum::Tuple(1, false),
+        Enum::Struct { value: 1e300 },
+      ],
+    }
+  }
+}
+
+fn round_trip<T: Serialize + DeserializeOwned + PartialEq + Debug>(value: &T) {
+  let bytes = to_vec(value).unwrap();
+  assert_eq!(from_slice::<T>(&bytes).unwrap(), *value);
+}
+
+#[test]
+fn round_trips_the_data_model() {
+  round_trip(&Everything::extremes());
+  round_trip(&0i64);
+  round_trip(&i64::MAX);
+  round_trip(&u64::MIN);
+  round_trip(&Vec::<Enum>::new());
+  round_trip(&Some(Some(())));
+}
+
+#[test]
+fn rejects_broken_input() {
+  let bytes = to_vec(&Everything::extremes()).unwrap();
+  for len in 0..bytes.len() {
+    assert!(from_slice::<Everything>(&bytes[..len]).is_err());
+  }
+  let mut trailing = bytes;
+  trailing.push(0);
+  assert!(from_slice::<Everything>(&trailing).is_err());
+
+  assert!(from_slice::<bool>(&[2]).is_err());
+  assert!(from_slice::<u8>(&[]).is_err());
+  // a varint longer than 64 bits
+  assert!(from_slice::<u64>(&[0xff; 11]).is_err());
+  assert!(from_slice::<u16>(&to_vec(&u32::MAX).unwrap()).is_err());
+  assert!(from_slice::<char>(&to_vec(&0xd800u32).unwrap()).is_err());
+  assert!(from_slice::<String>(&to_vec(&[0xffu8, 0xfe].as_slice()).unwrap()).is_err());
+  // a sequence longer than the input
+  assert!(from_slice::<Vec<u8>>(&[100, 1]).is_err());
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Points(#[serde(with = "serde_points")] Vec<na::Point2<f32>>);
+
+#[test]
+fn quantizes_points() {
+  let points = Points(vec![
+    na::point![0.0, 0.0],
+    na::point![-1.234_567, 8.765_43],
+    na::point![1000.0, -1000.0],
+    na::point![1_000.000_01, -999.999_99],
+  ]);
+  let bytes = to_vec(&points).unwrap();
+  let read = from_slice::<Points>(&bytes).unwrap();
+  assert_eq!(read.0.len(), points.0.len());
+  for (read, point) in read.0.iter().zip(&points.0) {
+    // the quantum and what f32 loses at this magnitude
+    assert!((read - point).norm() < 1e-4, "{read} != {point}");
+  }
+  // quantizing again changes nothing
+  assert_eq!(to_vec(&read).unwrap(), bytes);
+
+  let ron = ron::to_string(&points).unwrap();
+  assert_eq!(ron::from_str::<Points>(&ron).unwrap().0, points.0);
+}
+
+/// a document with a long history, like after a few hours of writing
+fn savefile() -> Savefile {
+  let mut content_manager = ContentManager::default();
+  let mut pages = PageList::default();
+  for page in 0..20 {
+    pages.insert_blank(page, PdfPageInfo::A4);
+  }
+  content_manager.run_cmd(SetPagesCommand::new(pages));
+  let color = palette::LinSrgba::new(0.1, 0.2, 0.7, 1.0);
+  for stroke in 0..2000 {
+    let origin = na::point![(stroke % 40) as f32 * 0.05, (stroke / 40) as f32 * 0.3];
+    let points = (0..100)
+      .map(|i| {
+        let t = i as f32 * 0.01;
+        origin + na::vector![t, 0.02 * (t * 40.0 + stroke as f32).sin()]
+      })
+      .collect();
+    content_manager.run_cmd(AddStrokeCommand::new(Stroke::new(points, color, 1.0)));
+    if stroke % 10 == 9 {
+      let (id, _) = content_manager.access().strokes().next().unwrap();
+      content_manager.run_cmd(RemoveStrokesCommand::single(id));
+    }
+  }
+  let (content, protocol) = content_manager.clone();
+  Savefile::new(content, protocol)
+}
+
+fn to_ron(savefile: &Savefile) -> String {
+  ron::ser::to_string_pretty(savefile, ron::ser::PrettyConfig::default()).unwrap()
+}
+
+#[test]
+fn round_trips_savefiles() {
+  let savefile = savefile();
+  let bytes = to_bytes(&savefile, false).unwrap();
+  let read = from_bytes(&bytes).unwrap();
+  assert_eq!(to_bytes(&read, false).unwrap(), bytes);
+  assert_eq!(read.protocol.nsteps(), savefile.protocol.nsteps());
+
+  assert_eq!(
+    ron::to_string(read.content.pages()).unwrap(),
+    ron::to_string(savefile.content.pages()).unwrap()
+  );
+
+  let (strokes, read_strokes) = (strokes(savefile), strokes(read));
+  assert_eq!(read_strokes.len(), strokes.len());
+  for (read, stroke) in read_strokes.iter().zip(&strokes) {
+    assert_eq!(read.color(), stroke.color());
+    assert_eq!(read.points().len(), stroke.points().len());
+    for (read, point) in read.points().iter().zip(stroke.points()) {
+      assert!((read - point).norm() < 1e-4);
+    }
+  }
+}
+
+fn strokes(savefile: Savefile) -> Vec<Stroke> {
+  let mut content_manager = ContentManager::default();
+  content_manager.replace(savefile.content, savefile.protocol);
+  let content = content_manager.access();
+  let strokes = content.strokes().map(|(_, stroke)| stroke.clone());
+  strokes.collect()
+}
+
+#[test]
+fn keeps_missing_pdfs_embedded() {
+  let dir = std::env::temp_dir().join(format!("carveout-embedded-{}", std::process::id()));
+  std::fs::create_dir_all(&dir).unwrap();
+  let source = dir.join("gone.pdf");
+  std::fs::write(&source, b"%PDF-1.4 not much of a pdf").unwrap();
+  let mut pages = PageList::default();
+  pages.insert_pdf(0, source.clone(), 0..1);
+  let savefile = Savefile::new(Content::new(pages, []), Protocol::default());
+  let bytes = to_bytes(&savefile, true).unwrap();
+  std::fs::remove_dir_all(&dir).unwrap();
+
+  let read = from_bytes(&bytes).unwrap();
+  // loading extracts nothing, the pages keep pointing to the source
+  assert_eq!(read.content.pages().sources(), [source.clone()]);
+  assert_eq!(read.embedded_pdfs.len(), 1);
+  assert_eq!(read.embedded_pdfs[0].source, source);
+  assert_eq!(read.embedded_pdfs[0].data, b"%PDF-1.4 not much of a pdf");
+  assert_eq!(to_bytes(&read, true).unwrap(), bytes);
+}
+
+#[test]
+fn rejects_newer_and_foreign_files() {
+  let mut bytes = to_bytes(&savefile(), false).unwrap();
+  bytes[MAGIC.len()..MAGIC.len() + 4].copy_from_slice(&(SAVEFILE_VERSION + 1).to_le_bytes());
+  assert!(matches!(
+    from_bytes(&bytes),
+    Err(FileError::Version { found, .. }) if found == SAVEFILE_VERSION + 1
+  ));
+  assert!(from_bytes(b"// carveout savefile version 1").is_err());
+  assert!(from_bytes(&bytes[..MAGIC.len() + 6]).is_err());
+}
+
+#[test]
+fn compact_is_smaller_than_ron() {
+  let savefile = savefile();
+  let ron = to_ron(&savefile).len();
+  let compact = to_bytes(&savefile, false).unwrap().len();
+  assert!(
+    compact * 10 < ron,
+    "ron: {ron} bytes, compact: {compact} bytes"
+  );
+}
+
+/// collects the changes like the autosave does
+#[cfg(feature = "app")]
+#[derive(Default)]
+struct Recorder(std::sync::Mutex<Vec<JournalEntry>>);
+
+#[cfg(feature = "app")]
+impl Journal for Recorder {
+  fn record(&self, entry: JournalEntry) {
+    self.0.lock().unwrap().push(entry);
+  }
+}
+
+#[cfg(feature = "app")]
+#[test]
+fn replays_journals() {
+  let recorder: &'static Recorder = Box::leak(Box::default());
+  let Savefile {
+    content, protocol, ..
+  } = savefile();
+  let mut content_manager = ContentManager::default().with_journal(recorder);
+  content_manager.replace(content.clone(), protocol.clone());
+  let mut data = to_bytes(&Savefile::new(content, protocol), false).unwrap();
+  recorder.0.lock().unwrap().clear();
+
+  let stroke = Stroke::new(
+    vec![na::point![0.0, 0.0], na::point![1.0, 1.0]],
+    palette::LinSrgba::new(1.0, 0.0, 0.0, 1.0),
+    2.0,
+  );
+  content_manager.run_cmd(AddStrokeCommand::new(stroke));
+  content_manager.undo_cmd();
+  content_manager.redo_cmd();
+  let journal = std::mem::take(&mut *recorder.0.lock().unwrap());
+  data.extend(journal_chunk(&journal).unwrap());
+  let (id, _) = content_manager.access().strokes().next().unwrap();
+  content_manager.run_cmd(RemoveStrokesCommand::single(id));
+  let journal = std::mem::take(&mut *recorder.0.lock().unwrap());
+  data.extend(journal_chunk(&journal).unwrap());
+  let (content, protocol) = content_manager.clone();
+  let expected = strokes(Savefile::new(content, protocol));
+
+  let replay = |data: &[u8]| {
+    let (savefile, journal) = from_bytes_with_journal::<Vec<JournalEntry>>(data).unwrap();
+    let mut content_manager = ContentManager::default();
+    content_manager.replace(savefile.content, savefile.protocol);
+    for entry in journal.into_iter().flatten() {
+      content_manager.replay(entry);
+    }
+    content_manager
+  };
+  let replayed = replay(&data);
+  assert_eq!(
+    replayed.protocol().nsteps(),
+    content_manager.protocol().nsteps()
+  );
+  let (content, protocol) = replayed.clone();
+  let strokes = strokes(Savefile::new(content, protocol));
+  assert_eq!(strokes.len(), expected.len());
+  for (stroke, expected) in strokes.iter().zip(&expected) {
+    for (point, expected) in stroke.points().iter().zip(expected.points()) {
+      assert!((point - expected).norm() < 1e-4);
+    }
+  }
+
+  // a crash while appending the removal
+  let replayed = replay(&data[..data.len() - 3]);
+  assert_eq!(replayed.access().strokes().count(), expected.len() + 1);
+}
+
+#[bench]
+fn save_ron(bencher: &mut Bencher) {
+  let savefile = savefile();
+  bencher.iter(|| to_ron(&savefile));
+}
+
+#[bench]
+fn save_compact(bencher: &mut Bencher) {
+  let savefile = savefile();
+  bencher.iter(|| to_bytes(&savefile, false).unwrap());
+}
+
+#[bench]
+fn load_ron(bencher: &mut Bencher) {
+  let ron = to_ron(&savefile());
+  bencher.iter(|| ron::from_str::<Savefile>(&ron).unwrap());
+}
+
+#[bench]
+fn load_compact(bencher: &mut Bencher) {
+  let bytes = to_bytes(&savefile(), false).unwrap();
+  bencher.iter(|| from_bytes(&bytes).unwrap());
+}
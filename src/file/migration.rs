@@ -0,0 +1,36 @@
+use super::{tree::Node, FileError, SAVEFILE_VERSION};
+
+/// Upgrades a savefile by one version, the one at index `n` from version `n` to `n + 1`.
+type Migration = fn(&mut Node);
+
+/// Adding a version means adding its migration here, bumping `SAVEFILE_VERSION`
+/// and adding a savefile of the new version to `tests/fixtures`.
+const MIGRATIONS: [Migration; SAVEFILE_VERSION as usize] = [
+  // 0 -> 1: the savefiles from before the header wrote palette colors as maps
+  |savefile| savefile.visit_mut(&mut color_map_to_tuple),
+];
+
+/// Rewrites the ron of an older savefile into the current format.
+pub fn migrate(ron: &str, version: u32) -> Result<String, FileError> {
+  let mut savefile = Node::parse(ron)?;
+  for migration in &MIGRATIONS[version as usize..] {
+    migration(&mut savefile);
+  }
+  Ok(savefile.to_string())
+}
+
+/// palette wrote colors as `{"red": r, "green": g, "blue": b, "alpha": a}`
+fn color_map_to_tuple(node: &mut Node) {
+  let Node::Map(entries) = node else { return };
+  let channels = ["\"red\"", "\"green\"", "\"blue\"", "\"alpha\""].map(|channel| {
+    entries
+      .iter()
+      .find(|(key, _)| *key == Node::Atom(channel.to_owned()))
+      .map(|(_, value)| value.clone())
+  });
+  if entries.len() == 4 {
+    if let [Some(red), Some(green), Some(blue), Some(alpha)] = channels {
+      *node = Node::Tuple(None, vec![red, green, blue, alpha]);
+    }
+  }
+}
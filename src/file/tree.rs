@@ -0,0 +1,254 @@
+use super::FileError;
+
+use std::fmt::{self, Write};
+
+/// The syntax tree of a ron document, keeping the names of structs and enum variants,
+/// so migrations can rewrite old savefiles before they are deserialized.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Node {
+  /// numbers, strings, chars, booleans and unit values, as written
+  Atom(String),
+  /// `Name(a, b)`, `(a, b)` or `Some(a)`
+  Tuple(Option<String>, Vec<Node>),
+  /// `Name(field: a)` or `(field: a)`
+  Struct(Option<String>, Vec<(String, Node)>),
+  List(Vec<Node>),
+  Map(Vec<(Node, Node)>),
+}
+
+impl Node {
+  pub fn parse(ron: &str) -> Result<Self, FileError> {
+    let mut parser = Parser { ron, i: 0 };
+    let node = parser.node()?;
+    parser.skip_whitespace();
+    match parser.i == ron.len() {
+      true => Ok(node),
+      false => Err(parser.error("trailing characters")),
+    }
+  }
+
+  /// Calls `f` on every node, children before their parents.
+  pub fn visit_mut(&mut self, f: &mut impl FnMut(&mut Node)) {
+    match self {
+      Node::Atom(_) => {}
+      Node::Tuple(_, items) | Node::List(items) => items.iter_mut().for_each(|n| n.visit_mut(f)),
+      Node::Struct(_, fields) => fields.iter_mut().for_each(|(_, n)| n.visit_mut(f)),
+      Node::Map(entries) => entries.iter_mut().for_each(|(k, v)| {
+        k.visit_mut(f);
+        v.visit_mut(f);
+      }),
+    }
+    f(self);
+  }
+}
+
+/// Writes the node back as compact ron.
+impl fmt::Display for Node {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    fn separated<T>(
+      f: &mut fmt::Formatter<'_>,
+      items: &[T],
+      mut item: impl FnMut(&mut fmt::Formatter<'_>, &T) -> fmt::Result,
+    ) -> fmt::Result {
+      for (i, it) in items.iter().enumerate() {
+        if i > 0 {
+          f.write_char(',')?;
+        }
+        item(f, it)?;
+      }
+      Ok(())
+    }
+
+    match self {
+      Node::Atom(atom) => f.write_str(atom),
+      Node::Tuple(name, items) => {
+        write!(f, "{}(", name.as_deref().unwrap_or_default())?;
+        separated(f, items, |f, item| write!(f, "{item}"))?;
+        f.write_char(')')
+      }
+      Node::Struct(name, fields) => {
+        write!(f, "{}(", name.as_deref().unwrap_or_default())?;
+        separated(f, fields, |f, (name, value)| write!(f, "{name}:{value}"))?;
+        f.write_char(')')
+      }
+      Node::List(items) => {
+        f.write_char('[')?;
+        separated(f, items, |f, item| write!(f, "{item}"))?;
+        f.write_char(']')
+      }
+      Node::Map(entries) => {
+        f.write_char('{')?;
+        separated(f, entries, |f, (key, value)| write!(f, "{key}:{value}"))?;
+        f.write_char('}')
+      }
+    }
+  }
+}
+
+struct Parser<'a> {
+  ron: &'a str,
+  /// byte offset
+  i: usize,
+}
+
+impl<'a> Parser<'a> {
+  fn rest(&self) -> &'a str {
+    &self.ron[self.i..]
+  }
+
+  fn peek(&self) -> Option<char> {
+    self.rest().chars().next()
+  }
+
+  fn error(&self, message: &str) -> FileError {
+    let before = &self.ron[..self.i];
+    let line = before.matches('\n').count() + 1;
+    let column = before.len() - before.rfind('\n').map_or(0, |i| i + 1) + 1;
+    FileError::Parse {
+      message: message.to_owned(),
+      line,
+      column,
+    }
+  }
+
+  fn skip_whitespace(&mut self) {
+    loop {
+      let rest = self.rest();
+      let trimmed = rest.trim_start();
+      self.i += rest.len() - trimmed.len();
+      if trimmed.starts_with("//") {
+        self.i += trimmed.find('\n').unwrap_or(trimmed.len());
+      } else if trimmed.starts_with("/*") {
+        self.i += trimmed.find("*/").map_or(trimmed.len(), |i| i + 2);
+      } else {
+        return;
+      }
+    }
+  }
+
+  fn expect(&mut self, c: char) -> Result<(), FileError> {
+    self.skip_whitespace();
+    match self.peek() == Some(c) {
+      true => {
+        self.i += c.len_utf8();
+        Ok(())
+      }
+      false => Err(self.error(&format!("expected `{c}`"))),
+    }
+  }
+
+  /// Parses the items up to `close`, which may have a trailing comma.
+  fn items<T>(
+    &mut self,
+    close: char,
+    mut item: impl FnMut(&mut Self) -> Result<T, FileError>,
+  ) -> Result<Vec<T>, FileError> {
+    let mut items = Vec::new();
+    loop {
+      self.skip_whitespace();
+      if self.peek() == Some(close) {
+        self.i += close.len_utf8();
+        return Ok(items);
+      }
+      items.push(item(self)?);
+      self.skip_whitespace();
+      match self.peek() {
+        Some(',') => self.i += 1,
+        Some(c) if c == close => {}
+        _ => return Err(self.error(&format!("expected `,` or `{close}`"))),
+      }
+    }
+  }
+
+  fn node(&mut self) -> Result<Node, FileError> {
+    self.skip_whitespace();
+    match self.peek() {
+      Some('[') => {
+        self.i += 1;
+        Ok(Node::List(self.items(']', Self::node)?))
+      }
+      Some('{') => {
+        self.i += 1;
+        let entries = self.items('}', |parser| {
+          let key = parser.node()?;
+          parser.expect(':')?;
+          Ok((key, parser.node()?))
+        })?;
+        Ok(Node::Map(entries))
+      }
+      Some('(') => self.parenthesized(None),
+      Some(quote @ ('"' | '\'')) => self.quoted(quote),
+      Some(_) => {
+        let word = self.word();
+        if word.is_empty() {
+          return Err(self.error("expected a value"));
+        }
+        let is_name = word.starts_with(|c: char| c.is_alphabetic() || c == '_');
+        let after_word = self.i;
+        self.skip_whitespace();
+        match is_name && self.peek() == Some('(') {
+          true => self.parenthesized(Some(word.to_owned())),
+          false => {
+            self.i = after_word;
+            Ok(Node::Atom(word.to_owned()))
+          }
+        }
+      }
+      None => Err(self.error("unexpected end")),
+    }
+  }
+
+  /// identifiers and numbers
+  fn word(&mut self) -> &'a str {
+    let rest = self.rest();
+    let len = rest
+      .find(|c: char| !(c.is_alphanumeric() || "_+-.".contains(c)))
+      .unwrap_or(rest.len());
+    self.i += len;
+    &rest[..len]
+  }
+
+  /// a tuple or a struct, told apart by the first `field:`
+  fn parenthesized(&mut self, name: Option<String>) -> Result<Node, FileError> {
+    self.expect('(')?;
+    self.skip_whitespace();
+    let start = self.i;
+    let word = self.word();
+    let is_struct = !word.is_empty() && {
+      self.skip_whitespace();
+      self.peek() == Some(':')
+    };
+    self.i = start;
+
+    match is_struct {
+      true => {
+        let fields = self.items(')', |parser| {
+          parser.skip_whitespace();
+          let field = parser.word().to_owned();
+          parser.expect(':')?;
+          Ok((field, parser.node()?))
+        })?;
+        Ok(Node::Struct(name, fields))
+      }
+      false => Ok(Node::Tuple(name, self.items(')', Self::node)?)),
+    }
+  }
+
+  /// strings and chars
+  fn quoted(&mut self, quote: char) -> Result<Node, FileError> {
+    let rest = self.rest();
+    let mut is_escaped = false;
+    for (i, c) in rest.char_indices().skip(1) {
+      match c {
+        _ if is_escaped => is_escaped = false,
+        '\\' => is_escaped = true,
+        _ if c == quote => {
+          self.i += i + 1;
+          return Ok(Node::Atom(rest[..=i].to_owned()));
+        }
+        _ => {}
+      }
+    }
+    Err(self.error("unterminated string"))
+  }
+}
@@ -1,16 +1,23 @@
+#[cfg(feature = "app")]
 pub mod canvas;
+#[cfg(feature = "app")]
 pub mod pdf;
 pub mod stroke;
+#[cfg(feature = "app")]
 pub mod ui;
 
 mod mesh;
 
+#[cfg(feature = "app")]
 use self::{canvas::CanvasRenderer, ui::UiRenderer};
 
+#[cfg(feature = "app")]
 use crate::{pdf::PdfManager, spaces::SpaceManager, stroke::StrokeManager};
 
+#[cfg(feature = "app")]
 use winit::window::Window;
 
+#[cfg(feature = "app")]
 pub struct Gfx {
   wgpu: WgpuCtx,
 
@@ -18,12 +25,14 @@ pub struct Gfx {
   canvas_renderer: CanvasRenderer,
 }
 
+#[cfg(feature = "app")]
 impl Gfx {
   pub async fn init(window: &winit::window::Window) -> Self {
     let wgpu = WgpuCtx::init(window).await;
 
     let ui_renderer = UiRenderer::init(&wgpu.device, wgpu.surface_configuration.format);
-    let canvas_renderer = CanvasRenderer::init(&wgpu.device, wgpu.surface_configuration.format);
+    let canvas_renderer =
+      CanvasRenderer::init(&wgpu.device, &wgpu.queue, wgpu.surface_configuration.format);
 
     Self {
       wgpu,
@@ -40,7 +49,7 @@ impl Gfx {
     egui_shapes: Vec<egui::epaint::ClippedShape>,
     egui_textures_delta: egui::TexturesDelta,
 
-    pdf_manager: Option<&PdfManager>,
+    pdf_manager: &mut PdfManager,
     spaces: &SpaceManager,
   ) {
     let mut encoder = self
@@ -51,6 +60,10 @@ impl Gfx {
     self
       .canvas_renderer
       .prepare(&self.wgpu.device, &self.wgpu.queue, spaces, pdf_manager);
+    // keep drawing frames until the pdf worker delivered everything
+    if self.canvas_renderer.is_waiting_for_tiles() {
+      egui_ctx.request_repaint();
+    }
 
     self.ui_renderer.prepare(
       window,
@@ -96,7 +109,7 @@ impl Gfx {
         color_attachments: &[Some(wgpu::RenderPassColorAttachment {
           view: render_target,
           ops: wgpu::Operations {
-            load: wgpu::LoadOp::Clear(wgpu::Color::WHITE),
+            load: wgpu::LoadOp::Clear(self.canvas_renderer.background_color()),
             store: true,
           },
           resolve_target: None,
@@ -123,6 +136,7 @@ impl Gfx {
   }
 }
 
+#[cfg(feature = "app")]
 pub struct WgpuCtx {
   device: wgpu::Device,
   queue: wgpu::Queue,
@@ -130,6 +144,7 @@ pub struct WgpuCtx {
   surface_configuration: wgpu::SurfaceConfiguration,
 }
 
+#[cfg(feature = "app")]
 impl WgpuCtx {
   pub async fn init(window: &winit::window::Window) -> Self {
     let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::default());
@@ -187,18 +202,31 @@ impl WgpuCtx {
   }
 }
 
+#[cfg(feature = "app")]
 impl WgpuCtx {
   pub fn device(&self) -> &wgpu::Device {
     &self.device
   }
 }
 
+#[cfg(feature = "app")]
 pub struct BufferSized {
   pub buffer: wgpu::Buffer,
   pub size: wgpu::BufferSize,
 }
+#[cfg(feature = "app")]
 impl BufferSized {
   pub fn new(buffer: wgpu::Buffer, size: wgpu::BufferSize) -> Self {
     Self { buffer, size }
   }
 }
+
+/// a shader module with the color filter functions prepended to `source`
+#[cfg(feature = "app")]
+fn create_filtered_shader(device: &wgpu::Device, label: &str, source: &str) -> wgpu::ShaderModule {
+  let source = format!("{}\n{}", include_str!("gfx/filter.wgsl"), source);
+  device.create_shader_module(wgpu::ShaderModuleDescriptor {
+    label: Some(label),
+    source: wgpu::ShaderSource::Wgsl(source.into()),
+  })
+}
@@ -12,10 +12,14 @@ pub struct CanvasRenderer {
   pdf_renderer: PdfRenderer,
   stroke_renderer: StrokeRenderer,
   camera_buffer: BufferSized,
+  filter_buffer: BufferSized,
+  background_color: wgpu::Color,
+  /// whether the render target stores the linear colors without encoding them
+  encodes_gamma: bool,
 }
 
 impl CanvasRenderer {
-  pub fn init(device: &wgpu::Device, format: wgpu::TextureFormat) -> Self {
+  pub fn init(device: &wgpu::Device, queue: &wgpu::Queue, format: wgpu::TextureFormat) -> Self {
     let camera_buffer_size = CameraUniform::min_size();
     let camera_buffer = device.create_buffer(&wgpu::BufferDescriptor {
       label: Some("stroke_renderer_camera_ubo"),
@@ -25,13 +29,25 @@ impl CanvasRenderer {
     });
     let camera_buffer = BufferSized::new(camera_buffer, camera_buffer_size);
 
-    let pdf_renderer = PdfRenderer::init(device, format);
-    let stroke_renderer = StrokeRenderer::init(device, format, &camera_buffer);
+    let filter_buffer_size = FilterUniform::min_size();
+    let filter_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+      label: Some("canvas_renderer_filter_ubo"),
+      size: filter_buffer_size.into(),
+      usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+      mapped_at_creation: false,
+    });
+    let filter_buffer = BufferSized::new(filter_buffer, filter_buffer_size);
+
+    let pdf_renderer = PdfRenderer::init(device, queue, format, &camera_buffer, &filter_buffer);
+    let stroke_renderer = StrokeRenderer::init(device, format, &camera_buffer, &filter_buffer);
 
     Self {
       pdf_renderer,
       stroke_renderer,
       camera_buffer,
+      filter_buffer,
+      background_color: wgpu::Color::WHITE,
+      encodes_gamma: !format.describe().srgb,
     }
   }
 
@@ -40,7 +56,7 @@ impl CanvasRenderer {
     device: &wgpu::Device,
     queue: &wgpu::Queue,
     spaces: &SpaceManager,
-    pdf_manager: Option<&PdfManager>,
+    pdf_manager: &mut PdfManager,
   ) {
     let view: na::Transform2<f32> = na::convert(spaces.canvas_to_view());
     let projection: na::Transform2<f32> = na::convert(spaces.canvas_view_to_screen_norm());
@@ -53,11 +69,41 @@ impl CanvasRenderer {
     let byte_buffer = buffer.into_inner();
     queue.write_buffer(&self.camera_buffer.buffer, 0, &byte_buffer);
 
-    if let Some(pdf_manager) = pdf_manager {
-      self
-        .pdf_renderer
-        .prepare(device, queue, spaces, pdf_manager);
-    }
+    let filter = pdf_manager.color_filter();
+    let tint = filter.tint();
+    let filter_uniform = FilterUniform {
+      invert_lightness: filter.inverts_lightness().into(),
+      grayscale: filter.is_grayscale().into(),
+      remap_ink: pdf_manager.filters_ink().into(),
+      encode_gamma: self.encodes_gamma.into(),
+      tint: na::vector![tint.red, tint.green, tint.blue],
+    };
+    let mut buffer = UniformBuffer::new(Vec::new());
+    buffer.write(&filter_uniform).unwrap();
+    let byte_buffer = buffer.into_inner();
+    queue.write_buffer(&self.filter_buffer.buffer, 0, &byte_buffer);
+
+    // the clear color is linear like the surface
+    let paper = filter.paper_color();
+    self.background_color = wgpu::Color {
+      r: paper.red.into(),
+      g: paper.green.into(),
+      b: paper.blue.into(),
+      a: 1.0,
+    };
+
+    self
+      .pdf_renderer
+      .prepare(device, queue, spaces, pdf_manager);
+  }
+
+  /// the color around the pages, filtered like the paper
+  pub fn background_color(&self) -> wgpu::Color {
+    self.background_color
+  }
+
+  pub fn is_waiting_for_tiles(&self) -> bool {
+    self.pdf_renderer.is_waiting_for_tiles()
   }
 
   pub fn render<'rp>(
@@ -80,6 +126,15 @@ impl CanvasRenderer {
       1.0,
     );
 
+    self.draw(render_pass, stroke_manager);
+  }
+
+  /// Draws into the viewport the render pass already has, e.g. the rect of an egui paint callback.
+  pub fn draw<'rp>(
+    &'rp self,
+    render_pass: &mut wgpu::RenderPass<'rp>,
+    stroke_manager: &'rp StrokeManager,
+  ) {
     self.pdf_renderer.render(render_pass);
     self.stroke_renderer.render(render_pass, stroke_manager);
   }
@@ -89,3 +144,12 @@ impl CanvasRenderer {
 struct CameraUniform {
   view_projection: na::Matrix3<f32>,
 }
+
+#[derive(ShaderType)]
+struct FilterUniform {
+  invert_lightness: u32,
+  grayscale: u32,
+  remap_ink: u32,
+  encode_gamma: u32,
+  tint: na::Vector3<f32>,
+}
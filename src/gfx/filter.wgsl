@@ -0,0 +1,31 @@
+struct FilterUniform {
+  invert_lightness: u32,
+  grayscale: u32,
+  remap_ink: u32,
+  encode_gamma: u32,
+  tint: vec3<f32>,
+};
+
+// for render targets without srgb encoding, like the ones egui prefers
+fn encode_output(color: vec4<f32>, params: FilterUniform) -> vec4<f32> {
+  if (params.encode_gamma == 0u) {
+    return color;
+  }
+  return vec4<f32>(pow(color.rgb, vec3<f32>(1.0 / 2.2)), color.a);
+}
+
+// `color` is linear
+fn apply_filter(color: vec3<f32>, params: FilterUniform) -> vec3<f32> {
+  var c = color;
+  if (params.grayscale != 0u) {
+    c = vec3<f32>(dot(c, vec3<f32>(0.2126, 0.7152, 0.0722)));
+  }
+  if (params.invert_lightness != 0u) {
+    // adding 1 - (max + min) inverts the hsl lightness and keeps hue and saturation.
+    // done in gamma space, so mid tones stay mid tones.
+    let s = pow(c, vec3<f32>(1.0 / 2.2));
+    let inverted = s + 1.0 - (max(max(s.r, s.g), s.b) + min(min(s.r, s.g), s.b));
+    c = pow(clamp(inverted, vec3<f32>(0.0), vec3<f32>(1.0)), vec3<f32>(2.2));
+  }
+  return c * params.tint;
+}
@@ -1,3 +1,4 @@
+#[cfg(feature = "app")]
 use wgpu::util::DeviceExt;
 
 pub type Tessellation<Vertex> = lyon::tessellation::VertexBuffers<Vertex, u32>;
@@ -21,11 +22,13 @@ impl<Vertex> MeshCpu<Vertex> {
   }
 }
 
+#[cfg(feature = "app")]
 pub struct MeshGpu {
   vertex_buffer: wgpu::Buffer,
   index_buffer: wgpu::Buffer,
   index_count: u32,
 }
+#[cfg(feature = "app")]
 impl MeshGpu {
   pub fn from_mesh_cpu<Vertex: bytemuck::Pod>(
     mesh_cpu: &MeshCpu<Vertex>,
@@ -1,91 +1,134 @@
+pub mod tile;
+
+use self::tile::{Tile, TileCache};
+
+use super::{create_filtered_shader, BufferSized};
+
 use crate::{
   math::Rect,
-  natrans,
-  pdf::PdfManager,
-  spaces::{Camera, Space, SpaceManager},
+  pdf::{
+    tile::{TileKey, TILE_SIZE_PHYSICAL},
+    PdfManager, RasterRequest, RasterResponse,
+  },
+  spaces::{Space, SpaceManager},
 };
 
-use pdfium_render::prelude::*;
 use std::mem;
 use wgpu::util::DeviceExt;
 
-// from `PdfPagePaperStandardSize::A4.width()`
-const A4_WIDTH_PDF_POINTS: f32 = 210.0;
-const CANVAS_UNITS_PER_PDF_POINT: f32 = 2.0 / A4_WIDTH_PDF_POINTS;
-
 pub struct PdfRenderer {
   pipeline: wgpu::RenderPipeline,
-  bind_group_layout: wgpu::BindGroupLayout,
-  bind_group: Option<wgpu::BindGroup>,
+  camera_bind_group: wgpu::BindGroup,
+  tile_bind_group_layout: wgpu::BindGroupLayout,
+  sampler: wgpu::Sampler,
   index_buffer: wgpu::Buffer,
-  vertex_buffer: wgpu::Buffer,
 
-  prev_camera: Camera,
-  prev_screen_rect: Rect,
-  static_since_nframes: u32,
+  placeholder_bind_group: wgpu::BindGroup,
+  _placeholder_texture: wgpu::Texture,
+
+  tile_cache: TileCache,
+  /// tiles the worker is rasterizing, most important first
+  requested_tiles: Vec<TileKey>,
+  page_placeholders: Vec<wgpu::Buffer>,
+  page_rects_canvas: Vec<Rect>,
+  draw_list: Vec<DrawItem>,
+  pages_generation: u64,
 }
 
+/// how many coarser zoom levels are searched for a tile to show while the actual one is missing
+const MAX_FALLBACK_LEVELS: i32 = 4;
+
 impl PdfRenderer {
-  pub fn init(device: &wgpu::Device, format: wgpu::TextureFormat) -> Self {
+  pub fn init(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    format: wgpu::TextureFormat,
+    camera_buffer: &BufferSized,
+    filter_buffer: &BufferSized,
+  ) -> Self {
     // a rectangle consiting of two triangles
     const INDICES: [u32; 6] = [0, 1, 2, 2, 3, 0];
     let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-      label: Some("pdf_vertex_buffer"),
+      label: Some("pdf_index_buffer"),
       contents: bytemuck::cast_slice(&INDICES),
       usage: wgpu::BufferUsages::INDEX,
     });
 
-    // a screen norm rectangle constiting of two triangles
-    const VERTICES: [PdfVertex; 4] = [
-      PdfVertex {
-        position: [-1.0, -1.0],
-        tex_coords: [0.0, 0.0],
-      },
-      PdfVertex {
-        position: [-1.0, 1.0],
-        tex_coords: [0.0, 1.0],
-      },
-      PdfVertex {
-        position: [1.0, 1.0],
-        tex_coords: [1.0, 1.0],
-      },
-      PdfVertex {
-        position: [1.0, -1.0],
-        tex_coords: [1.0, 0.0],
-      },
-    ];
-    let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-      label: Some("pdf_vertex_buffer"),
-      contents: bytemuck::cast_slice(&VERTICES),
-      usage: wgpu::BufferUsages::VERTEX,
-    });
-
-    let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-      label: Some("pdf_renderer_bind_group_layout"),
+    let camera_bind_group_layout =
+      device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label: Some("pdf_renderer_camera_bind_group_layout"),
+        entries: &[
+          wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility: wgpu::ShaderStages::VERTEX,
+            ty: wgpu::BindingType::Buffer {
+              ty: wgpu::BufferBindingType::Uniform,
+              has_dynamic_offset: false,
+              min_binding_size: Some(camera_buffer.size),
+            },
+            count: None,
+          },
+          wgpu::BindGroupLayoutEntry {
+            binding: 1,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Buffer {
+              ty: wgpu::BufferBindingType::Uniform,
+              has_dynamic_offset: false,
+              min_binding_size: Some(filter_buffer.size),
+            },
+            count: None,
+          },
+        ],
+      });
+    let camera_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+      label: Some("pdf_renderer_camera_bind_group"),
+      layout: &camera_bind_group_layout,
       entries: &[
-        wgpu::BindGroupLayoutEntry {
+        wgpu::BindGroupEntry {
           binding: 0,
-          visibility: wgpu::ShaderStages::FRAGMENT,
-          ty: wgpu::BindingType::Texture {
-            sample_type: wgpu::TextureSampleType::Float { filterable: true },
-            view_dimension: wgpu::TextureViewDimension::D2,
-            multisampled: false,
-          },
-          count: None,
+          resource: camera_buffer.buffer.as_entire_binding(),
         },
-        wgpu::BindGroupLayoutEntry {
+        wgpu::BindGroupEntry {
           binding: 1,
-          visibility: wgpu::ShaderStages::FRAGMENT,
-          ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
-          count: None,
+          resource: filter_buffer.buffer.as_entire_binding(),
         },
       ],
     });
-    let bind_group = None;
+
+    let tile_bind_group_layout =
+      device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label: Some("pdf_renderer_tile_bind_group_layout"),
+        entries: &[
+          wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+              sample_type: wgpu::TextureSampleType::Float { filterable: true },
+              view_dimension: wgpu::TextureViewDimension::D2,
+              multisampled: false,
+            },
+            count: None,
+          },
+          wgpu::BindGroupLayoutEntry {
+            binding: 1,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+            count: None,
+          },
+        ],
+      });
+    let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+      label: Some("pdf_tile_sampler"),
+      address_mode_u: wgpu::AddressMode::ClampToEdge,
+      address_mode_v: wgpu::AddressMode::ClampToEdge,
+      mag_filter: wgpu::FilterMode::Linear,
+      min_filter: wgpu::FilterMode::Linear,
+      ..Default::default()
+    });
 
     let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
       label: Some("pdf_renderer_pipeline_layout"),
-      bind_group_layouts: &[&bind_group_layout],
+      bind_group_layouts: &[&camera_bind_group_layout, &tile_bind_group_layout],
       push_constant_ranges: &[],
     });
 
@@ -95,7 +138,7 @@ impl PdfRenderer {
       write_mask: wgpu::ColorWrites::ALL,
     })];
 
-    let shader = device.create_shader_module(wgpu::include_wgsl!("pdf/shader.wgsl"));
+    let shader = create_filtered_shader(device, "pdf_shader", include_str!("pdf/shader.wgsl"));
 
     let pipeline_descriptor = wgpu::RenderPipelineDescriptor {
       label: Some("pdf_render_pipeline"),
@@ -129,20 +172,33 @@ impl PdfRenderer {
     };
     let pipeline = device.create_render_pipeline(&pipeline_descriptor);
 
-    let prev_camera = Camera::default();
-    let prev_screen_rect = Rect::default();
-    let static_since_nframes = 0;
+    const WHITE: [u8; 4] = [u8::MAX; 4];
+    let (placeholder_texture, placeholder_bind_group) =
+      create_texture(device, queue, &tile_bind_group_layout, &sampler, 1, &WHITE);
+
+    let tile_cache = TileCache::default();
+    let requested_tiles = Vec::new();
+    let page_placeholders = Vec::new();
+    let page_rects_canvas = Vec::new();
+    let draw_list = Vec::new();
+    let pages_generation = 0;
 
     Self {
       pipeline,
-      bind_group_layout,
-      bind_group,
+      camera_bind_group,
+      tile_bind_group_layout,
+      sampler,
       index_buffer,
-      vertex_buffer,
 
-      prev_camera,
-      prev_screen_rect,
-      static_since_nframes,
+      placeholder_bind_group,
+      _placeholder_texture: placeholder_texture,
+
+      tile_cache,
+      requested_tiles,
+      page_placeholders,
+      page_rects_canvas,
+      draw_list,
+      pages_generation,
     }
   }
 
@@ -151,181 +207,278 @@ impl PdfRenderer {
     device: &wgpu::Device,
     queue: &wgpu::Queue,
     spaces: &SpaceManager,
-    pdf_manager: &PdfManager,
+    pdf_manager: &mut PdfManager,
   ) {
-    let screen_size_physical = spaces.transform_vector(
-      spaces.screen_rect_window_logical().size(),
-      Space::WindowLogical,
-      Space::WindowPhysical,
-    );
-
-    let is_camera_and_screen_static = self.prev_camera == *spaces.camera()
-      && self.prev_screen_rect == spaces.screen_rect_window_logical();
-    if is_camera_and_screen_static {
-      self.static_since_nframes += 1;
-    } else {
-      self.static_since_nframes = 0;
+    self.draw_list.clear();
+    self.tile_cache.begin_frame();
+
+    // the tiles are keyed by the position in the page list
+    let pages_generation = pdf_manager.pages_generation();
+    if pages_generation != self.pages_generation {
+      self.tile_cache.clear();
+      self.requested_tiles.clear();
+      self.page_placeholders.clear();
+      self.page_rects_canvas.clear();
+      self.pages_generation = pages_generation;
     }
+    if pdf_manager.npages() == 0 {
+      return;
+    }
+    let page_rects_canvas = pdf_manager.page_rects_canvas();
+    self.update_page_rects(device, queue, page_rects_canvas);
+    let page_rects_canvas = &self.page_rects_canvas;
 
-    const NIMPROVEMENTS: u32 = 4;
-    const NFRAMES_BETWEEN_IMPROVEMENTS: u32 = 5;
-    const NFRAMES_ALL_IMPROVEMENTS: u32 = (NIMPROVEMENTS - 1) * NFRAMES_BETWEEN_IMPROVEMENTS;
-    const MIN_RESOLUTION: f32 = 0.25;
-    const RESOLUTION_STEP_SIZE: f32 = (1.0 - MIN_RESOLUTION) / NIMPROVEMENTS as f32;
-
-    let (resolution_factor, has_resolution_increased) =
-      match self.static_since_nframes % NFRAMES_BETWEEN_IMPROVEMENTS == 0
-        && self.static_since_nframes <= NFRAMES_ALL_IMPROVEMENTS
-      {
-        true => (
-          (self.static_since_nframes / NFRAMES_BETWEEN_IMPROVEMENTS + 1) as f32
-            * RESOLUTION_STEP_SIZE,
-          true,
-        ),
-        false => (1.0, false),
-      };
-
-    let texture_size_physical = screen_size_physical * resolution_factor;
-    let texture_width_u16 = texture_size_physical.x as u16;
-    let texture_height_u16 = texture_size_physical.y as u16;
-    let texture_width_u32 = texture_width_u16 as u32;
-    let texture_height_u32 = texture_height_u16 as u32;
-
-    let was_transform_updated = !is_camera_and_screen_static || has_resolution_increased;
-    self.prev_camera = spaces.camera().clone();
-    self.prev_screen_rect = spaces.screen_rect_window_logical();
+    for response in pdf_manager.receive_tiles() {
+      if pdf_manager.pdf_page(response.tile.page) != Some((response.document, response.page)) {
+        continue;
+      }
+      let page_rect_canvas = &page_rects_canvas[response.tile.page];
+      let tile = create_tile(
+        device,
+        queue,
+        &self.tile_bind_group_layout,
+        &self.sampler,
+        page_rect_canvas,
+        &response,
+      );
+      self.tile_cache.insert(response.tile, tile);
+    }
 
+    let zoom_level = zoom_level(spaces);
     let screen_rect_canvas = spaces.transform_rect(
       spaces.screen_rect_window_logical(),
       Space::WindowLogical,
       Space::Canvas,
     );
 
-    let texture_nbytes = 4 * texture_width_u32 as usize * texture_height_u32 as usize;
-    let mut texture_data = vec![0u8; texture_nbytes];
-    let mut page_center_canvas = na::Point2::origin();
-    let mut was_a_page_rendered = false;
-    pdf_manager.page_slice().iter().for_each(|page| {
-      let page_size_canvas = na::vector![
-        page.width().value * CANVAS_UNITS_PER_PDF_POINT,
-        page.height().value * CANVAS_UNITS_PER_PDF_POINT
-      ];
-      let page_rect_canvas = Rect::from_size_center(page_size_canvas, page_center_canvas);
-
-      let _is_visible = parry2d::query::intersection_test(
-        &page_rect_canvas.isometry(),
-        &page_rect_canvas.shape(),
-        &screen_rect_canvas.isometry(),
-        &screen_rect_canvas.shape(),
-      )
-      .unwrap();
-      let is_visible = true;
-
-      let should_render = is_visible && (was_transform_updated || has_resolution_increased);
-      if should_render {
-        was_a_page_rendered = true;
-
-        let transform = page_to_texture_renderer_transform(
-          page,
-          page_center_canvas,
-          texture_size_physical,
-          spaces,
-        );
-        page_center_canvas.y += page_size_canvas.y * 1.05;
-
-        let render_config = PdfRenderConfig::default()
-          .clear_before_rendering(false)
-          .set_target_size(texture_width_u16, texture_height_u16)
-          .set_maximum_width(texture_width_u16)
-          .set_maximum_height(texture_height_u16)
-          .transform(
-            transform.m11,
-            transform.m21,
-            transform.m12,
-            transform.m22,
-            transform.m13,
-            transform.m23,
-          )
-          .unwrap();
-
-        // TODO: stop recreating bitmap
-        let mut bitmap = PdfBitmap::empty(
-          texture_width_u16,
-          texture_height_u16,
-          PdfBitmapFormat::default(),
-          page.bindings(),
-        )
-        .unwrap();
-        page
-          .render_into_bitmap_with_config(&mut bitmap, &render_config)
-          .unwrap();
-
-        texture_data
-          .iter_mut()
-          .zip(bitmap.as_bytes())
-          .for_each(|(store, new)| *store = store.saturating_add(*new));
-
-        assert_eq!(texture_data.len(), bitmap.as_bytes().len());
+    let mut fallback_tiles = Vec::new();
+    let mut current_tiles = Vec::new();
+    let mut missing_tiles = Vec::new();
+    for ipage in visible_pages(page_rects_canvas, &screen_rect_canvas) {
+      self.draw_list.push(DrawItem::Placeholder(ipage));
+      // blank pages and the ones pdfium cannot render are just the placeholder
+      if pdf_manager.pdf_page(ipage).is_none() || pdf_manager.is_render_failed(ipage) {
+        continue;
       }
-    });
 
-    if was_a_page_rendered {
-      let size = wgpu::Extent3d {
-        width: texture_width_u32,
-        height: texture_height_u32,
-        depth_or_array_layers: 1,
+      let page_rect_canvas = &page_rects_canvas[ipage];
+      for key in visible_tiles(ipage, page_rect_canvas, &screen_rect_canvas, zoom_level) {
+        if self.tile_cache.touch(&key) {
+          current_tiles.push(key);
+          continue;
+        }
+
+        missing_tiles.push(key);
+        // until the tile arrives, show the closest coarser tile that is available
+        let fallback = (1..=MAX_FALLBACK_LEVELS)
+          .map(|nlevels| key.ancestor(nlevels))
+          .find(|ancestor| self.tile_cache.touch(ancestor));
+        if let Some(fallback) = fallback {
+          if !fallback_tiles.contains(&fallback) {
+            fallback_tiles.push(fallback);
+          }
+        }
+      }
+    }
+    fallback_tiles.sort_by_key(|key| key.zoom_level);
+    self
+      .draw_list
+      .extend(fallback_tiles.into_iter().map(DrawItem::Tile));
+    self
+      .draw_list
+      .extend(current_tiles.into_iter().map(DrawItem::Tile));
+
+    // tiles in the screen center first
+    let screen_center_canvas = screen_rect_canvas.center;
+    missing_tiles.sort_by(|a, b| {
+      let distance = |key: &TileKey| {
+        let center = key.rect_canvas(&page_rects_canvas[key.page]).center;
+        (center - screen_center_canvas).magnitude_squared()
       };
-      let texture = device.create_texture(&wgpu::TextureDescriptor {
-        label: Some("pdf_page"),
-        size,
-        mip_level_count: 1,
-        sample_count: 1,
-        dimension: wgpu::TextureDimension::D2,
-        format: wgpu::TextureFormat::Rgba8UnormSrgb,
-        usage: wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::TEXTURE_BINDING,
-        view_formats: &[],
-      });
-      queue.write_texture(
-        texture.as_image_copy(),
-        &texture_data,
-        wgpu::ImageDataLayout {
-          offset: 0,
-          bytes_per_row: std::num::NonZeroU32::new(4 * size.width),
-          rows_per_image: std::num::NonZeroU32::new(size.height),
-        },
-        size,
-      );
+      distance(a).total_cmp(&distance(b))
+    });
+    // only talk to the worker if the wanted tiles changed.
+    // this replaces the previous requests, which cancels stale ones.
+    if missing_tiles != self.requested_tiles {
+      let requests = missing_tiles
+        .iter()
+        .filter_map(|key| {
+          let (document, page) = pdf_manager.pdf_page(key.page)?;
+          Some(RasterRequest {
+            document,
+            page,
+            tile: *key,
+            page_rect_canvas: page_rects_canvas[key.page],
+          })
+        })
+        .collect();
+      pdf_manager.request_tiles(requests);
+      self.requested_tiles = missing_tiles;
+    }
 
-      let texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
-      let sampler = device.create_sampler(&wgpu::SamplerDescriptor::default());
+    self.tile_cache.evict();
+  }
 
-      self.bind_group = Some(device.create_bind_group(&wgpu::BindGroupDescriptor {
-        label: Some("pdf_renderer_bind_group"),
-        layout: &self.bind_group_layout,
-        entries: &[
-          wgpu::BindGroupEntry {
-            binding: 0,
-            resource: wgpu::BindingResource::TextureView(&texture_view),
-          },
-          wgpu::BindGroupEntry {
-            binding: 1,
-            resource: wgpu::BindingResource::Sampler(&sampler),
-          },
-        ],
-      }));
-    }
+  /// whether some visible tiles are still being rasterized.
+  pub fn is_waiting_for_tiles(&self) -> bool {
+    !self.requested_tiles.is_empty()
   }
 
   pub fn render<'rp>(&'rp self, render_pass: &mut wgpu::RenderPass<'rp>) {
-    if let Some(bind_group) = self.bind_group.as_ref() {
-      render_pass.set_pipeline(&self.pipeline);
-      render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
-      render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+    if self.draw_list.is_empty() {
+      return;
+    }
 
-      render_pass.set_bind_group(0, bind_group, &[]);
+    render_pass.set_pipeline(&self.pipeline);
+    render_pass.set_bind_group(0, &self.camera_bind_group, &[]);
+    render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+    for item in &self.draw_list {
+      let (bind_group, vertex_buffer) = match item {
+        DrawItem::Placeholder(ipage) => (
+          &self.placeholder_bind_group,
+          &self.page_placeholders[*ipage],
+        ),
+        DrawItem::Tile(key) => {
+          let tile = self.tile_cache.get(key).unwrap();
+          (tile.bind_group(), tile.vertex_buffer())
+        }
+      };
+      render_pass.set_bind_group(1, bind_group, &[]);
+      render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
       render_pass.draw_indexed(0..6, 0, 0..1);
     }
   }
+
+  /// moves the quads of all pages whose position changed, e.g. because of a new layout.
+  fn update_page_rects(
+    &mut self,
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    page_rects_canvas: Vec<Rect>,
+  ) {
+    if page_rects_canvas.len() != self.page_rects_canvas.len() {
+      self.page_placeholders = page_rects_canvas
+        .iter()
+        .map(|page_rect_canvas| create_quad(device, page_rect_canvas, page_rect_canvas))
+        .collect();
+      self.tile_cache.clear();
+      self.page_rects_canvas = page_rects_canvas;
+      return;
+    }
+
+    for (ipage, (old, new)) in self
+      .page_rects_canvas
+      .iter_mut()
+      .zip(page_rects_canvas)
+      .enumerate()
+    {
+      if *old == new {
+        continue;
+      }
+      *old = new;
+
+      let vertices = tile_vertices(&new, &new);
+      queue.write_buffer(
+        &self.page_placeholders[ipage],
+        0,
+        bytemuck::cast_slice(&vertices),
+      );
+      for (key, tile) in self.tile_cache.tiles_of_page(ipage) {
+        let vertices = tile_vertices(&new, &key.rect_canvas(&new));
+        queue.write_buffer(tile.vertex_buffer(), 0, bytemuck::cast_slice(&vertices));
+      }
+    }
+  }
+}
+
+enum DrawItem {
+  /// a blank page, shown before any tile arrived
+  Placeholder(usize),
+  Tile(TileKey),
+}
+
+fn create_tile(
+  device: &wgpu::Device,
+  queue: &wgpu::Queue,
+  bind_group_layout: &wgpu::BindGroupLayout,
+  sampler: &wgpu::Sampler,
+  page_rect_canvas: &Rect,
+  response: &RasterResponse,
+) -> Tile {
+  let (texture, bind_group) = create_texture(
+    device,
+    queue,
+    bind_group_layout,
+    sampler,
+    TILE_SIZE_PHYSICAL as u32,
+    &response.data,
+  );
+  let tile_rect_canvas = response.tile.rect_canvas(page_rect_canvas);
+  let vertex_buffer = create_quad(device, page_rect_canvas, &tile_rect_canvas);
+  Tile::new(texture, bind_group, vertex_buffer)
+}
+
+/// a square rgba texture together with its bind group
+fn create_texture(
+  device: &wgpu::Device,
+  queue: &wgpu::Queue,
+  bind_group_layout: &wgpu::BindGroupLayout,
+  sampler: &wgpu::Sampler,
+  size: u32,
+  data: &[u8],
+) -> (wgpu::Texture, wgpu::BindGroup) {
+  let size = wgpu::Extent3d {
+    width: size,
+    height: size,
+    depth_or_array_layers: 1,
+  };
+  let texture = device.create_texture(&wgpu::TextureDescriptor {
+    label: Some("pdf_tile"),
+    size,
+    mip_level_count: 1,
+    sample_count: 1,
+    dimension: wgpu::TextureDimension::D2,
+    format: wgpu::TextureFormat::Rgba8UnormSrgb,
+    usage: wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::TEXTURE_BINDING,
+    view_formats: &[],
+  });
+  queue.write_texture(
+    texture.as_image_copy(),
+    data,
+    wgpu::ImageDataLayout {
+      offset: 0,
+      bytes_per_row: std::num::NonZeroU32::new(4 * size.width),
+      rows_per_image: std::num::NonZeroU32::new(size.height),
+    },
+    size,
+  );
+
+  let texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+  let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+    label: Some("pdf_tile_bind_group"),
+    layout: bind_group_layout,
+    entries: &[
+      wgpu::BindGroupEntry {
+        binding: 0,
+        resource: wgpu::BindingResource::TextureView(&texture_view),
+      },
+      wgpu::BindGroupEntry {
+        binding: 1,
+        resource: wgpu::BindingResource::Sampler(sampler),
+      },
+    ],
+  });
+  (texture, bind_group)
+}
+
+/// a quad covering `rect_canvas` clipped to the page
+fn create_quad(device: &wgpu::Device, page_rect_canvas: &Rect, rect_canvas: &Rect) -> wgpu::Buffer {
+  let vertices = tile_vertices(page_rect_canvas, rect_canvas);
+  device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+    label: Some("pdf_quad_vertex_buffer"),
+    contents: bytemuck::cast_slice(&vertices),
+    usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+  })
 }
 
 #[repr(C)]
@@ -347,38 +500,203 @@ impl PdfVertex {
   }
 }
 
-fn page_to_texture_renderer_transform(
-  page: &PdfPage,
-  page_center_canvas: na::Point2<f32>,
-  texture_size_physical: na::Vector2<f32>,
-  spaces: &SpaceManager,
-) -> na::Matrix3<f32> {
-  let page_anchor = na::Point2::new(page.width().value / 2.0, page.height().value / 2.0);
-  let page_to_canvas = natrans!(na::Translation2::from(page_center_canvas))
-    * natrans!(na::Scale2::new(
-      CANVAS_UNITS_PER_PDF_POINT,
-      CANVAS_UNITS_PER_PDF_POINT
-    ))
-    * natrans!(na::Translation::from(-page_anchor));
-
-  let screen_norm_to_texture = {
-    let translation = na::Translation2::new(1.0, 1.0);
-    let scale = na::Scale2::new(texture_size_physical.x / 2.0, texture_size_physical.y / 2.0);
-    natrans!(scale) * natrans!(translation)
+/// the pages overlapping the screen
+fn visible_pages<'a>(
+  page_rects_canvas: &'a [Rect],
+  screen_rect_canvas: &'a Rect,
+) -> impl Iterator<Item = usize> + 'a {
+  // the screen rect is rotated in canvas space whenever the camera is
+  page_rects_canvas
+    .iter()
+    .enumerate()
+    .filter(|(_, page_rect_canvas)| page_rect_canvas.intersects(screen_rect_canvas))
+    .map(|(ipage, _)| ipage)
+}
+
+/// the tiles of the page at the zoom level overlapping the screen
+fn visible_tiles(
+  ipage: usize,
+  page_rect_canvas: &Rect,
+  screen_rect_canvas: &Rect,
+  zoom_level: i32,
+) -> Vec<TileKey> {
+  let tile_size_canvas = TileKey::tile_size_canvas(zoom_level);
+  let (screen_min_canvas, screen_max_canvas) = screen_rect_canvas.aabb();
+
+  // the range of tiles of this page overlapping the bounding box of the screen
+  let page_min_canvas = page_rect_canvas.center - page_rect_canvas.extents_half;
+  let ntiles = page_rect_canvas
+    .size()
+    .map(|e| (e / tile_size_canvas).ceil() as i64);
+  let tile_min = (screen_min_canvas - page_min_canvas)
+    .map(|e| (e / tile_size_canvas).floor() as i64)
+    .sup(&na::Vector2::zeros());
+  let tile_max = (screen_max_canvas - page_min_canvas)
+    .map(|e| (e / tile_size_canvas).ceil() as i64)
+    .inf(&ntiles);
+
+  let mut tiles = Vec::new();
+  for y in tile_min.y..tile_max.y {
+    for x in tile_min.x..tile_max.x {
+      let key = TileKey {
+        page: ipage,
+        zoom_level,
+        x: x as u32,
+        y: y as u32,
+      };
+      // with a rotated camera, the corners of the bounding box are not on screen
+      if key
+        .rect_canvas(page_rect_canvas)
+        .intersects(screen_rect_canvas)
+      {
+        tiles.push(key);
+      }
+    }
+  }
+  tiles
+}
+
+/// the smallest zoom level whose tiles have at least the resolution of the screen.
+fn zoom_level(spaces: &SpaceManager) -> i32 {
+  let screen_size_physical = spaces.transform_vector(
+    spaces.screen_rect_window_logical().size(),
+    Space::WindowLogical,
+    Space::WindowPhysical,
+  );
+  // the screen is two canvas units wide at zoom 1
+  let physical_per_canvas = screen_size_physical.x / 2.0 * spaces.camera().zoom;
+  physical_per_canvas.log2().ceil() as i32
+}
+
+/// a quad covering the tile, clipped to the page.
+fn tile_vertices(page_rect_canvas: &Rect, tile_rect_canvas: &Rect) -> [PdfVertex; 4] {
+  let page_min = page_rect_canvas.center - page_rect_canvas.extents_half;
+  let page_max = page_rect_canvas.center + page_rect_canvas.extents_half;
+  let tile_min = tile_rect_canvas.center - tile_rect_canvas.extents_half;
+  let tile_max = tile_rect_canvas.center + tile_rect_canvas.extents_half;
+  let tile_size = tile_rect_canvas.size();
+
+  let min = tile_min.sup(&page_min);
+  let max = tile_max.inf(&page_max);
+  let vertex = |x: f32, y: f32| PdfVertex {
+    position: [x, y],
+    tex_coords: [
+      (x - tile_min.x) / tile_size.x,
+      (y - tile_min.y) / tile_size.y,
+    ],
   };
 
-  // the transformation we want to do from page to the texture
-  let page_to_texture = screen_norm_to_texture
-    * natrans!(spaces.canvas_view_to_screen_norm())
-    * natrans!(spaces.canvas_to_view())
-    * page_to_canvas;
-
-  // for reverting what the pdf renderer is going to do automatically
-  let page_to_texture_scale = natrans!(na::Scale2::new(
-    page.width().value / texture_size_physical.x,
-    page.height().value / texture_size_physical.y
-  ));
-  // the transform to give to the renderer
-  let transform = page_to_texture_scale * page_to_texture;
-  transform.to_homogeneous()
+  [
+    vertex(min.x, min.y),
+    vertex(min.x, max.y),
+    vertex(max.x, max.y),
+    vertex(max.x, min.y),
+  ]
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{visible_pages, visible_tiles};
+  use crate::{math::Rect, pdf::tile::TileKey};
+
+  use std::f32::consts::FRAC_PI_4;
+
+  /// tiles of one canvas unit
+  const ZOOM_LEVEL: i32 = 8;
+
+  fn rect(size: [f32; 2], center: [f32; 2]) -> Rect {
+    Rect::from_size_center(size.into(), center.into())
+  }
+
+  /// the x and y of the tiles
+  fn tiles(page_rect_canvas: &Rect, screen_rect_canvas: &Rect) -> Vec<(u32, u32)> {
+    let tiles = visible_tiles(0, page_rect_canvas, screen_rect_canvas, ZOOM_LEVEL);
+    tiles.iter().map(|key| (key.x, key.y)).collect()
+  }
+
+  #[test]
+  fn culls_pages() {
+    assert_eq!(TileKey::tile_size_canvas(ZOOM_LEVEL), 1.0);
+    let pages = [
+      rect([2.0, 2.0], [0.0, 0.0]),
+      rect([2.0, 2.0], [0.0, 3.0]),
+      rect([2.0, 2.0], [0.0, 6.0]),
+    ];
+    let screen = rect([1.0, 1.0], [0.0, 3.0]);
+    assert_eq!(visible_pages(&pages, &screen).collect::<Vec<_>>(), [1]);
+    let screen = rect([1.0, 4.0], [0.0, 3.0]);
+    assert_eq!(
+      visible_pages(&pages, &screen).collect::<Vec<_>>(),
+      [0, 1, 2]
+    );
+    let screen = rect([1.0, 1.0], [5.0, 3.0]);
+    assert_eq!(visible_pages(&pages, &screen).count(), 0);
+  }
+
+  #[test]
+  fn keeps_pages_touching_the_screen() {
+    let pages = [rect([2.0, 2.0], [0.0, 0.0]), rect([2.0, 2.0], [0.0, 3.0])];
+    // the bottom edge of the screen is the top edge of the second page
+    let screen = rect([2.0, 1.0], [0.0, 1.5]);
+    assert_eq!(visible_pages(&pages, &screen).collect::<Vec<_>>(), [0, 1]);
+  }
+
+  #[test]
+  fn culls_pages_with_a_rotated_camera() {
+    let pages = [rect([2.0, 2.0], [0.0, 0.0]), rect([2.0, 2.0], [3.0, 3.0])];
+    // a thin screen along the other diagonal, whose bounding box covers both pages
+    let screen = Rect {
+      angle: -FRAC_PI_4,
+      ..rect([6.0, 0.5], [1.5, 1.5])
+    };
+    let (min, max) = screen.aabb();
+    assert!(min.x < 2.0 && min.y < 2.0 && max.x > 2.0 && max.y > 2.0);
+    assert_eq!(visible_pages(&pages, &screen).count(), 0);
+    let screen = Rect {
+      angle: FRAC_PI_4,
+      ..screen
+    };
+    assert_eq!(visible_pages(&pages, &screen).collect::<Vec<_>>(), [0, 1]);
+  }
+
+  #[test]
+  fn culls_tiles() {
+    let page = rect([4.0, 4.0], [0.0, 0.0]);
+    // the whole page
+    assert_eq!(tiles(&page, &rect([10.0, 10.0], [0.0, 0.0])).len(), 16);
+    // inside the top left tile
+    assert_eq!(tiles(&page, &rect([0.5, 0.5], [-1.5, -1.5])), [(0, 0)]);
+    // over the corner of four tiles
+    let screen = rect([0.5, 0.5], [0.0, 0.0]);
+    assert_eq!(tiles(&page, &screen), [(1, 1), (2, 1), (1, 2), (2, 2)]);
+    // next to the page
+    assert!(tiles(&page, &rect([1.0, 1.0], [5.0, 0.0])).is_empty());
+  }
+
+  #[test]
+  fn skips_tiles_touching_the_screen() {
+    let page = rect([4.0, 4.0], [0.0, 0.0]);
+    // the left half, its right edge on the border between tiles
+    let screen = rect([2.0, 4.0], [-1.0, 0.0]);
+    let tiles = tiles(&page, &screen);
+    assert_eq!(tiles.len(), 8);
+    assert!(tiles.iter().all(|&(x, _)| x < 2));
+  }
+
+  #[test]
+  fn culls_tiles_with_a_rotated_camera() {
+    let page = rect([4.0, 4.0], [0.0, 0.0]);
+    // a thin screen along the diagonal, whose bounding box covers the whole page
+    let screen = Rect {
+      angle: FRAC_PI_4,
+      ..rect([6.0, 0.1], [0.0, 0.0])
+    };
+    let tiles = tiles(&page, &screen);
+    for x in 0..4u32 {
+      for y in 0..4 {
+        let near_diagonal = x.abs_diff(y) <= 1;
+        assert_eq!(tiles.contains(&(x, y)), near_diagonal, "tile {x}, {y}");
+      }
+    }
+  }
 }
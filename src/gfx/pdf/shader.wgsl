@@ -8,24 +8,37 @@ struct VertexOutput {
   @location(0) tex_coords: vec2<f32>,
 }
 
+struct CameraUniform {
+  view_projection: mat3x3<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> u_camera: CameraUniform;
+@group(0) @binding(1)
+var<uniform> u_filter: FilterUniform;
+
 @vertex
 fn vs_main(
   in: VertexInput,
 ) -> VertexOutput {
   var out: VertexOutput;
 
-  out.clip_position = vec4<f32>(in.position * vec2<f32>(1.0, -1.0), 0.0, 1.0);
+  let clip_pos_a = (u_camera.view_projection * vec3<f32>(in.position, 1.0)).xy;
+  let clip_pos = vec2<f32>(clip_pos_a.x, -clip_pos_a.y);
+
+  out.clip_position = vec4<f32>(clip_pos, 0.0, 1.0);
   out.tex_coords = in.tex_coords;
   return out;
 }
 
 
-@group(0) @binding(0)
+@group(1) @binding(0)
 var tex: texture_2d<f32>;
-@group(0)@binding(1)
+@group(1)@binding(1)
 var samp: sampler;
 
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    return textureSample(tex, samp, in.tex_coords);
+    let color = textureSample(tex, samp, in.tex_coords);
+    return encode_output(vec4<f32>(apply_filter(color.rgb, u_filter), color.a), u_filter);
 }
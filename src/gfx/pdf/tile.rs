@@ -0,0 +1,98 @@
+use crate::pdf::tile::TileKey;
+
+use std::collections::HashMap;
+
+/// maximum number of tiles kept in gpu memory
+const TILE_CACHE_CAPACITY: usize = 384;
+
+pub struct Tile {
+  _texture: wgpu::Texture,
+  bind_group: wgpu::BindGroup,
+  vertex_buffer: wgpu::Buffer,
+  last_used_frame: u64,
+}
+
+impl Tile {
+  pub fn new(
+    texture: wgpu::Texture,
+    bind_group: wgpu::BindGroup,
+    vertex_buffer: wgpu::Buffer,
+  ) -> Self {
+    Self {
+      _texture: texture,
+      bind_group,
+      vertex_buffer,
+      last_used_frame: 0,
+    }
+  }
+
+  pub fn bind_group(&self) -> &wgpu::BindGroup {
+    &self.bind_group
+  }
+
+  pub fn vertex_buffer(&self) -> &wgpu::Buffer {
+    &self.vertex_buffer
+  }
+}
+
+/// Rasterized pdf tiles with least recently used eviction.
+#[derive(Default)]
+pub struct TileCache {
+  tiles: HashMap<TileKey, Tile>,
+  frame: u64,
+}
+
+impl TileCache {
+  pub fn begin_frame(&mut self) {
+    self.frame += 1;
+  }
+
+  pub fn get(&self, key: &TileKey) -> Option<&Tile> {
+    self.tiles.get(key)
+  }
+
+  /// marks the tile as used in the current frame.
+  /// returns `false` if the tile is not cached.
+  pub fn touch(&mut self, key: &TileKey) -> bool {
+    match self.tiles.get_mut(key) {
+      Some(tile) => {
+        tile.last_used_frame = self.frame;
+        true
+      }
+      None => false,
+    }
+  }
+
+  pub fn insert(&mut self, key: TileKey, mut tile: Tile) {
+    tile.last_used_frame = self.frame;
+    self.tiles.insert(key, tile);
+  }
+
+  pub fn tiles_of_page(&self, page: usize) -> impl Iterator<Item = (&TileKey, &Tile)> {
+    self.tiles.iter().filter(move |(key, _)| key.page == page)
+  }
+
+  pub fn clear(&mut self) {
+    self.tiles.clear();
+  }
+
+  /// evicts the least recently used tiles until the capacity is met.
+  /// tiles used in the current frame are never evicted.
+  pub fn evict(&mut self) {
+    let nexcess = self.tiles.len().saturating_sub(TILE_CACHE_CAPACITY);
+    if nexcess == 0 {
+      return;
+    }
+
+    let mut candidates: Vec<_> = self
+      .tiles
+      .iter()
+      .filter(|(_, tile)| tile.last_used_frame != self.frame)
+      .map(|(key, tile)| (tile.last_used_frame, *key))
+      .collect();
+    candidates.sort_unstable_by_key(|(last_used_frame, _)| *last_used_frame);
+    for (_, key) in candidates.into_iter().take(nexcess) {
+      self.tiles.remove(&key);
+    }
+  }
+}
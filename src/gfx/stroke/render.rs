@@ -1,39 +1,65 @@
-use crate::{gfx::BufferSized, stroke::StrokeManager};
+#[cfg(feature = "app")]
+use crate::{
+  gfx::{create_filtered_shader, BufferSized},
+  stroke::StrokeManager,
+};
 
+#[cfg(feature = "app")]
 use std::mem;
 
+#[cfg(feature = "app")]
 pub struct StrokeRenderer {
   pipeline: wgpu::RenderPipeline,
   bind_group: wgpu::BindGroup,
 }
 
+#[cfg(feature = "app")]
 impl StrokeRenderer {
   pub fn init(
     device: &wgpu::Device,
     format: wgpu::TextureFormat,
     camera_buffer: &BufferSized,
+    filter_buffer: &BufferSized,
   ) -> Self {
     let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
       label: Some("stroke_renderer_bind_group_layout"),
-      entries: &[wgpu::BindGroupLayoutEntry {
-        binding: 0,
-        visibility: wgpu::ShaderStages::VERTEX,
-        ty: wgpu::BindingType::Buffer {
-          ty: wgpu::BufferBindingType::Uniform,
-          has_dynamic_offset: false,
-          min_binding_size: Some(camera_buffer.size),
+      entries: &[
+        wgpu::BindGroupLayoutEntry {
+          binding: 0,
+          visibility: wgpu::ShaderStages::VERTEX,
+          ty: wgpu::BindingType::Buffer {
+            ty: wgpu::BufferBindingType::Uniform,
+            has_dynamic_offset: false,
+            min_binding_size: Some(camera_buffer.size),
+          },
+          count: None,
         },
-        count: None,
-      }],
+        wgpu::BindGroupLayoutEntry {
+          binding: 1,
+          visibility: wgpu::ShaderStages::FRAGMENT,
+          ty: wgpu::BindingType::Buffer {
+            ty: wgpu::BufferBindingType::Uniform,
+            has_dynamic_offset: false,
+            min_binding_size: Some(filter_buffer.size),
+          },
+          count: None,
+        },
+      ],
     });
 
     let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
       label: Some("stroke_renderer_bind_group"),
       layout: &bind_group_layout,
-      entries: &[wgpu::BindGroupEntry {
-        binding: 0,
-        resource: camera_buffer.buffer.as_entire_binding(),
-      }],
+      entries: &[
+        wgpu::BindGroupEntry {
+          binding: 0,
+          resource: camera_buffer.buffer.as_entire_binding(),
+        },
+        wgpu::BindGroupEntry {
+          binding: 1,
+          resource: filter_buffer.buffer.as_entire_binding(),
+        },
+      ],
     });
 
     let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
@@ -48,7 +74,7 @@ impl StrokeRenderer {
       write_mask: wgpu::ColorWrites::ALL,
     })];
 
-    let shader = device.create_shader_module(wgpu::include_wgsl!("shader.wgsl"));
+    let shader = create_filtered_shader(device, "stroke_shader", include_str!("shader.wgsl"));
 
     let pipeline_descriptor = wgpu::RenderPipelineDescriptor {
       label: Some("stroke_render_pipeline"),
@@ -105,6 +131,7 @@ impl StrokeRenderer {
 }
 
 pub type StrokeMeshCpu = crate::gfx::mesh::MeshCpu<StrokeVertex>;
+#[cfg(feature = "app")]
 pub type StrokeMeshGpu = crate::gfx::mesh::MeshGpu;
 
 #[repr(C)]
@@ -116,6 +143,7 @@ pub struct StrokeVertex {
   pub color: [f32; 4],
 }
 
+#[cfg(feature = "app")]
 impl StrokeVertex {
   const LAYOUT_ATTRIBUTES: [wgpu::VertexAttribute; 4] =
     wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x2, 2 => Float32, 3 => Float32x4];
@@ -9,6 +9,8 @@ struct CameraUniform {
 
 @group(0) @binding(0)
 var<uniform> u_camera: CameraUniform;
+@group(0) @binding(1)
+var<uniform> u_filter: FilterUniform;
 
 @vertex
 fn vs_main(
@@ -30,5 +32,8 @@ fn vs_main(
 
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    return in.color;
+    if (u_filter.remap_ink == 0u) {
+      return encode_output(in.color, u_filter);
+    }
+    return encode_output(vec4<f32>(apply_filter(in.color.rgb, u_filter), in.color.a), u_filter);
 }
\ No newline at end of file
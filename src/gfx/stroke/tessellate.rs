@@ -11,6 +11,18 @@ use lyon::{
 const DEFAULT_STROKE_WIDTH: f32 = 0.005;
 const STROKE_WIDTH_ATTRIBUTE: lyon::path::AttributeIndex = 0;
 
+/// the full width of the tessellated stroke in canvas units
+pub fn stroke_width_canvas(stroke: &Stroke) -> f32 {
+  // the shader offsets both sides by the width along the normal
+  2.0 * DEFAULT_STROKE_WIDTH * stroke.width_multiplier()
+}
+
+/// the width multiplier giving a stroke the full width in canvas units
+#[cfg(not(target_arch = "wasm32"))]
+pub fn width_multiplier_for(width_canvas: f32) -> f32 {
+  width_canvas / (2.0 * DEFAULT_STROKE_WIDTH)
+}
+
 #[derive(Default)]
 pub struct StrokeTessellator {
   tessellator: LyonStrokeTessellator,
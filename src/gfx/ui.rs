@@ -24,6 +24,7 @@ impl UiRenderer {
     }
   }
 
+  #[allow(clippy::too_many_arguments)]
   pub fn prepare(
     &mut self,
     window: &Window,
@@ -0,0 +1,442 @@
+//! Documents without a window, for scripts, tools and the command line.
+//!
+//! A [`Document`] is loaded from a savefile or starts empty, is edited by running
+//! [`ProtocolCommand`]s, like the app does, and is saved or exported again.
+//! The functions at the bottom do the same for whole files.
+
+pub use crate::{
+  content::{
+    access::ContentAccess,
+    command::{
+      AddHighlightCommand, AddStrokeCommand, AddStrokesCommand, ArrangePagesCommand,
+      ProtocolCommand, RemoveHighlightCommand, RemoveStrokesCommand, SetPagesCommand,
+    },
+    HighlightId, StrokeId,
+  },
+  file::{FileError, SaveFormat},
+  import::{ImportError, ImportReport},
+  pdf::{
+    PageEntry, PageId, PageKind, PageList, PdfError, PdfHighlight, PdfLayout, PdfPageInfo,
+    PdfSource,
+  },
+  stroke::Stroke,
+};
+
+use crate::{
+  content::{protocol::Protocol, Content, ContentManager},
+  export::{self, ExportRegion, PngOptions, SvgOptions},
+  file::{self, EmbeddedPdf, Savefile},
+  import::{self, SvgPlacement},
+  pdf::PdfManager,
+};
+
+use std::{
+  fmt, fs,
+  path::{Path, PathBuf},
+};
+
+#[derive(Debug)]
+pub enum HeadlessError {
+  File(FileError),
+  Import(ImportError),
+  /// a pdf of the document could not be loaded, or the exported one not be built
+  Pdf(PdfError),
+  /// the file extension names no known format
+  UnknownFormat(String),
+}
+
+impl fmt::Display for HeadlessError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      HeadlessError::File(error) => write!(f, "{error}"),
+      HeadlessError::Import(error) => write!(f, "{error}"),
+      HeadlessError::Pdf(error) => write!(f, "{error}"),
+      HeadlessError::UnknownFormat(path) => write!(f, "unknown file format: {path}"),
+    }
+  }
+}
+
+impl std::error::Error for HeadlessError {}
+
+impl From<FileError> for HeadlessError {
+  fn from(error: FileError) -> Self {
+    Self::File(error)
+  }
+}
+
+impl From<ImportError> for HeadlessError {
+  fn from(error: ImportError) -> Self {
+    Self::Import(error)
+  }
+}
+
+impl From<PdfError> for HeadlessError {
+  fn from(error: PdfError) -> Self {
+    Self::Pdf(error)
+  }
+}
+
+impl From<std::io::Error> for HeadlessError {
+  fn from(error: std::io::Error) -> Self {
+    Self::File(error.into())
+  }
+}
+
+/// A document with its undo history.
+///
+/// Every edit is a [`ProtocolCommand`], so it can be undone, also after saving and
+/// opening the document in the app.
+#[derive(Default)]
+pub struct Document {
+  content_manager: ContentManager,
+  /// saved again as they are, only exports extract them
+  embedded_pdfs: Vec<EmbeddedPdf>,
+}
+
+impl Document {
+  /// Opens a savefile of either format.
+  pub fn load(path: &Path) -> Result<Self, HeadlessError> {
+    Ok(Self::from_savefile(file::load(path)?))
+  }
+
+  /// Returns where it was saved, which has the extension added if it was missing.
+  pub fn save(&mut self, path: &Path, format: SaveFormat) -> Result<PathBuf, HeadlessError> {
+    let path = file::save(&self.to_savefile(), path, format)?;
+    self.content_manager.mark_saved();
+    Ok(path)
+  }
+
+  fn from_savefile(savefile: Savefile) -> Self {
+    let mut content_manager = ContentManager::default();
+    content_manager.replace(savefile.content, savefile.protocol);
+    content_manager.reset_delta();
+    content_manager.mark_saved();
+    Self {
+      content_manager,
+      embedded_pdfs: savefile.embedded_pdfs,
+    }
+  }
+
+  fn to_savefile(&self) -> Savefile {
+    let (content, protocol) = self.content_manager.clone();
+    let mut savefile = Savefile::new(content, protocol);
+    savefile.embedded_pdfs = self.embedded_pdfs.clone();
+    savefile
+  }
+
+  /// The strokes, highlights and pages.
+  pub fn content(&self) -> ContentAccess {
+    self.content_manager.access()
+  }
+
+  /// for the canvas widget, whose tools edit the content
+  #[cfg(feature = "app")]
+  pub(crate) fn content_manager_mut(&mut self) -> &mut ContentManager {
+    &mut self.content_manager
+  }
+
+  pub fn pdf_layout(&self) -> &PdfLayout {
+    self.content().layout()
+  }
+
+  /// Replaces the pages, moving the strokes on them along, as one undoable step.
+  /// Loads the pdfs for their page sizes.
+  pub fn set_pages(&mut self, pages: PageList) {
+    let layout = self.pdf_layout().clone();
+    self.arrange_pages(pages, layout);
+  }
+
+  /// Rearranges the pages, moving the strokes on them along, as one undoable step.
+  /// Loads the pdfs for their page sizes.
+  pub fn set_pdf_layout(&mut self, pdf_layout: PdfLayout) {
+    let pages = self.content().pages().clone();
+    self.arrange_pages(pages, pdf_layout);
+  }
+
+  fn arrange_pages(&mut self, pages: PageList, pdf_layout: PdfLayout) {
+    let mut pdf_manager = PdfManager::default();
+    pdf_manager.set_pages(self.content().pages());
+    for source in pages.sources() {
+      // missing pdfs have A4 pages
+      let _ = pdf_manager.load_source(source.clone());
+    }
+    let command = ArrangePagesCommand::new(self.content(), pages, pdf_layout, &pdf_manager);
+    self.run(command);
+  }
+
+  /// Runs the command, which becomes the newest step of the undo history.
+  /// A redoable step is kept as a branch of the history.
+  pub fn run(&mut self, command: ProtocolCommand) {
+    self.content_manager.run_cmd(command);
+    // nobody renders the changes
+    self.content_manager.reset_delta();
+  }
+
+  /// Returns `false` if there was nothing to undo.
+  pub fn undo(&mut self) -> bool {
+    if !self.content_manager.undoable() {
+      return false;
+    }
+    self.content_manager.undo_cmd();
+    self.content_manager.reset_delta();
+    true
+  }
+
+  /// Returns `false` if there was nothing to redo.
+  pub fn redo(&mut self) -> bool {
+    if !self.content_manager.redoable() {
+      return false;
+    }
+    self.content_manager.redo_cmd();
+    self.content_manager.reset_delta();
+    true
+  }
+
+  /// the commands in the history, including undone ones
+  pub fn nhistory_steps(&self) -> usize {
+    self.content_manager.protocol().nsteps()
+  }
+
+  /// Drops the undo history, keeping the content.
+  pub fn strip_history(&mut self) {
+    let (content, _) = self.content_manager.clone();
+    self.content_manager.replace(content, Protocol::default());
+    self.content_manager.reset_delta();
+  }
+
+  /// whether there are changes since the document was loaded or saved
+  pub fn is_modified(&self) -> bool {
+    self.content_manager.is_modified()
+  }
+
+  /// Exports everything, in the format of the output extension.
+  /// Returns `false` if there was nothing to export.
+  pub fn export(&self, output_path: &Path, options: &ExportOptions) -> Result<bool, HeadlessError> {
+    let format = ExportFormat::from_path(output_path)?;
+    // the pages need the embedded pdfs that are missing at their sources
+    let extracted = match self.embedded_pdfs.is_empty() {
+      true => None,
+      false => {
+        let mut savefile = self.to_savefile();
+        savefile.extract_pdfs()?;
+        Some(Self::from_savefile(savefile))
+      }
+    };
+    let content = extracted.as_ref().unwrap_or(self).content();
+    let mut pdf_manager = PdfManager::default();
+    pdf_manager.set_pages(content.pages());
+    pdf_manager.set_layout(content.layout().clone());
+    // an export with placeholders instead of the pdf pages is not what was asked for
+    if let Some(error) = pdf_manager.take_load_errors().into_iter().next() {
+      return Err(error.into());
+    }
+
+    let result = match format {
+      ExportFormat::Png => {
+        let options = PngOptions {
+          region: ExportRegion::All,
+          dpi: options.dpi,
+          pdf_background: options.pdf_background,
+        };
+        export::export_png(&options, content, &mut pdf_manager)
+          .map(|png| file::save_png(&png, output_path))
+      }
+      ExportFormat::Svg => {
+        let options = SvgOptions {
+          region: ExportRegion::All,
+          pdf_background: options.pdf_background,
+        };
+        export::export_svg(&options, content, &mut pdf_manager)
+          .map(|svg| file::save_svg(&svg, output_path))
+      }
+      ExportFormat::Pdf => Some(file::save_pdf(&pdf_manager.export(content)?, output_path)),
+      ExportFormat::InkMl => export::export_inkml(&ExportRegion::All, content, &pdf_manager)
+        .map(|inkml| file::save_inkml(&inkml, output_path)),
+    };
+    match result {
+      Some(result) => result.map(|_| true).map_err(HeadlessError::from),
+      None => Ok(false),
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+  Png,
+  Svg,
+  Pdf,
+  InkMl,
+}
+
+impl ExportFormat {
+  /// the format named by the extension
+  pub fn from_path(path: &Path) -> Result<Self, HeadlessError> {
+    match extension(path).as_deref() {
+      Some("png") => Ok(Self::Png),
+      Some("svg") => Ok(Self::Svg),
+      Some("pdf") => Ok(Self::Pdf),
+      Some("inkml" | "ink") => Ok(Self::InkMl),
+      _ => Err(HeadlessError::UnknownFormat(path.display().to_string())),
+    }
+  }
+}
+
+#[derive(Debug, Clone)]
+pub struct ExportOptions {
+  /// resolution of png exports
+  pub dpi: f32,
+  /// whether image exports show the pdf pages below the strokes
+  pub pdf_background: bool,
+}
+
+impl Default for ExportOptions {
+  fn default() -> Self {
+    Self {
+      dpi: 150.0,
+      pdf_background: true,
+    }
+  }
+}
+
+/// Exports everything in the savefile, in the format of the output extension.
+/// Returns `false` if there was nothing to export.
+pub fn export(
+  savefile_path: &Path,
+  output_path: &Path,
+  options: &ExportOptions,
+) -> Result<bool, HeadlessError> {
+  Document::load(savefile_path)?.export(output_path, options)
+}
+
+/// What a savefile holds.
+pub struct SavefileInfo {
+  pub format: SaveFormat,
+  /// in bytes
+  pub size: u64,
+  pub npages: usize,
+  pub nblank_pages: usize,
+  pub pdfs: Vec<String>,
+  pub nstrokes: usize,
+  pub npoints: usize,
+  pub nhighlights: usize,
+  /// the commands in the history, including undone ones
+  pub nhistory_steps: usize,
+  pub layout: &'static str,
+}
+
+impl fmt::Display for SavefileInfo {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let format = match self.format {
+      SaveFormat::Ron => "ron",
+      SaveFormat::Binary => "compact",
+    };
+    writeln!(f, "format:     {format}, {} bytes", self.size)?;
+    writeln!(
+      f,
+      "pages:      {} ({} blank)",
+      self.npages, self.nblank_pages
+    )?;
+    for pdf in &self.pdfs {
+      writeln!(f, "pdf:        {pdf}")?;
+    }
+    writeln!(f, "layout:     {}", self.layout)?;
+    writeln!(f, "strokes:    {} ({} points)", self.nstrokes, self.npoints)?;
+    writeln!(f, "highlights: {}", self.nhighlights)?;
+    write!(f, "history:    {} steps", self.nhistory_steps)
+  }
+}
+
+pub fn info(savefile_path: &Path) -> Result<SavefileInfo, HeadlessError> {
+  let format = file::format_of(savefile_path)?;
+  let size = fs::metadata(savefile_path)?.len();
+  let document = Document::load(savefile_path)?;
+  let content = document.content();
+
+  let pages = content.pages();
+  Ok(SavefileInfo {
+    format,
+    size,
+    npages: pages.npages(),
+    nblank_pages: pages
+      .pages()
+      .iter()
+      .filter(|entry| matches!(entry.kind, PageKind::Blank(_)))
+      .count(),
+    pdfs: pages
+      .sources()
+      .iter()
+      .map(|source| source.display().to_string())
+      .collect(),
+    nstrokes: content.strokes().count(),
+    npoints: content
+      .strokes()
+      .map(|(_, stroke)| stroke.points().len())
+      .sum(),
+    nhighlights: content.highlights().count(),
+    nhistory_steps: document.nhistory_steps(),
+    layout: document.pdf_layout().name(),
+  })
+}
+
+/// Rewrites the savefile in the compact format, with the pdfs embedded.
+pub fn compact(savefile_path: &Path, output_path: &Path) -> Result<(), HeadlessError> {
+  Document::load(savefile_path)?.save(output_path, SaveFormat::Binary)?;
+  Ok(())
+}
+
+/// Drops the undo history, keeping the format of the savefile.
+pub fn strip_history(savefile_path: &Path, output_path: &Path) -> Result<(), HeadlessError> {
+  let format = file::format_of(savefile_path)?;
+  let mut document = Document::load(savefile_path)?;
+  document.strip_history();
+  document.save(output_path, format)?;
+  Ok(())
+}
+
+/// Converts a savefile into the format, or imports a Xournal++ notebook, InkML ink
+/// or svg drawing into a new savefile.
+/// Returns what the import lost.
+pub fn convert(
+  input_path: &Path,
+  output_path: &Path,
+  format: SaveFormat,
+) -> Result<ImportReport, HeadlessError> {
+  let (mut document, report) = match extension(input_path).as_deref() {
+    Some("co") => (Document::load(input_path)?, ImportReport::default()),
+    Some("xopp") => {
+      let import = import::import_xopp(input_path)?;
+      (new_document(import.content), import.report)
+    }
+    Some("inkml" | "ink" | "svg") => {
+      let import = match extension(input_path).as_deref() {
+        Some("svg") => {
+          let placement = SvgPlacement {
+            center_canvas: na::Point2::origin(),
+            scale: 1.0,
+          };
+          import::import_svg(input_path, &placement)?
+        }
+        _ => import::import_inkml(input_path)?,
+      };
+      let content = Content::new(PageList::default(), import.strokes);
+      (new_document(content), import.report)
+    }
+    _ => {
+      return Err(HeadlessError::UnknownFormat(
+        input_path.display().to_string(),
+      ))
+    }
+  };
+  document.save(output_path, format)?;
+  Ok(report)
+}
+
+fn new_document(content: Content) -> Document {
+  Document::from_savefile(Savefile::new(content, Protocol::default()))
+}
+
+fn extension(path: &Path) -> Option<String> {
+  path
+    .extension()
+    .map(|extension| extension.to_string_lossy().to_lowercase())
+}
@@ -0,0 +1,113 @@
+mod inkml;
+#[cfg(feature = "app")]
+mod raster;
+mod svg;
+mod xopp;
+
+#[cfg(feature = "app")]
+pub use self::raster::import_image;
+pub use self::{
+  inkml::{import_inkml, InkImport},
+  svg::{import_svg, SvgPlacement},
+  xopp::import_xopp,
+};
+
+use crate::{content::Content, file::FileError, pdf::PdfError};
+
+use std::{collections::BTreeMap, fmt};
+use xml::attribute::OwnedAttribute;
+
+/// Content converted from another program and what got lost on the way.
+pub struct Import {
+  pub content: Content,
+  pub report: ImportReport,
+}
+
+/// Counts the parts of an imported file that could not be converted faithfully.
+#[derive(Debug, Default, Clone)]
+pub struct ImportReport {
+  notes: BTreeMap<&'static str, usize>,
+}
+
+impl ImportReport {
+  pub fn note(&mut self, what: &'static str) {
+    *self.notes.entry(what).or_default() += 1;
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.notes.is_empty()
+  }
+
+  /// one line per kind of loss, e.g. "3× text skipped"
+  pub fn lines(&self) -> impl Iterator<Item = String> + '_ {
+    self
+      .notes
+      .iter()
+      .map(|(what, count)| format!("{count}× {what}"))
+  }
+}
+
+#[derive(Debug)]
+pub enum ImportError {
+  Io(std::io::Error),
+  Xml(xml::reader::Error),
+  /// the file is well formed but not what it claims to be
+  Invalid(&'static str),
+  Image(image::ImageError),
+  /// the pdf the image was put into
+  Pdf(PdfError),
+  Cache(FileError),
+}
+
+impl fmt::Display for ImportError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      ImportError::Io(error) => write!(f, "failed to read the file: {error}"),
+      ImportError::Xml(error) => write!(f, "malformed xml: {error}"),
+      ImportError::Invalid(reason) => write!(f, "invalid file: {reason}"),
+      ImportError::Image(error) => write!(f, "failed to decode the image: {error}"),
+      ImportError::Pdf(error) => write!(f, "{error}"),
+      ImportError::Cache(error) => write!(f, "failed to store the pdf: {error}"),
+    }
+  }
+}
+
+impl std::error::Error for ImportError {}
+
+impl From<std::io::Error> for ImportError {
+  fn from(error: std::io::Error) -> Self {
+    Self::Io(error)
+  }
+}
+
+impl From<image::ImageError> for ImportError {
+  fn from(error: image::ImageError) -> Self {
+    Self::Image(error)
+  }
+}
+
+impl From<PdfError> for ImportError {
+  fn from(error: PdfError) -> Self {
+    Self::Pdf(error)
+  }
+}
+
+impl From<FileError> for ImportError {
+  fn from(error: FileError) -> Self {
+    Self::Cache(error)
+  }
+}
+
+impl From<xml::reader::Error> for ImportError {
+  fn from(error: xml::reader::Error) -> Self {
+    Self::Xml(error)
+  }
+}
+
+/// the value of the attribute, ignoring namespaces
+fn attribute<'a>(attributes: &'a [OwnedAttribute], name: &str) -> Option<&'a str> {
+  attributes
+    .iter()
+    .find(|attribute| attribute.name.local_name == name)
+    .map(|attribute| attribute.value.as_str())
+}
@@ -0,0 +1,343 @@
+use super::{attribute, ImportError, ImportReport};
+
+use crate::{gfx::stroke, pdf::CANVAS_UNITS_PER_PDF_POINT, stroke::Stroke};
+
+use std::{collections::HashMap, fs, path::Path};
+use xml::reader::{EventReader, XmlEvent};
+
+/// Strokes read from an InkML file, placed where the file puts them on the canvas.
+pub struct InkImport {
+  pub strokes: Vec<Stroke>,
+  pub report: ImportReport,
+}
+
+/// Imports the traces of a W3C InkML file as strokes.
+///
+/// The coordinates are taken as absolute canvas positions in the units of the trace format,
+/// so files written by `export_inkml` round-trip. Traces without units are read as points.
+/// The pressure scales the width of the stroke, the timing is dropped.
+pub fn import_inkml(path: &Path) -> Result<InkImport, ImportError> {
+  let xml = fs::read(path)?;
+  let mut report = ImportReport::default();
+  let strokes = parse(&xml, &mut report)?;
+  Ok(InkImport { strokes, report })
+}
+
+#[derive(Debug, Clone)]
+struct Channel {
+  name: String,
+  /// points per unit of the channel
+  points_per_unit: f32,
+  /// upper bound of the values, used to normalize the pressure
+  max: Option<f32>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Brush {
+  color: Option<[u8; 3]>,
+  width_points: Option<f32>,
+  /// 0 is opaque, 255 fully transparent
+  transparency: Option<u8>,
+}
+
+fn parse(xml: &[u8], report: &mut ImportReport) -> Result<Vec<Stroke>, ImportError> {
+  let mut strokes = Vec::new();
+  let mut is_root_checked = false;
+
+  // the active trace format and the one being read
+  let mut channels = default_channels();
+  let mut new_channels: Option<Vec<Channel>> = None;
+
+  let mut brushes: HashMap<String, Brush> = HashMap::new();
+  let mut new_brush: Option<(String, Brush)> = None;
+  let mut context_brush: Option<String> = None;
+  // the brushes of the enclosing trace groups
+  let mut group_brushes: Vec<Option<String>> = Vec::new();
+
+  // the brush and the data of the trace being read
+  let mut trace: Option<(Option<String>, String)> = None;
+
+  for event in EventReader::new(xml) {
+    match event? {
+      XmlEvent::StartElement {
+        name, attributes, ..
+      } => {
+        let tag = name.local_name.as_str();
+        if !is_root_checked {
+          if tag != "ink" {
+            return Err(ImportError::Invalid("not an inkml document"));
+          }
+          is_root_checked = true;
+          continue;
+        }
+        let brush_ref = || attribute(&attributes, "brushRef").map(strip_ref);
+        match tag {
+          "traceFormat" => new_channels = Some(Vec::new()),
+          "channel" => {
+            if let Some(channels) = &mut new_channels {
+              let name = attribute(&attributes, "name").unwrap_or_default();
+              channels.push(Channel {
+                name: name.to_owned(),
+                points_per_unit: points_per_unit(attribute(&attributes, "units")),
+                max: attribute(&attributes, "max").and_then(|max| max.parse().ok()),
+              });
+            }
+          }
+          "brush" => {
+            let id = attribute(&attributes, "id").unwrap_or_default();
+            new_brush = Some((id.to_owned(), Brush::default()));
+          }
+          "brushProperty" => {
+            if let Some((_, brush)) = &mut new_brush {
+              parse_brush_property(&attributes, brush);
+            }
+          }
+          "context" => {
+            if let Some(brush) = brush_ref() {
+              context_brush = Some(brush);
+            }
+          }
+          "traceGroup" => group_brushes.push(brush_ref()),
+          "trace" => trace = Some((brush_ref(), String::new())),
+          "annotation" | "annotationXML" => report.note("annotations skipped"),
+          _ => {}
+        }
+      }
+      XmlEvent::Characters(text) => {
+        if let Some((_, data)) = &mut trace {
+          data.push_str(&text);
+        }
+      }
+      XmlEvent::EndElement { name } => match name.local_name.as_str() {
+        "traceFormat" => {
+          if let Some(new_channels) = new_channels.take() {
+            channels = new_channels;
+          }
+        }
+        "brush" => {
+          if let Some((id, brush)) = new_brush.take() {
+            brushes.insert(id, brush);
+          }
+        }
+        "traceGroup" => {
+          group_brushes.pop();
+        }
+        "trace" => {
+          let Some((brush_ref, data)) = trace.take() else { continue };
+          // the closest brush wins
+          let brush_ref = brush_ref
+            .or_else(|| group_brushes.iter().rev().flatten().next().cloned())
+            .or_else(|| context_brush.clone());
+          let brush = match brush_ref {
+            Some(brush_ref) => match brushes.get(&brush_ref) {
+              Some(brush) => brush.clone(),
+              None => {
+                report.note("unknown brushes replaced by the default");
+                Brush::default()
+              }
+            },
+            None => Brush::default(),
+          };
+          strokes.extend(convert_trace(&data, &channels, &brush, report)?);
+        }
+        _ => {}
+      },
+      _ => {}
+    }
+  }
+  Ok(strokes)
+}
+
+fn convert_trace(
+  data: &str,
+  channels: &[Channel],
+  brush: &Brush,
+  report: &mut ImportReport,
+) -> Result<Option<Stroke>, ImportError> {
+  let channel_index = |name: &str| channels.iter().position(|channel| channel.name == name);
+  let (Some(x), Some(y)) = (channel_index("X"), channel_index("Y")) else {
+    return Err(ImportError::Invalid("trace format without x and y channels"));
+  };
+  let pressure = channel_index("F");
+  if channel_index("T").is_some() {
+    report.note("trace timings dropped");
+  }
+
+  let values = parse_trace(data, channels.len())?;
+  let mut points: Vec<_> = values
+    .iter()
+    .map(|point| {
+      na::point![
+        point[x] * channels[x].points_per_unit,
+        point[y] * channels[y].points_per_unit
+      ] * CANVAS_UNITS_PER_PDF_POINT
+    })
+    .collect();
+  match points.len() {
+    0 => {
+      report.note("empty traces skipped");
+      return Ok(None);
+    }
+    // a dot
+    1 => points.push(points[0]),
+    _ => {}
+  }
+
+  // the model has one width per stroke, so the pressure scales the whole stroke
+  let mut width_points = brush.width_points.unwrap_or(1.0);
+  if let Some(pressure) = pressure {
+    match channels[pressure].max.filter(|max| *max > 0.0) {
+      Some(max) => {
+        if values
+          .iter()
+          .any(|point| point[pressure] != values[0][pressure])
+        {
+          report.note("trace pressures averaged into the width");
+        }
+        let mean = values.iter().map(|point| point[pressure]).sum::<f32>() / values.len() as f32;
+        width_points *= (mean / max).clamp(0.0, 1.0);
+      }
+      None => report.note("trace pressures of unknown range dropped"),
+    }
+  }
+  let width_multiplier = stroke::width_multiplier_for(width_points * CANVAS_UNITS_PER_PDF_POINT);
+
+  let [r, g, b] = brush.color.unwrap_or([0, 0, 0]);
+  let a = 255 - brush.transparency.unwrap_or(0);
+  let color = palette::Srgba::new(r, g, b, a)
+    .into_format::<f32, f32>()
+    .into_linear();
+  Ok(Some(Stroke::new(points, color, width_multiplier)))
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Encoding {
+  /// `!`, the value itself
+  Explicit,
+  /// `'`, the difference to the previous value
+  FirstDifference,
+  /// `"`, the difference to the previous difference
+  SecondDifference,
+}
+
+/// Decodes the points of a trace, one value per channel.
+///
+/// Points are separated by commas and values by whitespace or their signs and prefixes.
+/// Each channel keeps its encoding until a prefix changes it.
+/// Missing values (`?`, `*` or cut off points) repeat the previous value.
+fn parse_trace(data: &str, nchannels: usize) -> Result<Vec<Vec<f32>>, ImportError> {
+  let mut encodings = vec![Encoding::Explicit; nchannels];
+  let mut values = vec![0.0_f32; nchannels];
+  let mut differences = vec![0.0_f32; nchannels];
+
+  let mut points = Vec::new();
+  for point in data.split(',') {
+    if point.trim().is_empty() {
+      continue;
+    }
+    let mut chars = point.chars().peekable();
+    for channel in 0..nchannels {
+      while chars.next_if(|c| c.is_whitespace()).is_some() {}
+      match chars.peek() {
+        Some('!') => encodings[channel] = Encoding::Explicit,
+        Some('\'') => encodings[channel] = Encoding::FirstDifference,
+        Some('"') => encodings[channel] = Encoding::SecondDifference,
+        _ => {}
+      }
+      chars.next_if(|c| matches!(c, '!' | '\'' | '"'));
+      while chars.next_if(|c| c.is_whitespace()).is_some() {}
+
+      if chars.next_if(|c| matches!(c, '?' | '*')).is_some() {
+        continue;
+      }
+      let mut number = String::new();
+      if let Some(sign) = chars.next_if(|c| matches!(c, '-' | '+')) {
+        number.push(sign);
+      }
+      let mut previous = ' ';
+      while let Some(c) = chars.next_if(|&c| {
+        c.is_ascii_digit()
+          || c == '.'
+          || matches!(c, 'e' | 'E')
+          || (matches!(c, '-' | '+') && matches!(previous, 'e' | 'E'))
+      }) {
+        number.push(c);
+        previous = c;
+      }
+      if number.is_empty() {
+        // fewer values than channels
+        continue;
+      }
+      let value: f32 = number
+        .parse()
+        .map_err(|_| ImportError::Invalid("malformed trace"))?;
+      match encodings[channel] {
+        Encoding::Explicit => {
+          differences[channel] = value - values[channel];
+          values[channel] = value;
+        }
+        Encoding::FirstDifference => {
+          differences[channel] = value;
+          values[channel] += value;
+        }
+        Encoding::SecondDifference => {
+          differences[channel] += value;
+          values[channel] += differences[channel];
+        }
+      }
+    }
+    if chars.any(|c| !c.is_whitespace()) {
+      return Err(ImportError::Invalid("malformed trace"));
+    }
+    points.push(values.clone());
+  }
+  Ok(points)
+}
+
+fn parse_brush_property(attributes: &[xml::attribute::OwnedAttribute], brush: &mut Brush) {
+  let Some(value) = attribute(attributes, "value") else { return };
+  match attribute(attributes, "name") {
+    Some("color") => {
+      brush.color = value.strip_prefix('#').and_then(|hex| {
+        let channel = |i: usize| u8::from_str_radix(hex.get(2 * i..2 * i + 2)?, 16).ok();
+        Some([channel(0)?, channel(1)?, channel(2)?])
+      })
+    }
+    Some("width") => {
+      let points_per_unit = points_per_unit(attribute(attributes, "units"));
+      brush.width_points = value
+        .parse::<f32>()
+        .ok()
+        .map(|width| width * points_per_unit);
+    }
+    Some("transparency") => brush.transparency = value.parse().ok(),
+    _ => {}
+  }
+}
+
+/// without a trace format the points are x and y
+fn default_channels() -> Vec<Channel> {
+  ["X", "Y"]
+    .map(|name| Channel {
+      name: name.to_owned(),
+      points_per_unit: 1.0,
+      max: None,
+    })
+    .to_vec()
+}
+
+/// lengths without or with unknown units are taken as points
+fn points_per_unit(units: Option<&str>) -> f32 {
+  match units {
+    Some("mm") => 72.0 / 25.4,
+    Some("cm") => 720.0 / 25.4,
+    Some("m") => 72_000.0 / 25.4,
+    Some("in") => 72.0,
+    Some("pc") => 12.0,
+    _ => 1.0,
+  }
+}
+
+fn strip_ref(reference: &str) -> String {
+  reference.trim_start_matches('#').to_owned()
+}
@@ -0,0 +1,34 @@
+//! Raster images become pdfs of a single page, the document shows them as pages.
+
+use super::ImportError;
+use crate::{
+  file,
+  pdf::{PdfManager, PdfPageInfo, PdfSource},
+};
+
+use std::path::Path;
+
+/// the resolution images are shown at, unless they are larger than a page
+const PIXELS_PER_POINT: f32 = 96.0 / 72.0;
+
+/// Puts the image into a pdf in the cache and returns its path.
+/// Images larger than an A4 page are scaled down to fit onto one.
+pub fn import_image(path: &Path, pdf_manager: &mut PdfManager) -> Result<PdfSource, ImportError> {
+  let image = image::open(path)?;
+  let size = page_size([image.width(), image.height()]);
+  let pdf = pdf_manager.image_pdf(image, size)?;
+  let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+  Ok(file::cache_pdf(&stem, &pdf)?)
+}
+
+fn page_size(size_pixels: [u32; 2]) -> PdfPageInfo {
+  let [width, height] = size_pixels.map(|pixels| pixels.max(1) as f32 / PIXELS_PER_POINT);
+  let a4 = PdfPageInfo::A4;
+  let scale = (a4.width_points / width)
+    .min(a4.height_points / height)
+    .min(1.0);
+  PdfPageInfo {
+    width_points: width * scale,
+    height_points: height * scale,
+  }
+}
@@ -0,0 +1,649 @@
+use super::{attribute, ImportError, ImportReport, InkImport};
+
+use crate::{gfx::stroke, pdf::CANVAS_UNITS_PER_PDF_POINT, stroke::Stroke};
+
+use lyon::{
+  math::{point, vector, Angle, Point, Transform},
+  path::{traits::SvgPathBuilder, ArcFlags, Path, PathEvent},
+};
+use std::{fs, path::Path as FsPath};
+use xml::{
+  attribute::OwnedAttribute,
+  reader::{EventReader, XmlEvent},
+};
+
+/// svg user units are css pixels
+const POINTS_PER_PIXEL: f32 = 0.75;
+/// max distance of the flattened strokes from the curves, in canvas units
+const FLATTENING_TOLERANCE: f32 = 0.0005;
+
+/// Where the imported drawing goes on the canvas.
+#[derive(Debug, Clone, Copy)]
+pub struct SvgPlacement {
+  /// the center of the drawing
+  pub center_canvas: na::Point2<f32>,
+  /// relative to the size the svg declares
+  pub scale: f32,
+}
+
+/// Imports the outlines of an svg drawing as strokes.
+///
+/// Paths, lines, polylines, polygons, rects, circles and ellipses are flattened into one stroke
+/// per subpath, with their transforms applied. Shapes without a stroke are outlined in their fill.
+pub fn import_svg(path: &FsPath, placement: &SvgPlacement) -> Result<InkImport, ImportError> {
+  let xml = fs::read(path)?;
+  let mut report = ImportReport::default();
+  let shapes = parse(&xml, placement.scale, &mut report)?;
+
+  let bounds = shapes
+    .iter()
+    .flat_map(|shape| &shape.points)
+    .fold(None, |bounds, p| match bounds {
+      Some((min, max)) => Some((p.inf(&min), p.sup(&max))),
+      None => Some((*p, *p)),
+    });
+  let Some((min, max)) = bounds else {
+    return Ok(InkImport {
+      strokes: Vec::new(),
+      report,
+    });
+  };
+  let offset = placement.center_canvas - na::center(&min, &max);
+
+  let strokes = shapes
+    .into_iter()
+    .map(|shape| {
+      let points = shape.points.into_iter().map(|p| p + offset).collect();
+      Stroke::new(points, shape.color, shape.width_multiplier)
+    })
+    .collect();
+  Ok(InkImport { strokes, report })
+}
+
+/// a flattened subpath on the canvas, not placed yet
+struct Shape {
+  points: Vec<na::Point2<f32>>,
+  color: palette::LinSrgba,
+  width_multiplier: f32,
+}
+
+/// paint of strokes and fills, `None` for no paint
+type Paint = Option<[u8; 3]>;
+
+/// the inherited presentation attributes
+#[derive(Debug, Clone)]
+struct Style {
+  stroke: Paint,
+  stroke_width: f32,
+  stroke_opacity: f32,
+  fill: Paint,
+  fill_opacity: f32,
+  opacity: f32,
+}
+
+impl Default for Style {
+  fn default() -> Self {
+    Self {
+      stroke: None,
+      stroke_width: 1.0,
+      stroke_opacity: 1.0,
+      fill: Some([0, 0, 0]),
+      fill_opacity: 1.0,
+      opacity: 1.0,
+    }
+  }
+}
+
+fn parse(xml: &[u8], scale: f32, report: &mut ImportReport) -> Result<Vec<Shape>, ImportError> {
+  let mut shapes = Vec::new();
+  let to_canvas = POINTS_PER_PIXEL * scale * CANVAS_UNITS_PER_PDF_POINT;
+  // the transform into canvas units and the style of every open element
+  let mut stack: Vec<(Transform, Style)> = Vec::new();
+  // depth within elements that are not drawn, like definitions
+  let mut hidden_depth = 0;
+
+  for event in EventReader::new(xml) {
+    match event? {
+      XmlEvent::StartElement {
+        name, attributes, ..
+      } => {
+        let tag = name.local_name.as_str();
+        let Some((parent_transform, parent_style)) = stack.last().cloned() else {
+          if tag != "svg" {
+            return Err(ImportError::Invalid("not an svg document"));
+          }
+          let transform = viewbox_transform(&attributes).then_scale(to_canvas, to_canvas);
+          stack.push((transform, parse_style(&attributes, Style::default(), report)));
+          continue;
+        };
+
+        let is_hidden = matches!(
+          tag,
+          "defs"
+            | "clipPath"
+            | "mask"
+            | "symbol"
+            | "marker"
+            | "pattern"
+            | "linearGradient"
+            | "radialGradient"
+            | "metadata"
+            | "title"
+            | "desc"
+        ) || attribute(&attributes, "display") == Some("none");
+        match tag {
+          "text" => report.note("texts skipped"),
+          "image" => report.note("images skipped"),
+          "use" => report.note("reused elements skipped"),
+          "style" => report.note("stylesheets ignored"),
+          _ => {}
+        }
+        if hidden_depth > 0 || is_hidden || matches!(tag, "text" | "image" | "use" | "style") {
+          hidden_depth += 1;
+          stack.push((parent_transform, parent_style));
+          continue;
+        }
+
+        let transform = match attribute(&attributes, "transform") {
+          Some(list) => parse_transform(list).then(&parent_transform),
+          None => parent_transform,
+        };
+        let style = parse_style(&attributes, parent_style, report);
+        if let Some(path) = shape_path(tag, &attributes, &transform, report) {
+          shapes.extend(stroke_path(&path, &transform, &style, report));
+        }
+        stack.push((transform, style));
+      }
+      XmlEvent::EndElement { .. } => {
+        stack.pop();
+        if hidden_depth > 0 {
+          hidden_depth -= 1;
+        }
+      }
+      _ => {}
+    }
+  }
+  Ok(shapes)
+}
+
+/// The outline of a basic shape, flattened and transformed onto the canvas.
+fn shape_path(
+  tag: &str,
+  attributes: &[OwnedAttribute],
+  transform: &Transform,
+  report: &mut ImportReport,
+) -> Option<Path> {
+  let number = |name| attribute(attributes, name).and_then(parse_length);
+  let mut builder = Path::builder()
+    .flattened(FLATTENING_TOLERANCE)
+    .transformed(*transform)
+    .with_svg();
+
+  match tag {
+    "path" => {
+      if !build_path_data(attribute(attributes, "d")?, &mut builder) {
+        report.note("malformed paths cut off at the error");
+      }
+    }
+    "line" => {
+      let from = point(number("x1").unwrap_or(0.0), number("y1").unwrap_or(0.0));
+      let to = point(number("x2").unwrap_or(0.0), number("y2").unwrap_or(0.0));
+      builder.move_to(from);
+      builder.line_to(to);
+    }
+    "polyline" | "polygon" => {
+      let mut data = PathData::new(attribute(attributes, "points")?);
+      let mut is_first = true;
+      while let (Some(x), Some(y)) = (data.number(), data.number()) {
+        match is_first {
+          true => builder.move_to(point(x, y)),
+          false => builder.line_to(point(x, y)),
+        };
+        is_first = false;
+      }
+      if tag == "polygon" {
+        builder.close();
+      }
+    }
+    "rect" => {
+      let (x, y) = (number("x").unwrap_or(0.0), number("y").unwrap_or(0.0));
+      let (width, height) = (number("width")?, number("height")?);
+      // a missing radius takes the other one
+      let (rx, ry) = match (number("rx"), number("ry")) {
+        (Some(rx), Some(ry)) => (rx, ry),
+        (Some(r), None) | (None, Some(r)) => (r, r),
+        (None, None) => (0.0, 0.0),
+      };
+      let (rx, ry) = (rx.min(width / 2.0), ry.min(height / 2.0));
+      let radii = vector(rx, ry);
+      let flags = ArcFlags {
+        large_arc: false,
+        sweep: true,
+      };
+      builder.move_to(point(x + rx, y));
+      builder.line_to(point(x + width - rx, y));
+      builder.arc_to(radii, Angle::zero(), flags, point(x + width, y + ry));
+      builder.line_to(point(x + width, y + height - ry));
+      builder.arc_to(
+        radii,
+        Angle::zero(),
+        flags,
+        point(x + width - rx, y + height),
+      );
+      builder.line_to(point(x + rx, y + height));
+      builder.arc_to(radii, Angle::zero(), flags, point(x, y + height - ry));
+      builder.line_to(point(x, y + ry));
+      builder.arc_to(radii, Angle::zero(), flags, point(x + rx, y));
+      builder.close();
+    }
+    "circle" | "ellipse" => {
+      let center = point(number("cx").unwrap_or(0.0), number("cy").unwrap_or(0.0));
+      let radii = match tag {
+        "circle" => vector(number("r")?, number("r")?),
+        _ => vector(number("rx")?, number("ry")?),
+      };
+      let flags = ArcFlags {
+        large_arc: false,
+        sweep: true,
+      };
+      builder.move_to(center + vector(radii.x, 0.0));
+      builder.arc_to(radii, Angle::zero(), flags, center - vector(radii.x, 0.0));
+      builder.arc_to(radii, Angle::zero(), flags, center + vector(radii.x, 0.0));
+      builder.close();
+    }
+    _ => return None,
+  }
+  Some(builder.build())
+}
+
+/// Turns the subpaths of a flattened path into shapes, painted like the svg would draw them.
+fn stroke_path(
+  path: &Path,
+  transform: &Transform,
+  style: &Style,
+  report: &mut ImportReport,
+) -> Vec<Shape> {
+  let (paint, opacity, width) = match (style.stroke, style.fill) {
+    (Some(stroke), fill) => {
+      if fill.is_some() {
+        report.note("fills dropped");
+      }
+      (stroke, style.stroke_opacity, style.stroke_width)
+    }
+    (None, Some(fill)) => {
+      report.note("fills drawn as outlines");
+      (fill, style.fill_opacity, 1.0)
+    }
+    (None, None) => return Vec::new(),
+  };
+  // transforms scale the width too, non uniform ones on average
+  let width_canvas = width * transform.determinant().abs().sqrt();
+  let width_multiplier = stroke::width_multiplier_for(width_canvas);
+  let [r, g, b] = paint;
+  let alpha = (opacity * style.opacity).clamp(0.0, 1.0);
+  let color = palette::Srgba::new(r, g, b, 255)
+    .into_format::<f32, f32>()
+    .into_linear();
+  let color = palette::LinSrgba::new(color.red, color.green, color.blue, alpha);
+
+  let mut shapes = Vec::new();
+  let mut points = Vec::new();
+  let to_na = |p: Point| na::point![p.x, p.y];
+  for event in path.iter() {
+    match event {
+      PathEvent::Begin { at } => points = vec![to_na(at)],
+      PathEvent::Line { to, .. } => push_point(&mut points, to_na(to)),
+      PathEvent::End { first, close, .. } => {
+        if close {
+          push_point(&mut points, to_na(first));
+        }
+        // a dot
+        if points.len() == 1 {
+          points.push(points[0]);
+        }
+        shapes.push(Shape {
+          points: std::mem::take(&mut points),
+          color,
+          width_multiplier,
+        });
+      }
+      // the path is flattened
+      _ => {}
+    }
+  }
+  shapes
+}
+
+/// sharp rect corners and paths that return to their start before closing
+/// would repeat points
+fn push_point(points: &mut Vec<na::Point2<f32>>, point: na::Point2<f32>) {
+  if points.last() != Some(&point) {
+    points.push(point);
+  }
+}
+
+/// Feeds the path data to the builder. `false` if it is malformed,
+/// in which case everything up to the error is built, like browsers do.
+fn build_path_data(d: &str, builder: &mut impl SvgPathBuilder) -> bool {
+  let mut data = PathData::new(d);
+  let mut command = None;
+  loop {
+    data.skip_separators();
+    if data.is_done() {
+      return true;
+    }
+    command = match data.command() {
+      Some(command) => Some(command),
+      // without a letter the last command repeats, a move turns into a line
+      None => match command {
+        Some(b'M') => Some(b'L'),
+        Some(b'm') => Some(b'l'),
+        Some(b'Z' | b'z') | None => return false,
+        command => command,
+      },
+    };
+    let Some(command) = command else { return false };
+    let is_built = (|| {
+      let mut p = || Some(point(data.number()?, data.number()?));
+      match command {
+        b'M' => builder.move_to(p()?),
+        b'm' => builder.relative_move_to(p()?.to_vector()),
+        b'L' => builder.line_to(p()?),
+        b'l' => builder.relative_line_to(p()?.to_vector()),
+        b'H' => builder.horizontal_line_to(data.number()?),
+        b'h' => builder.relative_horizontal_line_to(data.number()?),
+        b'V' => builder.vertical_line_to(data.number()?),
+        b'v' => builder.relative_vertical_line_to(data.number()?),
+        b'C' => builder.cubic_bezier_to(p()?, p()?, p()?),
+        b'c' => {
+          builder.relative_cubic_bezier_to(p()?.to_vector(), p()?.to_vector(), p()?.to_vector())
+        }
+        b'S' => builder.smooth_cubic_bezier_to(p()?, p()?),
+        b's' => builder.smooth_relative_cubic_bezier_to(p()?.to_vector(), p()?.to_vector()),
+        b'Q' => builder.quadratic_bezier_to(p()?, p()?),
+        b'q' => builder.relative_quadratic_bezier_to(p()?.to_vector(), p()?.to_vector()),
+        b'T' => builder.smooth_quadratic_bezier_to(p()?),
+        b't' => builder.smooth_relative_quadratic_bezier_to(p()?.to_vector()),
+        b'A' | b'a' => {
+          let radii = vector(data.number()?.abs(), data.number()?.abs());
+          let x_rotation = Angle::degrees(data.number()?);
+          let flags = ArcFlags {
+            large_arc: data.flag()?,
+            sweep: data.flag()?,
+          };
+          let to = point(data.number()?, data.number()?);
+          match command {
+            b'A' => builder.arc_to(radii, x_rotation, flags, to),
+            _ => builder.relative_arc_to(radii, x_rotation, flags, to.to_vector()),
+          }
+        }
+        b'Z' | b'z' => builder.close(),
+        _ => return None,
+      }
+      Some(())
+    })();
+    if is_built.is_none() {
+      return false;
+    }
+  }
+}
+
+/// Scanner over path data and point lists.
+struct PathData<'a> {
+  data: &'a [u8],
+  i: usize,
+}
+
+impl<'a> PathData<'a> {
+  fn new(data: &'a str) -> Self {
+    Self {
+      data: data.as_bytes(),
+      i: 0,
+    }
+  }
+
+  fn is_done(&self) -> bool {
+    self.i >= self.data.len()
+  }
+
+  fn peek(&self) -> Option<u8> {
+    self.data.get(self.i).copied()
+  }
+
+  fn skip_separators(&mut self) {
+    while matches!(self.peek(), Some(c) if c.is_ascii_whitespace() || c == b',') {
+      self.i += 1;
+    }
+  }
+
+  fn command(&mut self) -> Option<u8> {
+    let command = self
+      .peek()
+      .filter(|c| b"MmLlHhVvCcSsQqTtAaZz".contains(c))?;
+    self.i += 1;
+    Some(command)
+  }
+
+  /// a number, which ends at the next sign, second dot or anything else
+  fn number(&mut self) -> Option<f32> {
+    self.skip_separators();
+    let start = self.i;
+    let digits = |scanner: &mut Self| {
+      let start = scanner.i;
+      while matches!(scanner.peek(), Some(c) if c.is_ascii_digit()) {
+        scanner.i += 1;
+      }
+      scanner.i > start
+    };
+    if matches!(self.peek(), Some(b'-' | b'+')) {
+      self.i += 1;
+    }
+    let mut has_digits = digits(self);
+    if self.peek() == Some(b'.') {
+      self.i += 1;
+      has_digits |= digits(self);
+    }
+    if !has_digits {
+      self.i = start;
+      return None;
+    }
+    if matches!(self.peek(), Some(b'e' | b'E')) {
+      let mantissa_end = self.i;
+      self.i += 1;
+      if matches!(self.peek(), Some(b'-' | b'+')) {
+        self.i += 1;
+      }
+      if !digits(self) {
+        self.i = mantissa_end;
+      }
+    }
+    std::str::from_utf8(&self.data[start..self.i])
+      .ok()?
+      .parse()
+      .ok()
+  }
+
+  /// arc flags are single digits, which may be written without separators
+  fn flag(&mut self) -> Option<bool> {
+    self.skip_separators();
+    let flag = match self.peek()? {
+      b'0' => false,
+      b'1' => true,
+      _ => return None,
+    };
+    self.i += 1;
+    Some(flag)
+  }
+}
+
+/// Maps the view box onto the declared size of the document, in pixels.
+/// Keeps the aspect ratio, the default of `preserveAspectRatio`, but does not align.
+fn viewbox_transform(attributes: &[OwnedAttribute]) -> Transform {
+  let size = |name| attribute(attributes, name).and_then(parse_length);
+  let Some(view_box) = attribute(attributes, "viewBox") else {
+    return Transform::identity();
+  };
+  let mut data = PathData::new(view_box);
+  let (Some(x), Some(y), Some(width), Some(height)) =
+    (data.number(), data.number(), data.number(), data.number())
+  else {
+    return Transform::identity();
+  };
+  if width <= 0.0 || height <= 0.0 {
+    return Transform::identity();
+  }
+  let scale_x = size("width").map_or(1.0, |size| size / width);
+  let scale_y = size("height").map_or(scale_x, |size| size / height);
+  let scale = scale_x.min(scale_y);
+  Transform::translation(-x, -y).then_scale(scale, scale)
+}
+
+/// Parses a transform list like `translate(10 20) rotate(45)`,
+/// where the rightmost transform applies first.
+fn parse_transform(list: &str) -> Transform {
+  let mut transform = Transform::identity();
+  for item in list.split(')') {
+    let Some((name, arguments)) = item.split_once('(') else { continue };
+    let mut data = PathData::new(arguments);
+    let arguments: Vec<f32> = std::iter::from_fn(|| data.number()).collect();
+    let item_transform = match (
+      name.trim_matches(|c: char| c.is_whitespace() || c == ','),
+      arguments.as_slice(),
+    ) {
+      ("matrix", &[a, b, c, d, e, f]) => Transform::new(a, b, c, d, e, f),
+      ("translate", &[x]) => Transform::translation(x, 0.0),
+      ("translate", &[x, y]) => Transform::translation(x, y),
+      ("scale", &[s]) => Transform::scale(s, s),
+      ("scale", &[x, y]) => Transform::scale(x, y),
+      ("rotate", &[angle]) => Transform::rotation(Angle::degrees(angle)),
+      ("rotate", &[angle, x, y]) => Transform::translation(-x, -y)
+        .then_rotate(Angle::degrees(angle))
+        .then_translate(vector(x, y)),
+      ("skewX", &[angle]) => Transform::new(1.0, 0.0, angle.to_radians().tan(), 1.0, 0.0, 0.0),
+      ("skewY", &[angle]) => Transform::new(1.0, angle.to_radians().tan(), 0.0, 1.0, 0.0, 0.0),
+      _ => continue,
+    };
+    transform = item_transform.then(&transform);
+  }
+  transform
+}
+
+/// Applies the presentation attributes and then the inline style, which takes precedence.
+fn parse_style(
+  attributes: &[OwnedAttribute],
+  mut style: Style,
+  report: &mut ImportReport,
+) -> Style {
+  let declarations = attribute(attributes, "style")
+    .into_iter()
+    .flat_map(|style| style.split(';'))
+    .filter_map(|declaration| declaration.split_once(':'))
+    .map(|(name, value)| (name.trim(), value.trim()));
+  let attributes = attributes
+    .iter()
+    .map(|attribute| (attribute.name.local_name.as_str(), attribute.value.as_str()));
+
+  for (name, value) in attributes.chain(declarations) {
+    match name {
+      "stroke" => style.stroke = parse_paint(value, style.stroke, report),
+      "fill" => style.fill = parse_paint(value, style.fill, report),
+      "stroke-width" => style.stroke_width = parse_length(value).unwrap_or(style.stroke_width),
+      "stroke-opacity" => style.stroke_opacity = value.parse().unwrap_or(style.stroke_opacity),
+      "fill-opacity" => style.fill_opacity = value.parse().unwrap_or(style.fill_opacity),
+      // group opacity applies to every shape, which is close enough without overlaps
+      "opacity" => style.opacity *= value.parse().unwrap_or(1.0),
+      _ => {}
+    }
+  }
+  style
+}
+
+fn parse_paint(value: &str, inherited: Paint, report: &mut ImportReport) -> Paint {
+  match value {
+    "none" | "transparent" => None,
+    "inherit" => inherited,
+    "currentColor" => Some([0, 0, 0]),
+    _ if value.starts_with("url(") => {
+      report.note("gradients and patterns drawn black");
+      Some([0, 0, 0])
+    }
+    _ => match parse_color(value) {
+      Some(color) => Some(color),
+      None => {
+        report.note("unknown colors drawn black");
+        Some([0, 0, 0])
+      }
+    },
+  }
+}
+
+/// `#rgb`, `#rrggbb`, `rgb(r, g, b)` and the basic color keywords
+fn parse_color(value: &str) -> Option<[u8; 3]> {
+  if let Some(hex) = value.strip_prefix('#') {
+    let digit = |i: usize| u8::from_str_radix(hex.get(i..i + 1)?, 16).ok();
+    let channel = |i: usize| u8::from_str_radix(hex.get(2 * i..2 * i + 2)?, 16).ok();
+    return match hex.len() {
+      3 => Some([digit(0)? * 17, digit(1)? * 17, digit(2)? * 17]),
+      6 => Some([channel(0)?, channel(1)?, channel(2)?]),
+      _ => None,
+    };
+  }
+  if let Some(arguments) = value
+    .strip_prefix("rgb(")
+    .and_then(|arguments| arguments.strip_suffix(')'))
+  {
+    let mut channels = arguments.split(',').map(|channel| {
+      let channel = channel.trim();
+      match channel.strip_suffix('%') {
+        Some(percent) => percent.parse::<f32>().ok().map(|p| p * 2.55),
+        None => channel.parse::<f32>().ok(),
+      }
+      .map(|channel| channel.round().clamp(0.0, 255.0) as u8)
+    });
+    return Some([channels.next()??, channels.next()??, channels.next()??]);
+  }
+  let color = match value.to_ascii_lowercase().as_str() {
+    "black" => [0, 0, 0],
+    "white" => [255, 255, 255],
+    "gray" | "grey" => [128, 128, 128],
+    "silver" => [192, 192, 192],
+    "red" => [255, 0, 0],
+    "maroon" => [128, 0, 0],
+    "orange" => [255, 165, 0],
+    "yellow" => [255, 255, 0],
+    "olive" => [128, 128, 0],
+    "lime" => [0, 255, 0],
+    "green" => [0, 128, 0],
+    "aqua" | "cyan" => [0, 255, 255],
+    "teal" => [0, 128, 128],
+    "blue" => [0, 0, 255],
+    "navy" => [0, 0, 128],
+    "fuchsia" | "magenta" => [255, 0, 255],
+    "purple" => [128, 0, 128],
+    _ => return None,
+  };
+  Some(color)
+}
+
+/// a length in pixels, percentages are not supported
+fn parse_length(value: &str) -> Option<f32> {
+  let value = value.trim();
+  let split = value
+    .find(|c: char| c.is_ascii_alphabetic() || c == '%')
+    .unwrap_or(value.len());
+  let (number, unit) = value.split_at(split);
+  let pixels_per_unit = match unit {
+    "" | "px" => 1.0,
+    "pt" => 1.0 / POINTS_PER_PIXEL,
+    "pc" => 16.0,
+    "mm" => 96.0 / 25.4,
+    "cm" => 960.0 / 25.4,
+    "in" => 96.0,
+    _ => return None,
+  };
+  number
+    .trim()
+    .parse::<f32>()
+    .ok()
+    .map(|n| n * pixels_per_unit)
+}
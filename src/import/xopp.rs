@@ -0,0 +1,312 @@
+use super::{attribute, Import, ImportError, ImportReport};
+
+use crate::{
+  content::Content,
+  gfx::stroke,
+  pdf::{page_norm_to_canvas_point, PageList, PdfLayout, PdfPageInfo, CANVAS_UNITS_PER_PDF_POINT},
+  stroke::Stroke,
+};
+
+use flate2::read::GzDecoder;
+use std::{
+  fs,
+  io::Read,
+  path::{Path, PathBuf},
+};
+use xml::{
+  attribute::OwnedAttribute,
+  reader::{EventReader, XmlEvent},
+};
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Imports a Xournal++ notebook (or an old Xournal `.xoj`).
+///
+/// Each page becomes a page of the page list, laid out with the default layout.
+/// Pdf backgrounds are looked up at their recorded path and next to the notebook.
+pub fn import_xopp(path: &Path) -> Result<Import, ImportError> {
+  let data = fs::read(path)?;
+  let xml = match data.starts_with(&GZIP_MAGIC) {
+    true => {
+      let mut xml = Vec::new();
+      GzDecoder::new(data.as_slice()).read_to_end(&mut xml)?;
+      xml
+    }
+    false => data,
+  };
+
+  let mut report = ImportReport::default();
+  let pages = parse(&xml, &mut report)?;
+  if pages.is_empty() {
+    return Err(ImportError::Invalid("the notebook has no pages"));
+  }
+  let content = convert(pages, path, &mut report);
+  Ok(Import { content, report })
+}
+
+struct XoppPage {
+  size: PdfPageInfo,
+  background: XoppBackground,
+  strokes: Vec<XoppStroke>,
+}
+
+enum XoppBackground {
+  /// plain paper, possibly ruled
+  Solid,
+  Pdf {
+    /// only recorded on the first page with the pdf
+    file: Option<(String, String)>,
+    /// zero based
+    page: usize,
+  },
+  Unsupported,
+}
+
+struct XoppStroke {
+  color: [u8; 4],
+  /// the nominal width followed by one width per segment if pressure was recorded
+  widths: Vec<f32>,
+  /// in points, origin upper left corner of the page
+  points: Vec<na::Point2<f32>>,
+}
+
+fn parse(xml: &[u8], report: &mut ImportReport) -> Result<Vec<XoppPage>, ImportError> {
+  let mut pages = Vec::new();
+  let mut page: Option<XoppPage> = None;
+  let mut nlayers = 0;
+  let mut stroke: Option<XoppStroke> = None;
+  let mut is_root_checked = false;
+
+  for event in EventReader::new(xml) {
+    match event? {
+      XmlEvent::StartElement {
+        name, attributes, ..
+      } => {
+        let tag = name.local_name.as_str();
+        if !is_root_checked {
+          if tag != "xournal" {
+            return Err(ImportError::Invalid("not a xournal notebook"));
+          }
+          is_root_checked = true;
+          continue;
+        }
+        match tag {
+          "page" => {
+            let width = number(&attributes, "width")?;
+            let height = number(&attributes, "height")?;
+            page = Some(XoppPage {
+              size: PdfPageInfo {
+                width_points: width,
+                height_points: height,
+              },
+              background: XoppBackground::Solid,
+              strokes: Vec::new(),
+            });
+            nlayers = 0;
+          }
+          "background" => {
+            let Some(page) = &mut page else { continue };
+            page.background = parse_background(&attributes, report);
+          }
+          "layer" => {
+            nlayers += 1;
+            if nlayers == 2 {
+              report.note("pages with layers merged into one");
+            }
+          }
+          "stroke" => {
+            let widths = attribute(&attributes, "width")
+              .unwrap_or("1")
+              .split_whitespace()
+              .filter_map(|width| width.parse().ok())
+              .collect();
+            let color = attribute(&attributes, "color").and_then(parse_color);
+            if color.is_none() {
+              report.note("stroke colors unknown, drawn black");
+            }
+            if attribute(&attributes, "fill").map_or(false, |fill| fill != "-1") {
+              report.note("stroke fills dropped");
+            }
+            stroke = Some(XoppStroke {
+              color: color.unwrap_or([0, 0, 0, 255]),
+              widths,
+              points: Vec::new(),
+            });
+          }
+          "text" => report.note("texts skipped"),
+          "image" => report.note("images skipped"),
+          "teximage" => report.note("latex formulas skipped"),
+          _ => {}
+        }
+      }
+      XmlEvent::Characters(text) => {
+        if let Some(stroke) = &mut stroke {
+          let coordinates: Vec<f32> = text
+            .split_whitespace()
+            .filter_map(|c| c.parse().ok())
+            .collect();
+          stroke.points.extend(
+            coordinates
+              .chunks_exact(2)
+              .map(|xy| na::point![xy[0], xy[1]]),
+          );
+        }
+      }
+      XmlEvent::EndElement { name } => match name.local_name.as_str() {
+        "stroke" => {
+          if let (Some(page), Some(stroke)) = (&mut page, stroke.take()) {
+            page.strokes.push(stroke);
+          }
+        }
+        "page" => pages.extend(page.take()),
+        _ => {}
+      },
+      _ => {}
+    }
+  }
+  Ok(pages)
+}
+
+fn parse_background(attributes: &[OwnedAttribute], report: &mut ImportReport) -> XoppBackground {
+  match attribute(attributes, "type") {
+    Some("solid") => {
+      if attribute(attributes, "style").map_or(false, |style| style != "plain") {
+        report.note("ruled backgrounds drawn plain");
+      }
+      let color = attribute(attributes, "color").and_then(parse_color);
+      if color.map_or(false, |[r, g, b, _]| [r, g, b] != [255, 255, 255]) {
+        report.note("colored backgrounds drawn white");
+      }
+      XoppBackground::Solid
+    }
+    Some("pdf") => {
+      let file = attribute(attributes, "filename").map(|filename| {
+        let domain = attribute(attributes, "domain").unwrap_or("absolute");
+        (domain.to_owned(), filename.to_owned())
+      });
+      // the page number is one based and may have a legacy "ll" suffix
+      let page = attribute(attributes, "pageno")
+        .map(|pageno| pageno.trim_end_matches(|c: char| !c.is_ascii_digit()))
+        .and_then(|pageno| pageno.parse::<usize>().ok())
+        .unwrap_or(1);
+      XoppBackground::Pdf {
+        file,
+        page: page.saturating_sub(1),
+      }
+    }
+    _ => {
+      report.note("image backgrounds drawn plain");
+      XoppBackground::Unsupported
+    }
+  }
+}
+
+fn convert(pages: Vec<XoppPage>, xopp_path: &Path, report: &mut ImportReport) -> Content {
+  let mut page_list = PageList::default();
+  let mut pdf: Option<PathBuf> = None;
+  for page in &pages {
+    let index = page_list.npages();
+    match &page.background {
+      XoppBackground::Pdf { file, page: ipage } => {
+        if let Some((domain, filename)) = file {
+          pdf = find_pdf(xopp_path, domain, filename);
+          if pdf.is_none() {
+            report.note("pdf backgrounds missing, pages left blank");
+          }
+        }
+        match &pdf {
+          Some(pdf) => page_list.insert_pdf(index, pdf.clone(), *ipage..*ipage + 1),
+          None => page_list.insert_blank(index, page.size),
+        }
+      }
+      XoppBackground::Solid | XoppBackground::Unsupported => {
+        page_list.insert_blank(index, page.size)
+      }
+    }
+  }
+
+  let page_sizes_canvas: Vec<_> = pages.iter().map(|page| page.size.size_canvas()).collect();
+  let page_ids: Vec<_> = page_list.pages().iter().map(|entry| entry.id).collect();
+  let page_rects_canvas = PdfLayout::default().page_rects_canvas(&page_ids, &page_sizes_canvas);
+
+  let mut strokes = Vec::new();
+  for (page, page_rect_canvas) in pages.iter().zip(&page_rects_canvas) {
+    let to_canvas = |point: &na::Point2<f32>| {
+      let point_page_norm = na::point![
+        point.x / page.size.width_points,
+        point.y / page.size.height_points
+      ];
+      page_norm_to_canvas_point(page_rect_canvas, point_page_norm)
+    };
+    for xopp_stroke in &page.strokes {
+      let mut points: Vec<_> = xopp_stroke.points.iter().map(to_canvas).collect();
+      match points.len() {
+        0 => {
+          report.note("empty strokes skipped");
+          continue;
+        }
+        // a dot
+        1 => points.push(points[0]),
+        _ => {}
+      }
+
+      // the model has one width per stroke
+      let width_points = match xopp_stroke.widths.as_slice() {
+        [] => 1.0,
+        [width] => *width,
+        [_, pressure_widths @ ..] => {
+          report.note("pressure sensitive strokes with averaged width");
+          pressure_widths.iter().sum::<f32>() / pressure_widths.len() as f32
+        }
+      };
+      let width_multiplier =
+        stroke::width_multiplier_for(width_points * CANVAS_UNITS_PER_PDF_POINT);
+
+      let [r, g, b, a] = xopp_stroke.color;
+      let color = palette::Srgba::new(r, g, b, a)
+        .into_format::<f32, f32>()
+        .into_linear();
+      strokes.push(Stroke::new(points, color, width_multiplier));
+    }
+  }
+
+  Content::new(page_list, strokes)
+}
+
+/// Looks for the pdf where the notebook says it is and next to the notebook.
+fn find_pdf(xopp_path: &Path, domain: &str, filename: &str) -> Option<PathBuf> {
+  let recorded = match domain {
+    // attached pdfs are stored as `<notebook>.<filename>`
+    "attach" => {
+      let mut attached = xopp_path.as_os_str().to_owned();
+      attached.push(".");
+      attached.push(filename);
+      PathBuf::from(attached)
+    }
+    _ => PathBuf::from(filename),
+  };
+  let beside = xopp_path
+    .parent()
+    .zip(recorded.file_name())
+    .map(|(dir, name)| dir.join(name));
+  std::iter::once(recorded)
+    .chain(beside)
+    .find(|path| path.is_file())
+}
+
+fn number(attributes: &[OwnedAttribute], name: &'static str) -> Result<f32, ImportError> {
+  attribute(attributes, name)
+    .and_then(|value| value.parse().ok())
+    .ok_or(ImportError::Invalid("page without size"))
+}
+
+/// parses `#rrggbbaa` or `#rrggbb`
+fn parse_color(color: &str) -> Option<[u8; 4]> {
+  let hex = color.strip_prefix('#')?;
+  let channel = |i: usize| u8::from_str_radix(hex.get(2 * i..2 * i + 2)?, 16).ok();
+  match hex.len() {
+    8 => Some([channel(0)?, channel(1)?, channel(2)?, channel(3)?]),
+    6 => Some([channel(0)?, channel(1)?, channel(2)?, 255]),
+    _ => None,
+  }
+}
@@ -12,6 +12,8 @@ pub struct InputManager {
 
   pub cursor_pos_screen_logical_left_clicked: Option<na::Point2<f32>>,
   pub mouse_scroll_delta_logical: Option<na::Vector2<f32>>,
+  /// pinch or ctrl+scroll zoom, only reported by egui
+  pub zoom_factor: Option<f32>,
   pub multi_touch_movement: Option<TouchMovement>,
 }
 
@@ -136,6 +138,7 @@ impl InputManager {
   pub fn reset(&mut self) {
     self.prev = self.curr.clone();
     self.mouse_scroll_delta_logical = None;
+    self.zoom_factor = None;
   }
 
   pub fn handle_event(&mut self, event: &WindowEvent, spaces: &SpaceManager) {
@@ -228,6 +231,126 @@ impl InputManager {
     };
   }
 
+  /// Like `handle_event`, for the input egui gives a widget.
+  /// Without focus only releases are taken, so that nothing stays pressed.
+  pub fn handle_egui_input(
+    &mut self,
+    input: &egui::InputState,
+    has_focus: bool,
+    spaces: &SpaceManager,
+  ) {
+    let modifiers = [
+      (VirtualKeyCode::LShift, input.modifiers.shift),
+      (VirtualKeyCode::LControl, input.modifiers.ctrl),
+      (VirtualKeyCode::LAlt, input.modifiers.alt),
+    ];
+    for (key, is_down) in modifiers {
+      if is_down && has_focus {
+        self.curr.pressed.insert(key);
+      } else if !is_down {
+        self.curr.pressed.remove(&key);
+      }
+    }
+    if has_focus {
+      self.curr.modifiers = ModifiersState::empty();
+      self
+        .curr
+        .modifiers
+        .set(ModifiersState::SHIFT, input.modifiers.shift);
+      self
+        .curr
+        .modifiers
+        .set(ModifiersState::CTRL, input.modifiers.ctrl);
+      self
+        .curr
+        .modifiers
+        .set(ModifiersState::ALT, input.modifiers.alt);
+      self
+        .curr
+        .modifiers
+        .set(ModifiersState::LOGO, input.modifiers.mac_cmd);
+    }
+
+    let to_screen_logical = |pos: egui::Pos2| {
+      spaces.transform_point(
+        na::point![pos.x, pos.y],
+        Space::WindowLogical,
+        Space::ScreenLogical,
+      )
+    };
+    for event in &input.events {
+      match event {
+        egui::Event::PointerButton {
+          button, pressed, ..
+        } => {
+          let button = match button {
+            egui::PointerButton::Primary => MouseButton::Left,
+            egui::PointerButton::Secondary => MouseButton::Right,
+            egui::PointerButton::Middle => MouseButton::Middle,
+            egui::PointerButton::Extra1 => MouseButton::Other(1),
+            egui::PointerButton::Extra2 => MouseButton::Other(2),
+          };
+          if *pressed && has_focus {
+            self.curr.clicked.insert(button);
+          } else if !*pressed {
+            self.curr.clicked.remove(&button);
+            self.cursor_pos_screen_logical_left_clicked = None;
+          }
+        }
+        egui::Event::Key { key, pressed, .. } => {
+          let Some(key) = virtual_key_code(*key) else {
+            continue;
+          };
+          if *pressed && has_focus {
+            self.curr.pressed.insert(key);
+          } else if !*pressed {
+            self.curr.pressed.remove(&key);
+          }
+        }
+        egui::Event::PointerGone => self.curr.cursor_pos_screen_logical = None,
+        egui::Event::PointerMoved(pos) => {
+          self.curr.cursor_pos_screen_logical = Some(to_screen_logical(*pos));
+
+          if self.is_clicked(MouseButton::Left)
+            && self.cursor_pos_screen_logical_left_clicked.is_none()
+          {
+            self.cursor_pos_screen_logical_left_clicked = self.curr.cursor_pos_screen_logical;
+          }
+        }
+        // egui scrolls in points, which are logical already
+        egui::Event::Scroll(delta) if has_focus => {
+          let delta = na::vector![delta.x, delta.y];
+          *self
+            .mouse_scroll_delta_logical
+            .get_or_insert_with(na::Vector2::zeros) += delta;
+        }
+        egui::Event::Zoom(factor) if has_focus => {
+          *self.zoom_factor.get_or_insert(1.0) *= factor;
+        }
+        egui::Event::Touch { id, phase, pos, .. } => {
+          let touch = Touch {
+            position_screen_logical: to_screen_logical(*pos),
+          };
+          match phase {
+            egui::TouchPhase::Start if has_focus => {
+              self.curr.touches.insert(id.0, touch);
+            }
+            egui::TouchPhase::Move => {
+              if let Some(curr) = self.curr.touches.get_mut(&id.0) {
+                *curr = touch;
+              }
+            }
+            egui::TouchPhase::End | egui::TouchPhase::Cancel => {
+              self.curr.touches.remove(&id.0);
+            }
+            _ => {}
+          }
+        }
+        _ => {}
+      }
+    }
+  }
+
   pub fn update(&mut self) {
     self.curr.multi_touch = self.curr.compute_multi_touch();
     self.multi_touch_movement = self.compute_touch_movement();
@@ -279,6 +402,23 @@ impl InputsSnapshot {
   }
 }
 
+/// the keys carveout has bindings for
+fn virtual_key_code(key: egui::Key) -> Option<VirtualKeyCode> {
+  let key = match key {
+    egui::Key::W => VirtualKeyCode::W,
+    egui::Key::A => VirtualKeyCode::A,
+    egui::Key::S => VirtualKeyCode::S,
+    egui::Key::D => VirtualKeyCode::D,
+    egui::Key::Q => VirtualKeyCode::Q,
+    egui::Key::E => VirtualKeyCode::E,
+    egui::Key::Space => VirtualKeyCode::Space,
+    egui::Key::PageUp => VirtualKeyCode::PageUp,
+    egui::Key::PageDown => VirtualKeyCode::PageDown,
+    _ => return None,
+  };
+  Some(key)
+}
+
 type TouchId = u64;
 
 #[derive(Clone, Debug)]
@@ -1,5 +1,234 @@
 use carveout::run;
 
 fn main() {
+  #[cfg(not(target_arch = "wasm32"))]
+  {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match cli::parse(&args) {
+      Ok(cli::Command::Open(files)) => futures::executor::block_on(carveout::run_with(files)),
+      Ok(command) => std::process::exit(cli::run(command)),
+      Err(message) => {
+        eprintln!("{message}\n\n{}", cli::USAGE);
+        std::process::exit(2);
+      }
+    }
+  }
+  #[allow(unreachable_code)]
   futures::executor::block_on(run());
 }
+
+#[cfg(not(target_arch = "wasm32"))]
+mod cli {
+  use carveout::{
+    headless::{self, ExportOptions, HeadlessError, SaveFormat},
+    OpenFiles,
+  };
+
+  use std::path::{Path, PathBuf};
+
+  pub const USAGE: &str = "\
+usage:
+  carveout [<savefile.co>] [<document.pdf>...]
+      open the savefile and append the pages of the pdfs
+  carveout export <savefile> <output> [--dpi <dpi>] [--no-background]
+      export everything as png, svg, pdf or inkml, after the output extension
+  carveout info <savefile>
+      show what the savefile holds
+  carveout compact <savefile> (<output> | --in-place)
+      rewrite the savefile in the compact format, with the pdfs embedded
+  carveout strip-history <savefile> (<output> | --in-place)
+      drop the undo history
+  carveout convert <input> <output> [--compact]
+      convert a savefile, Xournal++ notebook, InkML ink or svg drawing into a savefile
+  carveout --help
+      show this
+
+exit status:
+  0 done, 1 failed or nothing to export, 2 wrong arguments
+
+environment:
+  CARVEOUT_PDFIUM
+      path of the pdfium library to use instead of the system one
+";
+
+  pub enum Command {
+    Open(OpenFiles),
+    Help,
+    Export {
+      savefile: PathBuf,
+      output: PathBuf,
+      options: ExportOptions,
+    },
+    Info(PathBuf),
+    Compact {
+      savefile: PathBuf,
+      output: PathBuf,
+    },
+    StripHistory {
+      savefile: PathBuf,
+      output: PathBuf,
+    },
+    Convert {
+      input: PathBuf,
+      output: PathBuf,
+      format: SaveFormat,
+    },
+  }
+
+  /// the options, which only some commands take
+  #[derive(Default, PartialEq)]
+  struct Flags {
+    dpi: Option<f32>,
+    no_background: bool,
+    compact: bool,
+    in_place: bool,
+  }
+
+  pub fn parse(args: &[String]) -> Result<Command, String> {
+    let Some((command, rest)) = args.split_first() else {
+      return Ok(Command::Open(OpenFiles::default()));
+    };
+    if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+      return Ok(Command::Help);
+    }
+    let mut paths = Vec::new();
+    let mut flags = Flags::default();
+    let mut rest = rest.iter();
+    while let Some(arg) = rest.next() {
+      match arg.as_str() {
+        "--dpi" => {
+          let dpi = rest.next().ok_or("--dpi needs a value")?;
+          flags.dpi = Some(dpi.parse().map_err(|_| "the dpi has to be a number")?);
+        }
+        "--no-background" => flags.no_background = true,
+        "--compact" => flags.compact = true,
+        "--in-place" => flags.in_place = true,
+        flag if flag.starts_with("--") => return Err(format!("unknown option {flag}")),
+        _ => paths.push(PathBuf::from(arg)),
+      }
+    }
+
+    let no_flags = flags == Flags::default();
+    let command = match (command.as_str(), paths.as_slice()) {
+      ("export", [savefile, output]) if !flags.compact && !flags.in_place => {
+        let mut options = ExportOptions::default();
+        options.dpi = flags.dpi.unwrap_or(options.dpi);
+        options.pdf_background = !flags.no_background;
+        Command::Export {
+          savefile: savefile.clone(),
+          output: output.clone(),
+          options,
+        }
+      }
+      ("info", [savefile]) if no_flags => Command::Info(savefile.clone()),
+      ("compact", [savefile, output @ ..]) => Command::Compact {
+        savefile: savefile.clone(),
+        output: rewrite_output(command, savefile, output, &flags)?,
+      },
+      ("strip-history", [savefile, output @ ..]) => Command::StripHistory {
+        savefile: savefile.clone(),
+        output: rewrite_output(command, savefile, output, &flags)?,
+      },
+      ("convert", [input, output])
+        if flags.dpi.is_none() && !flags.no_background && !flags.in_place =>
+      {
+        Command::Convert {
+          input: input.clone(),
+          output: output.clone(),
+          format: match flags.compact {
+            true => SaveFormat::Binary,
+            false => SaveFormat::Ron,
+          },
+        }
+      }
+      ("export" | "info" | "compact" | "strip-history" | "convert", _) => {
+        return Err(format!("wrong arguments for {command}"));
+      }
+      _ => open_files(args)?,
+    };
+    Ok(command)
+  }
+
+  /// the savefile is only overwritten when asked for, so a typo cannot replace it
+  fn rewrite_output(
+    command: &str,
+    savefile: &Path,
+    output: &[PathBuf],
+    flags: &Flags,
+  ) -> Result<PathBuf, String> {
+    let in_place = Flags {
+      in_place: true,
+      ..Default::default()
+    };
+    match output {
+      [] if *flags == in_place => Ok(savefile.to_owned()),
+      [output] if *flags == Flags::default() => Ok(output.clone()),
+      [] => Err(format!("{command} needs an output or --in-place")),
+      _ => Err(format!("wrong arguments for {command}")),
+    }
+  }
+
+  fn open_files(args: &[String]) -> Result<Command, String> {
+    let mut files = OpenFiles::default();
+    for arg in args {
+      let path = PathBuf::from(arg);
+      match path.extension().and_then(|extension| extension.to_str()) {
+        Some("co") if files.document.is_none() => files.document = Some(path),
+        Some("co") => return Err("only one savefile can be opened".into()),
+        Some("pdf") => files.pdfs.push(path),
+        _ => return Err(format!("unknown command or file {arg}")),
+      }
+    }
+    Ok(Command::Open(files))
+  }
+
+  /// Runs a command without window, returns the exit code.
+  pub fn run(command: Command) -> i32 {
+    let result = match command {
+      Command::Open(_) => unreachable!(),
+      Command::Help => {
+        print!("{USAGE}");
+        return 0;
+      }
+      Command::Export {
+        savefile,
+        output,
+        options,
+      } => match headless::export(&savefile, &output, &options) {
+        Ok(true) => Ok(()),
+        // scripts must not go on with an output that was not written
+        Ok(false) => {
+          eprintln!(
+            "error: nothing to export, {} was not written",
+            output.display()
+          );
+          return 1;
+        }
+        Err(error) => Err(error),
+      },
+      Command::Info(savefile) => headless::info(&savefile).map(|info| println!("{info}")),
+      Command::Compact { savefile, output } => headless::compact(&savefile, &output),
+      Command::StripHistory { savefile, output } => headless::strip_history(&savefile, &output),
+      Command::Convert {
+        input,
+        output,
+        format,
+      } => headless::convert(&input, &output, format).map(|report| {
+        for line in report.lines() {
+          eprintln!("{line}");
+        }
+      }),
+    };
+    report(result)
+  }
+
+  fn report(result: Result<(), HeadlessError>) -> i32 {
+    match result {
+      Ok(()) => 0,
+      Err(error) => {
+        eprintln!("error: {error}");
+        1
+      }
+    }
+  }
+}
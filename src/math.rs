@@ -1,4 +1,4 @@
-#[derive(Debug, Copy, Clone, Default, PartialEq)]
+#[derive(Debug, Copy, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct Rect {
   pub extents_half: na::Vector2<f32>,
   pub center: na::Point2<f32>,
@@ -98,4 +98,122 @@ impl Rect {
   pub fn isometry(&self) -> na::Isometry2<f32> {
     na::Isometry2::new(self.center.coords, self.angle)
   }
+
+  /// whether the two rects overlap, taking their rotation into account.
+  /// touching counts as overlapping.
+  pub fn intersects(&self, other: &Rect) -> bool {
+    parry2d::query::intersection_test(
+      &self.isometry(),
+      &self.shape(),
+      &other.isometry(),
+      &other.shape(),
+    )
+    .unwrap()
+  }
+
+  pub fn contains_point(&self, point: na::Point2<f32>) -> bool {
+    use parry2d::query::PointQuery;
+    self.shape().contains_point(&self.isometry(), &point)
+  }
+
+  /// axis aligned bounding box as `(min, max)`.
+  pub fn aabb(&self) -> (na::Point2<f32>, na::Point2<f32>) {
+    let vertices = self.vertices();
+    let min = vertices.iter().fold(vertices[0], |a, b| a.inf(b));
+    let max = vertices.iter().fold(vertices[0], |a, b| a.sup(b));
+    (min, max)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::Rect;
+
+  use std::f32::consts::{FRAC_PI_2, FRAC_PI_4, SQRT_2};
+
+  fn rect(size: [f32; 2], center: [f32; 2]) -> Rect {
+    Rect::from_size_center(size.into(), center.into())
+  }
+
+  fn rotated(rect: Rect, angle: f32) -> Rect {
+    Rect { angle, ..rect }
+  }
+
+  fn assert_near(a: na::Point2<f32>, b: na::Point2<f32>) {
+    assert!((a - b).norm() < 1e-5, "{a} != {b}");
+  }
+
+  #[test]
+  fn intersects_overlapping_and_contained_rects() {
+    let square = rect([2.0, 2.0], [0.0, 0.0]);
+    assert!(square.intersects(&rect([2.0, 2.0], [1.0, 1.0])));
+    assert!(square.intersects(&rect([0.5, 0.5], [0.0, 0.0])));
+    assert!(rect([0.5, 0.5], [0.0, 0.0]).intersects(&square));
+    assert!(square.intersects(&rect([10.0, 0.1], [0.0, 0.0])));
+  }
+
+  #[test]
+  fn does_not_intersect_separate_rects() {
+    let square = rect([2.0, 2.0], [0.0, 0.0]);
+    assert!(!square.intersects(&rect([2.0, 2.0], [3.0, 0.0])));
+    assert!(!square.intersects(&rect([2.0, 2.0], [0.0, -2.5])));
+    assert!(!square.intersects(&rect([1.0, 1.0], [2.0, 2.0])));
+  }
+
+  #[test]
+  fn intersects_touching_rects() {
+    let square = rect([2.0, 2.0], [0.0, 0.0]);
+    // sharing an edge
+    assert!(square.intersects(&rect([2.0, 2.0], [2.0, 0.0])));
+    assert!(square.intersects(&rect([2.0, 2.0], [0.0, -2.0])));
+    // sharing a corner
+    assert!(square.intersects(&rect([2.0, 2.0], [2.0, 2.0])));
+  }
+
+  #[test]
+  fn intersects_rotated_rects() {
+    // a diamond, whose bounding box reaches to ±√2
+    let diamond = rotated(rect([2.0, 2.0], [0.0, 0.0]), FRAC_PI_4);
+    // in the corner of the bounding box, but outside of the diamond
+    let corner = rect([0.2, 0.2], [1.25, 1.25]);
+    assert!(!diamond.intersects(&corner));
+    assert!(diamond.intersects(&rect([0.2, 0.2], [1.3, 0.0])));
+    // touching the tip of the diamond
+    assert!(diamond.intersects(&rect([1.0, 1.0], [SQRT_2 + 0.5, 0.0])));
+    // both rotated, crossing like an x
+    let bar = rect([4.0, 0.2], [0.0, 0.0]);
+    assert!(rotated(bar, FRAC_PI_4).intersects(&rotated(bar, -FRAC_PI_4)));
+    let shifted = Rect {
+      center: na::point![3.0, 0.0],
+      ..rotated(bar, FRAC_PI_4)
+    };
+    assert!(!rotated(bar, -FRAC_PI_4).intersects(&shifted));
+  }
+
+  #[test]
+  fn aabb_of_axis_aligned_rects() {
+    let (min, max) = rect([4.0, 2.0], [1.0, -1.0]).aabb();
+    assert_near(min, na::point![-1.0, -2.0]);
+    assert_near(max, na::point![3.0, 0.0]);
+  }
+
+  #[test]
+  fn aabb_of_rotated_rects() {
+    // a quarter turn swaps width and height
+    let (min, max) = rotated(rect([4.0, 2.0], [1.0, -1.0]), FRAC_PI_2).aabb();
+    assert_near(min, na::point![0.0, -3.0]);
+    assert_near(max, na::point![2.0, 1.0]);
+
+    let (min, max) = rotated(rect([2.0, 2.0], [0.0, 0.0]), FRAC_PI_4).aabb();
+    assert_near(min, na::point![-SQRT_2, -SQRT_2]);
+    assert_near(max, na::point![SQRT_2, SQRT_2]);
+
+    // the bounding box contains every vertex
+    let rect = rotated(rect([3.0, 1.0], [-2.0, 5.0]), 0.3);
+    let (min, max) = rect.aabb();
+    for vertex in rect.vertices() {
+      assert!(min.x <= vertex.x && vertex.x <= max.x);
+      assert!(min.y <= vertex.y && vertex.y <= max.y);
+    }
+  }
 }
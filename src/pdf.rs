@@ -1,56 +1,693 @@
-use std::path::Path;
+#[cfg(feature = "app")]
+pub mod navigation;
+pub mod tile;
 
-use pdfium_render::prelude::*;
+mod export;
+#[cfg(feature = "app")]
+mod filter;
+mod highlight;
 
-#[ouroboros::self_referencing]
-pub struct PdfManager {
-  pdfium: Pdfium,
+mod layout;
+mod links;
+mod pages;
+#[cfg(feature = "app")]
+mod search;
+#[cfg(feature = "app")]
+mod text;
+mod worker;
+
+#[cfg(feature = "app")]
+pub use self::{
+  filter::PdfColorFilter,
+  search::{PdfSearchHit, PdfSearchResults},
+  text::PageText,
+};
+pub use self::{
+  highlight::PdfHighlight,
+  layout::PdfLayout,
+  links::{PdfLink, PdfLinkTarget},
+  pages::{PageEntry, PageId, PageKind, PageList},
+  worker::{PdfError, PdfSource, PdfThumbnail, RasterRequest, RasterResponse},
+};
+
+use self::worker::PdfWorker;
+#[cfg(feature = "app")]
+use self::worker::WorkerResponse;
+
+use crate::{content::access::ContentAccess, math::Rect};
 
-  #[borrows(pdfium)]
-  #[covariant]
-  document: PdfDocument<'this>,
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "app")]
+use std::collections::HashSet;
+use std::{
+  collections::HashMap,
+  sync::atomic::{AtomicU64, Ordering},
+};
 
-  #[borrows(document)]
-  #[covariant]
-  pages: PdfPages<'this>,
+// from `PdfPagePaperStandardSize::A4.width()`
+const A4_WIDTH_PDF_POINTS: f32 = 210.0;
+pub const CANVAS_UNITS_PER_PDF_POINT: f32 = 2.0 / A4_WIDTH_PDF_POINTS;
 
-  #[borrows(pages)]
-  #[covariant]
-  page_vec: Vec<PdfPage<'this>>,
+/// identifies a loaded document, so that derived data (e.g. rendered tiles) can be invalidated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PdfDocumentId(u64);
+impl PdfDocumentId {
+  fn next() -> Self {
+    static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+    Self(NEXT_ID.fetch_add(1, Ordering::Relaxed))
+  }
+}
+
+/// Owns the pdf documents.
+///
+/// All pdfium calls happen on the worker, because pdfium may only be used by one thread at a time.
+/// This side only keeps plain data about the loaded documents.
+///
+/// The page list lives in the content, this keeps a copy of it and the documents it references.
+#[derive(Default)]
+pub struct PdfManager {
+  worker: Option<PdfWorker>,
+  documents: HashMap<PdfSource, PdfDocumentInfo>,
+  /// sources of the page list that failed to load, their pages are placeholders
+  failed_sources: HashMap<PdfSource, PdfError>,
+  /// failures the ui has not shown yet
+  load_errors: Vec<PdfError>,
+  /// pages of the documents pdfium failed to render, they are not requested again
+  #[cfg(feature = "app")]
+  failed_renders: HashSet<(PdfDocumentId, usize)>,
+  pages: PageList,
+  /// changes whenever the page list does, so derived data (e.g. rendered tiles) can be invalidated
+  pages_generation: u64,
+  /// the outlines of all sources with pages in the page list
+  outline: Vec<PdfOutlineItem>,
+  layout: PdfLayout,
+  #[cfg(feature = "app")]
+  color_filter: PdfColorFilter,
+  /// whether the color filter also applies to the strokes
+  #[cfg(feature = "app")]
+  filters_ink: bool,
+  received_tiles: Vec<RasterResponse>,
+  received_thumbnails: Vec<PdfThumbnail>,
+  /// the query of the search the worker is working on
+  #[cfg(feature = "app")]
+  pending_search: Option<String>,
+  #[cfg(feature = "app")]
+  search_results: Option<PdfSearchResults>,
+  #[cfg(feature = "app")]
+  selected_search_hit: Option<usize>,
+  #[cfg(feature = "app")]
+  page_texts: HashMap<usize, PageText>,
+  #[cfg(feature = "app")]
+  requested_page_texts: HashSet<usize>,
 }
 
 impl PdfManager {
+  /// Loads the pdf unless it is loaded already.
+  /// Blocks until the document is loaded.
+  /// Failures are also kept for the ui, see `take_load_errors`.
+  pub fn load_source(&mut self, source: PdfSource) -> Result<&PdfDocumentInfo, PdfError> {
+    if !self.documents.contains_key(&source) {
+      let worker = self.worker.get_or_insert_with(PdfWorker::spawn);
+      match worker.load(PdfDocumentId::next(), source.clone()) {
+        Ok(info) => {
+          self.failed_sources.remove(&source);
+          self.documents.insert(source.clone(), info);
+        }
+        Err(error) => {
+          tracing::error!("{error}");
+          self.failed_sources.insert(source, error.clone());
+          self.load_errors.push(error.clone());
+          return Err(error);
+        }
+      }
+    }
+    Ok(&self.documents[&source])
+  }
+
+  /// The load and render failures since the last call.
+  pub fn take_load_errors(&mut self) -> Vec<PdfError> {
+    std::mem::take(&mut self.load_errors)
+  }
+
+  /// The source of the pdf page if it failed to load.
+  #[cfg(feature = "app")]
+  pub fn missing_source(&self, page: usize) -> Option<&PdfSource> {
+    match self.pages.pages().get(page)?.kind {
+      PageKind::Pdf { source, .. } => {
+        let source = self.pages.sources().get(source)?;
+        self.failed_sources.contains_key(source).then_some(source)
+      }
+      PageKind::Blank(_) => None,
+    }
+  }
+
+  /// Takes over the page list of the content.
+  /// Loads the sources it references and unloads the ones it does not anymore.
+  /// Sources that failed to load before are not tried again, their pages stay placeholders.
+  pub fn set_pages(&mut self, pages: &PageList) {
+    if *pages == self.pages {
+      return;
+    }
+    self.received_tiles.clear();
+    self.received_thumbnails.clear();
+    #[cfg(feature = "app")]
+    {
+      self.clear_search();
+      self.page_texts.clear();
+      self.requested_page_texts.clear();
+    }
+
+    self
+      .failed_sources
+      .retain(|source, _| pages.sources().contains(source));
+    for source in pages.sources() {
+      if !self.failed_sources.contains_key(source) {
+        // the error is kept for the ui
+        let _ = self.load_source(source.clone());
+      }
+    }
+    let unused: Vec<_> = self
+      .documents
+      .keys()
+      .filter(|source| !pages.sources().contains(source))
+      .cloned()
+      .collect();
+    for source in unused {
+      let document = self.documents.remove(&source).unwrap();
+      if let Some(worker) = &self.worker {
+        worker.unload(document.id);
+      }
+    }
+
+    self.pages = pages.clone();
+    self.pages_generation += 1;
+    self.outline = self.build_outline();
+  }
+
+  /// Renders the pages with the strokes and highlights of the content as vector graphics
+  /// into a new pdf.
+  /// Blocks until the pdf is written.
+  pub fn export(&mut self, content: ContentAccess) -> Result<Vec<u8>, PdfError> {
+    let pages = export::export_pages(self, content);
+    let worker = self.worker.get_or_insert_with(PdfWorker::spawn);
+    worker.export(pages).map_err(|error| {
+      tracing::error!("{error}");
+      error
+    })
+  }
+
+  /// Puts the image into a new pdf with a single page `size` large.
+  /// Blocks until the pdf is built.
+  #[cfg(feature = "app")]
   #[cfg(not(target_arch = "wasm32"))]
-  pub fn load_document(path: impl AsRef<Path>) -> Self {
-    let bindings = Pdfium::bind_to_library("/home/luis/dl/pdfium/lib/libpdfium.so").unwrap();
-    let pdfium = Pdfium::new(bindings);
+  pub fn image_pdf(
+    &mut self,
+    image: image::DynamicImage,
+    size: PdfPageInfo,
+  ) -> Result<Vec<u8>, PdfError> {
+    let worker = self.worker.get_or_insert_with(PdfWorker::spawn);
+    worker.image_pdf(image, size).map_err(|error| {
+      tracing::error!("{error}");
+      error
+    })
+  }
+
+  /// Renders the pdf page shown as `page` `width` pixels wide into rgba bytes.
+  /// `None` for blank pages and pages pdfium fails to render, which is kept for the ui.
+  /// Blocks until the page is rendered.
+  pub fn render_page(&mut self, page: usize, width: u16) -> Option<([usize; 2], Vec<u8>)> {
+    let (document, pdf_page) = self.pdf_page(page)?;
+    let worker = self.worker.get_or_insert_with(PdfWorker::spawn);
+    match worker.render_page(document, pdf_page, width) {
+      Ok(rendered) => rendered,
+      Err(reason) => {
+        self.report_render_error(document, pdf_page, reason);
+        None
+      }
+    }
+  }
+
+  /// Whether pdfium failed to render the pdf page shown as `page`.
+  #[cfg(feature = "app")]
+  pub fn is_render_failed(&self, page: usize) -> bool {
+    self
+      .pdf_page(page)
+      .map_or(false, |key| self.failed_renders.contains(&key))
+  }
+
+  #[cfg(feature = "app")]
+  pub fn pages_generation(&self) -> u64 {
+    self.pages_generation
+  }
+
+  pub fn npages(&self) -> usize {
+    self.pages.npages()
+  }
+
+  pub fn page_id(&self, page: usize) -> Option<PageId> {
+    self.pages.pages().get(page).map(|entry| entry.id)
+  }
+
+  pub fn page_index(&self, id: PageId) -> Option<usize> {
+    self.pages.pages().iter().position(|entry| entry.id == id)
+  }
+
+  /// The document and the page in it shown as `page`.
+  /// `None` for blank pages.
+  pub fn pdf_page(&self, page: usize) -> Option<(PdfDocumentId, usize)> {
+    let (document, page) = self.pdf_page_info(page)?;
+    Some((document.id, page))
+  }
+
+  /// The size of the page, `None` if it is out of range or its pdf is missing.
+  pub fn page_info(&self, page: usize) -> Option<PdfPageInfo> {
+    self.entry_info(&self.pages, self.pages.pages().get(page)?)
+  }
+
+  /// The outlines of all pdfs, with the pages of the page list.
+  /// With multiple pdfs each outline is nested below an item with the name of its pdf.
+  #[cfg(feature = "app")]
+  pub fn outline(&self) -> &[PdfOutlineItem] {
+    &self.outline
+  }
+
+  #[cfg(feature = "app")]
+  pub fn layout(&self) -> &PdfLayout {
+    &self.layout
+  }
+
+  pub fn set_layout(&mut self, layout: PdfLayout) {
+    self.layout = layout;
+  }
+
+  #[cfg(feature = "app")]
+  pub fn color_filter(&self) -> PdfColorFilter {
+    self.color_filter
+  }
+
+  #[cfg(feature = "app")]
+  pub fn set_color_filter(&mut self, color_filter: PdfColorFilter) {
+    self.color_filter = color_filter;
+  }
+
+  #[cfg(feature = "app")]
+  pub fn filters_ink(&self) -> bool {
+    self.filters_ink
+  }
+
+  #[cfg(feature = "app")]
+  pub fn set_filters_ink(&mut self, filters_ink: bool) {
+    self.filters_ink = filters_ink;
+  }
+
+  /// The canvas area of every page according to the layout.
+  /// Empty if there are no pages.
+  pub fn page_rects_canvas(&self) -> Vec<Rect> {
+    self.arranged_page_rects_canvas(&self.pages, &self.layout)
+  }
+
+  /// The canvas area of every page of another page list and layout, e.g. before changing them.
+  /// Pages of pdfs that are not loaded are A4.
+  pub fn arranged_page_rects_canvas(&self, pages: &PageList, layout: &PdfLayout) -> Vec<Rect> {
+    let page_ids: Vec<_> = pages.pages().iter().map(|entry| entry.id).collect();
+    let page_sizes_canvas: Vec<_> = pages
+      .pages()
+      .iter()
+      .map(|entry| {
+        self
+          .entry_info(pages, entry)
+          .unwrap_or(PdfPageInfo::A4)
+          .size_canvas()
+      })
+      .collect();
+    layout.page_rects_canvas(&page_ids, &page_sizes_canvas)
+  }
+
+  /// The target of the link under the point on the topmost page containing it.
+  /// Links to pages missing from the page list are ignored.
+  #[cfg(feature = "app")]
+  pub fn link_at(&self, point_canvas: na::Point2<f32>) -> Option<PdfLinkTarget> {
+    let page = self.page_at(point_canvas)?;
+    let (document, pdf_page) = self.pdf_page_info(page)?;
+    let page_rect_canvas = self.page_rects_canvas()[page];
+    let link = document.links[pdf_page].iter().find(|link| {
+      page_norm_to_canvas_rect(&page_rect_canvas, &link.rect_page_norm).contains_point(point_canvas)
+    })?;
+    match &link.target {
+      PdfLinkTarget::Page {
+        page,
+        position_page_norm,
+      } => Some(PdfLinkTarget::Page {
+        page: self.display_pages(document.id, *page).next()?,
+        position_page_norm: *position_page_norm,
+      }),
+      PdfLinkTarget::Uri(uri) => Some(PdfLinkTarget::Uri(uri.clone())),
+    }
+  }
+
+  /// The topmost page containing the point.
+  #[cfg(feature = "app")]
+  pub fn page_at(&self, point_canvas: na::Point2<f32>) -> Option<usize> {
+    self
+      .page_rects_canvas()
+      .iter()
+      .rposition(|rect| rect.contains_point(point_canvas))
+  }
+
+  /// Switches to the free layout, keeping the current positions of all other pages.
+  /// Only until the layout of the content is set again, which is what makes it permanent.
+  #[cfg(feature = "app")]
+  pub fn move_page(&mut self, page: usize, translation_canvas: na::Vector2<f32>) {
+    let mut page_centers_canvas = self.free_page_centers_canvas();
+    if let Some(center) = self
+      .page_id(page)
+      .and_then(|id| page_centers_canvas.get_mut(&id))
+    {
+      *center += translation_canvas;
+    }
+    self.layout = PdfLayout::Free {
+      page_centers_canvas,
+    };
+  }
+
+  /// The current positions of the pages, to keep them when switching to the free layout.
+  #[cfg(feature = "app")]
+  pub fn free_page_centers_canvas(&self) -> std::collections::BTreeMap<PageId, na::Point2<f32>> {
+    self
+      .pages
+      .pages()
+      .iter()
+      .zip(self.page_rects_canvas())
+      .map(|(entry, rect)| (entry.id, rect.center))
+      .collect()
+  }
+
+  /// Replaces all outstanding raster requests.
+  #[cfg(feature = "app")]
+  pub fn request_tiles(&self, requests: Vec<RasterRequest>) {
+    if let Some(worker) = &self.worker {
+      worker.rasterize(requests);
+    }
+  }
+
+  /// Queues thumbnails of the pages.
+  /// They arrive with the document and the page in it, blank pages have none.
+  #[cfg(feature = "app")]
+  pub fn request_thumbnails(&self, pages: Vec<usize>) {
+    let Some(worker) = &self.worker else {
+      return;
+    };
+    for page in pages {
+      if let Some((document, page)) = self.pdf_page(page) {
+        worker.render_thumbnails(document, vec![page]);
+      }
+    }
+  }
+
+  /// Starts searching all pdfs in the background.
+  #[cfg(feature = "app")]
+  pub fn search(&mut self, query: String) {
+    if let Some(worker) = &self.worker {
+      let documents = self.documents.values().map(|d| d.id).collect();
+      worker.search(documents, query.clone());
+      self.pending_search = Some(query);
+    }
+  }
+
+  #[cfg(feature = "app")]
+  pub fn is_searching(&self) -> bool {
+    self.pending_search.is_some()
+  }
+
+  #[cfg(feature = "app")]
+  pub fn clear_search(&mut self) {
+    self.pending_search = None;
+    self.search_results = None;
+    self.selected_search_hit = None;
+  }
+
+  #[cfg(feature = "app")]
+  pub fn search_results(&self) -> Option<&PdfSearchResults> {
+    self.search_results.as_ref()
+  }
+
+  #[cfg(feature = "app")]
+  pub fn selected_search_hit(&self) -> Option<usize> {
+    self.selected_search_hit
+  }
+
+  #[cfg(feature = "app")]
+  pub fn select_search_hit(&mut self, hit: Option<usize>) {
+    self.selected_search_hit = hit;
+  }
+
+  /// The text of the page with char positions.
+  /// `None` until the worker extracted it, which is started by the first call.
+  /// Blank pages have no text.
+  #[cfg(feature = "app")]
+  pub fn page_text(&mut self, page: usize) -> Option<&PageText> {
+    if let (Some(worker), Some((document, pdf_page))) = (&self.worker, self.pdf_page(page)) {
+      if self.requested_page_texts.insert(page) {
+        worker.extract_text(document, pdf_page);
+      }
+    }
+    self.page_texts.get(&page)
+  }
+
+  /// Tiles finished since the last call.
+  #[cfg(feature = "app")]
+  pub fn receive_tiles(&mut self) -> Vec<RasterResponse> {
+    self.poll_worker();
+    std::mem::take(&mut self.received_tiles)
+  }
 
-    PdfManagerBuilder {
-      pdfium,
-      document_builder: |pdfium| pdfium.load_pdf_from_file(&path, None).unwrap(),
-      pages_builder: |document| document.pages(),
-      page_vec_builder: |pages| pages.iter().collect(),
+  /// Thumbnails finished since the last call.
+  #[cfg(feature = "app")]
+  pub fn receive_thumbnails(&mut self) -> Vec<PdfThumbnail> {
+    self.poll_worker();
+    std::mem::take(&mut self.received_thumbnails)
+  }
+
+  /// sorts the worker responses, so each kind can be picked up by its consumer.
+  #[cfg(feature = "app")]
+  fn poll_worker(&mut self) {
+    let Some(worker) = &self.worker else {
+      return;
+    };
+    for response in worker.receive() {
+      match response {
+        WorkerResponse::Rasterized(tile) => self.received_tiles.push(tile),
+        WorkerResponse::Thumbnail(thumbnail) => self.received_thumbnails.push(thumbnail),
+        WorkerResponse::RenderFailed {
+          document,
+          page,
+          reason,
+        } => {
+          // tiles of the same page fail alike
+          if self.failed_renders.insert((document, page)) {
+            self.report_render_error(document, page, reason);
+          }
+        }
+        WorkerResponse::Searched { query, hits } => {
+          // older searches are superseded
+          if self.pending_search.as_ref() != Some(&query) {
+            continue;
+          }
+          // a pdf page can be shown more than once
+          let mut hits: Vec<_> = hits
+            .into_iter()
+            .flat_map(|(document, hit)| {
+              self
+                .display_pages(document, hit.page)
+                .map(move |page| hit.placed_on(page))
+            })
+            .collect();
+          hits.sort_by_key(|hit| hit.page);
+          self.pending_search = None;
+          self.search_results = Some(PdfSearchResults { query, hits });
+          self.selected_search_hit = None;
+        }
+        WorkerResponse::PageText {
+          document,
+          page,
+          text,
+        } => {
+          let pages: Vec<_> = self.display_pages(document, page).collect();
+          for page in pages {
+            self.page_texts.insert(page, text.clone());
+          }
+        }
+        WorkerResponse::Loaded { .. }
+        | WorkerResponse::Exported(_)
+        | WorkerResponse::RenderedPage(_) => {}
+        #[cfg(not(target_arch = "wasm32"))]
+        WorkerResponse::ImagePdf(_) => {}
+      }
+    }
+  }
+
+  fn report_render_error(&mut self, document: PdfDocumentId, page: usize, reason: String) {
+    let Some(source) = self
+      .documents
+      .iter()
+      .find_map(|(source, info)| (info.id == document).then(|| source.clone()))
+    else {
+      return;
+    };
+    let error = PdfError::Render {
+      source,
+      page,
+      reason,
+    };
+    tracing::error!("{error}");
+    self.load_errors.push(error);
+  }
+
+  /// the loaded document and the page in it shown as `page`
+  fn entry_info(&self, pages: &PageList, entry: &PageEntry) -> Option<PdfPageInfo> {
+    match entry.kind {
+      PageKind::Pdf { source, page } => {
+        let document = self.documents.get(pages.sources().get(source)?)?;
+        document.pages.get(page).copied()
+      }
+      PageKind::Blank(info) => Some(info),
+    }
+  }
+
+  fn pdf_page_info(&self, page: usize) -> Option<(&PdfDocumentInfo, usize)> {
+    match self.pages.pages().get(page)?.kind {
+      PageKind::Pdf { source, page } => {
+        let source = self.pages.sources().get(source)?;
+        Some((self.documents.get(source)?, page))
+      }
+      PageKind::Blank(_) => None,
     }
-    .build()
   }
 
-  #[cfg(target_arch = "wasm32")]
-  pub async fn load_document(url: String) -> Self {
-    let bindings = Pdfium::bind_to_system_library().unwrap();
-    let pdfium = Pdfium::new(bindings);
+  /// the pages of the page list showing the page of the document
+  fn display_pages(
+    &self,
+    document: PdfDocumentId,
+    pdf_page: usize,
+  ) -> impl Iterator<Item = usize> + '_ {
+    (0..self.npages()).filter(move |&page| self.pdf_page(page) == Some((document, pdf_page)))
+  }
+
+  fn build_outline(&self) -> Vec<PdfOutlineItem> {
+    let sources = self.pages.sources();
+    let is_nested = sources.len() > 1;
+    let mut outline = Vec::new();
+    for source in sources {
+      let Some(document) = self.documents.get(source) else {
+        continue;
+      };
+      let depth_offset = usize::from(is_nested);
+      if is_nested {
+        outline.push(PdfOutlineItem {
+          title: source_name(source),
+          page: (0..document.npages())
+            .find_map(|page| self.display_pages(document.id, page).next()),
+          depth: 0,
+        });
+      }
+      outline.extend(document.outline.iter().map(|item| {
+        PdfOutlineItem {
+          title: item.title.clone(),
+          page: item
+            .page
+            .and_then(|page| self.display_pages(document.id, page).next()),
+          depth: item.depth + depth_offset,
+        }
+      }));
+    }
+    outline
+  }
+}
 
-    PdfManagerBuilder {
-      pdfium,
-      document_builder: |pdfium| {
-        futures::executor::block_on(pdfium.load_pdf_from_fetch(url, None)).unwrap()
-      },
-      pages_builder: |document| document.pages(),
-      page_vec_builder: |pages| pages.iter().collect(),
+/// the file name of the pdf, to tell sources apart
+pub(crate) fn source_name(source: &PdfSource) -> String {
+  cfg_if::cfg_if! {
+    if #[cfg(target_arch = "wasm32")] {
+      source.rsplit('/').next().unwrap_or(source).to_owned()
+    } else {
+      source
+        .file_name()
+        .map_or_else(|| source.to_string_lossy(), |name| name.to_string_lossy())
+        .into_owned()
     }
-    .build()
   }
+}
+
+pub struct PdfDocumentInfo {
+  id: PdfDocumentId,
+  pages: Vec<PdfPageInfo>,
+  outline: Vec<PdfOutlineItem>,
+  /// the links of every page, looked up by the app
+  #[cfg_attr(not(feature = "app"), allow(dead_code))]
+  links: Vec<Vec<PdfLink>>,
+}
+
+impl PdfDocumentInfo {
+  pub fn npages(&self) -> usize {
+    self.pages.len()
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PdfPageInfo {
+  pub width_points: f32,
+  pub height_points: f32,
+}
+
+/// maps normalized page coordinates (origin upper left, page size 1x1) onto the page on the canvas.
+pub fn page_norm_to_canvas_point(
+  page_rect_canvas: &Rect,
+  point_page_norm: na::Point2<f32>,
+) -> na::Point2<f32> {
+  let page_min_canvas = page_rect_canvas.center - page_rect_canvas.extents_half;
+  page_min_canvas
+    + point_page_norm
+      .coords
+      .component_mul(&page_rect_canvas.size())
+}
+
+/// maps a point on the page on the canvas to normalized page coordinates.
+pub fn canvas_to_page_norm_point(
+  page_rect_canvas: &Rect,
+  point_canvas: na::Point2<f32>,
+) -> na::Point2<f32> {
+  let page_min_canvas = page_rect_canvas.center - page_rect_canvas.extents_half;
+  na::Point2::from((point_canvas - page_min_canvas).component_div(&page_rect_canvas.size()))
+}
+
+/// maps a rect in normalized page coordinates onto the page on the canvas.
+pub fn page_norm_to_canvas_rect(page_rect_canvas: &Rect, rect_page_norm: &Rect) -> Rect {
+  let min_page_norm = rect_page_norm.center - rect_page_norm.extents_half;
+  Rect::from_size_min(
+    rect_page_norm
+      .size()
+      .component_mul(&page_rect_canvas.size()),
+    page_norm_to_canvas_point(page_rect_canvas, min_page_norm),
+  )
+}
+
+impl PdfPageInfo {
+  pub const A4: Self = Self {
+    width_points: 595.0,
+    height_points: 842.0,
+  };
 
-  pub fn page_slice(&self) -> &[PdfPage] {
-    self.borrow_page_vec()
+  pub fn size_canvas(&self) -> na::Vector2<f32> {
+    na::vector![self.width_points, self.height_points].scale(CANVAS_UNITS_PER_PDF_POINT)
   }
 }
+
+/// A bookmark of the document outline.
+#[derive(Debug, Clone)]
+pub struct PdfOutlineItem {
+  pub title: String,
+  /// `None` if the bookmark does not point into this document
+  pub page: Option<usize>,
+  /// nesting level, top level bookmarks have depth 0
+  pub depth: usize,
+}
@@ -0,0 +1,224 @@
+use super::{canvas_to_page_norm_point, PdfDocumentId, PdfManager, PdfPageInfo};
+
+use crate::{content::access::ContentAccess, gfx::stroke, util};
+
+use pdfium_render::prelude::*;
+
+/// A page of the exported pdf together with the annotations flattened onto it.
+///
+/// Coordinates are in pdf points with the origin in the lower left corner of the page.
+#[derive(Debug, Clone)]
+pub struct ExportPage {
+  pub source: ExportPageSource,
+  pub size: PdfPageInfo,
+  pub strokes: Vec<ExportStroke>,
+  pub highlights: Vec<ExportHighlight>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum ExportPageSource {
+  /// copied from the page of a loaded document
+  Pdf {
+    document: PdfDocumentId,
+    page: usize,
+  },
+  /// a new empty page
+  Blank,
+}
+
+#[derive(Debug, Clone)]
+pub struct ExportStroke {
+  pub points: Vec<na::Point2<f32>>,
+  pub width_points: f32,
+  /// srgb with straight alpha
+  pub color: [u8; 4],
+}
+
+#[derive(Debug, Clone)]
+pub struct ExportHighlight {
+  /// `[min, max]` corners
+  pub rects: Vec<[na::Point2<f32>; 2]>,
+  /// srgb with straight alpha
+  pub color: [u8; 4],
+}
+
+/// Lays out the pages of the page list with everything drawn on them.
+///
+/// A stroke is put on every page it overlaps, the part outside of the page is clipped by the pdf.
+pub fn export_pages(pdf_manager: &PdfManager, content: ContentAccess) -> Vec<ExportPage> {
+  let page_rects_canvas = pdf_manager.page_rects_canvas();
+  let stroke_bounds: Vec<_> = content
+    .strokes()
+    .map(|(_, stroke)| (stroke, stroke.bounds_canvas()))
+    .collect();
+
+  page_rects_canvas
+    .iter()
+    .enumerate()
+    .map(|(ipage, page_rect_canvas)| {
+      let size = pdf_manager.page_info(ipage).unwrap_or(PdfPageInfo::A4);
+      let to_points = |point_canvas: na::Point2<f32>| {
+        let point_page_norm = canvas_to_page_norm_point(page_rect_canvas, point_canvas);
+        na::point![
+          point_page_norm.x * size.width_points,
+          (1.0 - point_page_norm.y) * size.height_points
+        ]
+      };
+      let points_per_canvas_unit = size.width_points / page_rect_canvas.size().x;
+
+      let source = match pdf_manager.pdf_page(ipage) {
+        Some((document, page)) => ExportPageSource::Pdf { document, page },
+        None => ExportPageSource::Blank,
+      };
+
+      let strokes = stroke_bounds
+        .iter()
+        .filter(|(_, bounds)| bounds.intersects(page_rect_canvas))
+        .map(|(stroke, _)| ExportStroke {
+          points: stroke.points().iter().copied().map(to_points).collect(),
+          width_points: stroke::stroke_width_canvas(stroke) * points_per_canvas_unit,
+          color: util::rgba_palette2srgb8(stroke.color()),
+        })
+        .collect();
+
+      let page_id = pdf_manager.page_id(ipage);
+      let highlights = content
+        .highlights()
+        .filter(|(_, highlight)| Some(highlight.page) == page_id)
+        .map(|(_, highlight)| ExportHighlight {
+          rects: highlight
+            .rects_page_norm
+            .iter()
+            .map(|rect| {
+              let (min, max) = (rect.min(), rect.max());
+              [
+                na::point![
+                  min.x * size.width_points,
+                  (1.0 - max.y) * size.height_points
+                ],
+                na::point![
+                  max.x * size.width_points,
+                  (1.0 - min.y) * size.height_points
+                ],
+              ]
+            })
+            .collect(),
+          color: util::rgba_palette2srgb8(highlight.color),
+        })
+        .collect();
+
+      ExportPage {
+        source,
+        size,
+        strokes,
+        highlights,
+      }
+    })
+    .collect()
+}
+
+/// A pdf of a single page `size` large, filled by the image.
+#[cfg(feature = "app")]
+#[cfg(not(target_arch = "wasm32"))]
+pub(super) fn build_image_pdf(
+  pdfium: &Pdfium,
+  image: &image::DynamicImage,
+  size: PdfPageInfo,
+) -> Result<Vec<u8>, PdfiumError> {
+  let document = pdfium.create_new_pdf()?;
+  let (width, height) = (
+    PdfPoints::new(size.width_points),
+    PdfPoints::new(size.height_points),
+  );
+  let mut page = document
+    .pages()
+    .create_page_at_end(PdfPagePaperSize::Custom(width, height))?;
+  let object = PdfPageImageObject::new_with_size(&document, image, width, height)?;
+  page.objects_mut().add_image_object(object)?;
+  document.save_to_bytes()
+}
+
+/// Builds the pdf on the worker, where the source documents are loaded.
+pub(super) fn build_pdf<'a>(
+  pdfium: &'a Pdfium,
+  sources: impl Fn(PdfDocumentId) -> Option<&'a PdfDocument<'a>>,
+  pages: &[ExportPage],
+) -> Result<Vec<u8>, PdfiumError> {
+  let document = pdfium.create_new_pdf()?;
+  for export_page in pages {
+    // pages of pdfs that went missing are exported blank
+    let source = match export_page.source {
+      ExportPageSource::Pdf { document, page } => sources(document).map(|source| (source, page)),
+      ExportPageSource::Blank => None,
+    };
+    let mut page = match source {
+      Some((source, page)) => {
+        let index = document.pages().len();
+        document
+          .pages()
+          .copy_page_from_document(source, page as u16, index)?;
+        document.pages().get(index)?
+      }
+      None => document
+        .pages()
+        .create_page_at_end(PdfPagePaperSize::Custom(
+          PdfPoints::new(export_page.size.width_points),
+          PdfPoints::new(export_page.size.height_points),
+        ))?,
+    };
+
+    // annotations are placed relative to the visible part of the page
+    let origin = page
+      .boundaries()
+      .crop()
+      .or_else(|_| page.boundaries().media())
+      .map(|boundary| na::point![boundary.bounds.left.value, boundary.bounds.bottom.value])
+      .unwrap_or_else(|_| na::Point2::origin());
+
+    page.set_content_regeneration_strategy(PdfPageContentRegenerationStrategy::Manual);
+    for highlight in &export_page.highlights {
+      for [min, max] in &highlight.rects {
+        let mut object = PdfPagePathObject::new_rect(
+          &document,
+          PdfRect {
+            bottom: PdfPoints::new(origin.y + min.y),
+            left: PdfPoints::new(origin.x + min.x),
+            top: PdfPoints::new(origin.y + max.y),
+            right: PdfPoints::new(origin.x + max.x),
+          },
+          None,
+          None,
+          Some(pdf_color(highlight.color)),
+        )?;
+        object.set_blend_mode(PdfPageObjectBlendMode::Multiply)?;
+        page.objects_mut().add_path_object(object)?;
+      }
+    }
+    for stroke in &export_page.strokes {
+      let (first, rest) = stroke.points.split_first().expect("strokes have points");
+      let mut object = PdfPagePathObject::new(
+        &document,
+        PdfPoints::new(origin.x + first.x),
+        PdfPoints::new(origin.y + first.y),
+        Some(pdf_color(stroke.color)),
+        Some(PdfPoints::new(stroke.width_points)),
+        None,
+      )?;
+      for point in rest {
+        object.line_to(
+          PdfPoints::new(origin.x + point.x),
+          PdfPoints::new(origin.y + point.y),
+        )?;
+      }
+      object.set_line_cap(PdfPageObjectLineCap::Round)?;
+      object.set_line_join(PdfPageObjectLineJoin::Round)?;
+      page.objects_mut().add_path_object(object)?;
+    }
+    page.regenerate_content()?;
+  }
+  document.save_to_bytes()
+}
+
+fn pdf_color([red, green, blue, alpha]: [u8; 4]) -> PdfColor {
+  PdfColor::new(red, green, blue, alpha)
+}
@@ -0,0 +1,56 @@
+/// sepia is grayscale tinted with this color
+const SEPIA_TINT: (f32, f32, f32) = (1.0, 0.82, 0.6);
+
+/// How the colors of the pdf pages are changed, e.g. for reading at night.
+/// Applied in the shader, so switching filters does not rasterize the pages again.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum PdfColorFilter {
+  #[default]
+  None,
+  /// light pages with dark text, keeping the hue of colors
+  Dark,
+  Sepia,
+  Grayscale,
+  /// the color of the paper, text stays dark
+  Tint(palette::LinSrgb),
+}
+
+impl PdfColorFilter {
+  pub fn name(&self) -> &'static str {
+    match self {
+      Self::None => "None",
+      Self::Dark => "Dark",
+      Self::Sepia => "Sepia",
+      Self::Grayscale => "Grayscale",
+      Self::Tint(_) => "Paper tint",
+    }
+  }
+
+  pub fn inverts_lightness(&self) -> bool {
+    matches!(self, Self::Dark)
+  }
+
+  pub fn is_grayscale(&self) -> bool {
+    matches!(self, Self::Sepia | Self::Grayscale)
+  }
+
+  /// multiplied with the color after the other steps
+  pub fn tint(&self) -> palette::LinSrgb {
+    match self {
+      Self::Sepia => {
+        let (r, g, b) = SEPIA_TINT;
+        palette::LinSrgb::new(r, g, b)
+      }
+      Self::Tint(tint) => *tint,
+      _ => palette::LinSrgb::new(1.0, 1.0, 1.0),
+    }
+  }
+
+  /// what white becomes, for the background around the pages
+  pub fn paper_color(&self) -> palette::LinSrgb {
+    match self.inverts_lightness() {
+      true => palette::LinSrgb::new(0.0, 0.0, 0.0),
+      false => self.tint(),
+    }
+  }
+}
@@ -0,0 +1,28 @@
+use super::{page_norm_to_canvas_rect, PageId};
+
+use crate::math::Rect;
+
+use serde::{Deserialize, Serialize};
+
+/// A highlighted range of text on a page.
+/// Anchored to the page, so it follows the page when the layout or the page list changes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PdfHighlight {
+  pub page: PageId,
+  /// one rect per line of the highlighted text.
+  /// in page coordinates normalized to [0,1]x[0,1] with the origin in the upper left corner.
+  pub rects_page_norm: Vec<Rect>,
+  pub text: String,
+  #[serde(with = "crate::util::serde_color")]
+  pub color: palette::LinSrgba,
+}
+
+impl PdfHighlight {
+  pub fn rects_canvas(&self, page_rect_canvas: &Rect) -> Vec<Rect> {
+    self
+      .rects_page_norm
+      .iter()
+      .map(|rect| page_norm_to_canvas_rect(page_rect_canvas, rect))
+      .collect()
+  }
+}
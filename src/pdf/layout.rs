@@ -0,0 +1,150 @@
+use super::PageId;
+
+use crate::math::Rect;
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// gap between pages relative to the largest page height
+const GAP_FACTOR: f32 = 0.05;
+
+/// How the pdf pages are arranged on the canvas.
+/// In all layouts except `Free` the first page is centered at the canvas origin.
+///
+/// Part of the content, so changing it is undoable and moves the strokes on the pages along.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub enum PdfLayout {
+  /// pages stacked top to bottom
+  #[default]
+  Vertical,
+  /// pages next to each other left to right
+  Horizontal,
+  /// two pages side by side, like an opened book.
+  /// with `cover` the first page is on its own on the right side.
+  Spread { cover: bool },
+  /// rows of `columns` pages in equally sized cells
+  Grid { columns: u32 },
+  /// every page has its own position
+  Free {
+    page_centers_canvas: BTreeMap<PageId, na::Point2<f32>>,
+  },
+}
+
+impl PdfLayout {
+  pub fn name(&self) -> &'static str {
+    match self {
+      Self::Vertical => "Vertical",
+      Self::Horizontal => "Horizontal",
+      Self::Spread { .. } => "Spread",
+      Self::Grid { .. } => "Grid",
+      Self::Free { .. } => "Free",
+    }
+  }
+
+  /// The rects of the pages with these ids and sizes, in the order given.
+  pub fn page_rects_canvas(
+    &self,
+    page_ids: &[PageId],
+    page_sizes_canvas: &[na::Vector2<f32>],
+  ) -> Vec<Rect> {
+    let npages = page_sizes_canvas.len();
+    let max_size = page_sizes_canvas
+      .iter()
+      .fold(na::Vector2::zeros(), |a: na::Vector2<f32>, b| a.sup(b));
+    let gap = GAP_FACTOR * max_size.y;
+
+    let centers = match self {
+      Self::Vertical => rows((0..npages).map(|i| vec![i]), page_sizes_canvas, gap),
+      Self::Horizontal => rows(
+        std::iter::once((0..npages).collect()),
+        page_sizes_canvas,
+        gap,
+      ),
+      Self::Spread { cover } => spread(*cover, page_sizes_canvas, gap),
+      Self::Grid { columns } => {
+        let columns = (*columns).max(1) as usize;
+        let pitch = max_size.add_scalar(gap);
+        (0..npages)
+          .map(|i| {
+            let cell = na::vector![(i % columns) as f32, (i / columns) as f32];
+            na::Point2::from(cell.component_mul(&pitch))
+          })
+          .collect()
+      }
+      Self::Free {
+        page_centers_canvas,
+      } => {
+        // pages without a stored position (e.g. inserted ones) fall back to `Vertical`
+        let fallback = Self::Vertical.page_rects_canvas(page_ids, page_sizes_canvas);
+        return fallback
+          .into_iter()
+          .zip(page_ids)
+          .map(|(rect, id)| match page_centers_canvas.get(id) {
+            Some(center) => Rect::from_size_center(rect.size(), *center),
+            None => rect,
+          })
+          .collect();
+      }
+    };
+
+    let offset = centers.first().map(|c| c.coords).unwrap_or_default();
+    centers
+      .into_iter()
+      .zip(page_sizes_canvas)
+      .map(|(center, size)| Rect::from_size_center(*size, center - offset))
+      .collect()
+  }
+}
+
+/// centers of pages arranged in rows, each row centered horizontally.
+fn rows(
+  rows: impl Iterator<Item = Vec<usize>>,
+  page_sizes_canvas: &[na::Vector2<f32>],
+  gap: f32,
+) -> Vec<na::Point2<f32>> {
+  let mut centers = vec![na::Point2::origin(); page_sizes_canvas.len()];
+  let mut row_top = 0.0;
+  for row in rows {
+    let sizes = row.iter().map(|&i| page_sizes_canvas[i]);
+    let row_height = sizes.clone().map(|s| s.y).fold(0.0, f32::max);
+    let row_width = sizes.map(|s| s.x).sum::<f32>() + gap * row.len().saturating_sub(1) as f32;
+
+    let mut left = -row_width / 2.0;
+    for &i in &row {
+      let size = page_sizes_canvas[i];
+      centers[i] = na::point![left + size.x / 2.0, row_top + row_height / 2.0];
+      left += size.x + gap;
+    }
+    row_top += row_height + gap;
+  }
+  centers
+}
+
+/// centers of pages arranged in pairs meeting at the vertical canvas axis.
+fn spread(cover: bool, page_sizes_canvas: &[na::Vector2<f32>], gap: f32) -> Vec<na::Point2<f32>> {
+  let npages = page_sizes_canvas.len();
+  let mut centers = vec![na::Point2::origin(); npages];
+
+  // the page index of the left page in each row, possibly one before the first page
+  let first_left = if cover { -1 } else { 0 };
+  let mut row_top = 0.0;
+  for left in (first_left..npages as isize).step_by(2) {
+    let pair = [left, left + 1].map(|i| usize::try_from(i).ok().filter(|&i| i < npages));
+    let row_height = pair
+      .iter()
+      .flatten()
+      .map(|&i| page_sizes_canvas[i].y)
+      .fold(0.0, f32::max);
+
+    if let Some(i) = pair[0] {
+      let size = page_sizes_canvas[i];
+      centers[i] = na::point![-gap / 2.0 - size.x / 2.0, row_top + row_height / 2.0];
+    }
+    if let Some(i) = pair[1] {
+      let size = page_sizes_canvas[i];
+      centers[i] = na::point![gap / 2.0 + size.x / 2.0, row_top + row_height / 2.0];
+    }
+    row_top += row_height + gap;
+  }
+  centers
+}
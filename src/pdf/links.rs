@@ -0,0 +1,134 @@
+use super::PdfPageInfo;
+
+use crate::math::Rect;
+
+use pdfium_render::prelude::*;
+use std::os::raw::c_int;
+
+// action types from `fpdf_doc.h`
+const PDFACTION_GOTO: u32 = 1;
+const PDFACTION_URI: u32 = 3;
+
+/// A link annotation of a page.
+#[derive(Debug, Clone)]
+pub struct PdfLink {
+  /// in page coordinates normalized to [0,1]x[0,1] with the origin in the upper left corner
+  pub rect_page_norm: Rect,
+  pub target: PdfLinkTarget,
+}
+
+#[derive(Debug, Clone)]
+pub enum PdfLinkTarget {
+  /// a position in this document
+  Page {
+    page: usize,
+    /// normalized like `PdfLink::rect_page_norm`.
+    /// `None` if the destination only names the page.
+    position_page_norm: Option<na::Point2<f32>>,
+  },
+  /// an external url
+  Uri(String),
+}
+
+/// the links of `page` with a target that can be followed.
+///
+/// pdfium-render does not expose the rect and the destination of a link,
+/// so the links are read with the raw bindings.
+pub fn read_links(document: &PdfDocument, page: &PdfPage, pages: &[PdfPageInfo]) -> Vec<PdfLink> {
+  let bindings = document.bindings();
+  let document_handle = bindings.get_handle_from_document(document);
+  let page_handle = bindings.get_handle_from_page(page);
+  let norm = |x: f32, y: f32, page: &PdfPageInfo| {
+    na::point![x / page.width_points, 1.0 - y / page.height_points]
+  };
+  let page_info = PdfPageInfo {
+    width_points: page.width().value,
+    height_points: page.height().value,
+  };
+
+  let mut links = Vec::new();
+  let mut start_pos: c_int = 0;
+  let mut link = std::ptr::null_mut();
+  while bindings.FPDFLink_Enumerate(page_handle, &mut start_pos, &mut link) != 0 {
+    // `FS_RECTF` is not exported, so let inference name it. all zero is a valid `FS_RECTF`.
+    let mut rect = unsafe { std::mem::zeroed() };
+    if bindings.FPDFLink_GetAnnotRect(link, &mut rect) == 0 {
+      continue;
+    }
+    let min = norm(
+      rect.left.min(rect.right),
+      rect.top.max(rect.bottom),
+      &page_info,
+    );
+    let max = norm(
+      rect.left.max(rect.right),
+      rect.top.min(rect.bottom),
+      &page_info,
+    );
+    let rect_page_norm = Rect::from_size_min(max - min, min);
+
+    let mut destination = bindings.FPDFLink_GetDest(document_handle, link);
+    let mut uri = None;
+    let action = bindings.FPDFLink_GetAction(link);
+    if destination.is_null() && !action.is_null() {
+      match bindings.FPDFAction_GetType(action) as u32 {
+        PDFACTION_GOTO => destination = bindings.FPDFAction_GetDest(document_handle, action),
+        PDFACTION_URI => {
+          let length =
+            bindings.FPDFAction_GetURIPath(document_handle, action, std::ptr::null_mut(), 0);
+          let mut buffer = vec![0u8; length as usize];
+          bindings.FPDFAction_GetURIPath(
+            document_handle,
+            action,
+            buffer.as_mut_ptr().cast(),
+            length,
+          );
+          // the length includes the nul terminator
+          buffer.pop();
+          uri = Some(String::from_utf8_lossy(&buffer).into_owned());
+        }
+        _ => {}
+      }
+    }
+
+    let target = if let Some(uri) = uri {
+      PdfLinkTarget::Uri(uri)
+    } else if !destination.is_null() {
+      let page = bindings.FPDFDest_GetDestPageIndex(document_handle, destination);
+      let Some(target_page_info) = usize::try_from(page).ok().and_then(|page| pages.get(page)) else {
+        continue;
+      };
+      let (mut has_x, mut has_y, mut has_zoom) = (0, 0, 0);
+      let (mut x, mut y, mut zoom) = (0.0, 0.0, 0.0);
+      bindings.FPDFDest_GetLocationInPage(
+        destination,
+        &mut has_x,
+        &mut has_y,
+        &mut has_zoom,
+        &mut x,
+        &mut y,
+        &mut zoom,
+      );
+      // a missing coordinate keeps the left or top edge in view
+      if has_x == 0 {
+        x = 0.0;
+      }
+      if has_y == 0 {
+        y = target_page_info.height_points;
+      }
+      let position_page_norm = (has_x != 0 || has_y != 0).then(|| norm(x, y, target_page_info));
+      PdfLinkTarget::Page {
+        page: page as usize,
+        position_page_norm,
+      }
+    } else {
+      continue;
+    };
+
+    links.push(PdfLink {
+      rect_page_norm,
+      target,
+    });
+  }
+  links
+}
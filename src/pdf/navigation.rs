@@ -0,0 +1,153 @@
+//! Jumping between the pages of the pdf.
+
+use super::{page_norm_to_canvas_point, PdfLinkTarget, PdfManager, PdfSearchHit};
+
+use crate::{
+  input::InputManager,
+  math::Rect,
+  spaces::{Camera, SpaceManager},
+};
+
+use winit::event::VirtualKeyCode;
+
+pub fn update(pdf_manager: &PdfManager, spaces: &mut SpaceManager, input: &InputManager) {
+  let Some(current) = current_page(pdf_manager, spaces) else {
+    return;
+  };
+  if input.got_pressed(VirtualKeyCode::PageDown) {
+    go_to_page(pdf_manager, spaces, current + 1);
+  }
+  if input.got_pressed(VirtualKeyCode::PageUp) {
+    go_to_page(pdf_manager, spaces, current.saturating_sub(1));
+  }
+}
+
+/// The page under the screen center or else the one closest to it.
+pub fn current_page(pdf_manager: &PdfManager, spaces: &SpaceManager) -> Option<usize> {
+  // the camera looks at the screen center
+  let screen_center_canvas = spaces.camera().position_canvas;
+  pdf_manager
+    .page_at(screen_center_canvas)
+    .or_else(|| closest_page(&pdf_manager.page_rects_canvas(), screen_center_canvas))
+}
+
+/// the page whose center is closest to the point
+fn closest_page(page_rects_canvas: &[Rect], point_canvas: na::Point2<f32>) -> Option<usize> {
+  page_rects_canvas
+    .iter()
+    .map(|rect| (rect.center - point_canvas).magnitude_squared())
+    .enumerate()
+    .min_by(|(_, a), (_, b)| a.total_cmp(b))
+    .map(|(ipage, _)| ipage)
+}
+
+/// Animates the camera to fit the page on the screen.
+/// Out of range pages are clamped to the last page.
+pub fn go_to_page(pdf_manager: &PdfManager, spaces: &mut SpaceManager, page: usize) {
+  let Some(page_rect_canvas) = clamped_page_rect(&pdf_manager.page_rects_canvas(), page) else {
+    return;
+  };
+  let target = spaces.camera_fitting_rect(&page_rect_canvas);
+  spaces.animate_camera(target);
+}
+
+fn clamped_page_rect(page_rects_canvas: &[Rect], page: usize) -> Option<Rect> {
+  page_rects_canvas
+    .get(page)
+    .or(page_rects_canvas.last())
+    .copied()
+}
+
+/// Selects the search hit and animates the camera to center it, keeping the zoom.
+pub fn go_to_search_hit(pdf_manager: &mut PdfManager, spaces: &mut SpaceManager, hit: usize) {
+  let page_rects_canvas = pdf_manager.page_rects_canvas();
+  let Some(hit_rect_canvas) = pdf_manager
+    .search_results()
+    .and_then(|results| results.hits.get(hit))
+    .and_then(|hit| first_hit_rect(&page_rects_canvas, hit))
+  else {
+    return;
+  };
+  pdf_manager.select_search_hit(Some(hit));
+  let target = Camera {
+    position_canvas: hit_rect_canvas.center,
+    ..spaces.camera().clone()
+  };
+  spaces.animate_camera(target);
+}
+
+/// the first line of the hit on the canvas
+fn first_hit_rect(page_rects_canvas: &[Rect], hit: &PdfSearchHit) -> Option<Rect> {
+  let page_rect_canvas = page_rects_canvas.get(hit.page)?;
+  hit.rects_canvas(page_rect_canvas).first().copied()
+}
+
+/// Moves the camera to the target of an internal link, keeping the zoom if the link names a position.
+/// External links are left to the caller.
+pub fn follow_link(pdf_manager: &PdfManager, spaces: &mut SpaceManager, target: &PdfLinkTarget) {
+  let PdfLinkTarget::Page {
+    page,
+    position_page_norm,
+  } = target
+  else {
+    return;
+  };
+  let Some(position_page_norm) = position_page_norm else {
+    go_to_page(pdf_manager, spaces, *page);
+    return;
+  };
+  let Some(page_rect_canvas) = pdf_manager.page_rects_canvas().get(*page).copied() else {
+    return;
+  };
+  let target = Camera {
+    position_canvas: page_norm_to_canvas_point(&page_rect_canvas, *position_page_norm),
+    ..spaces.camera().clone()
+  };
+  spaces.animate_camera(target);
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{clamped_page_rect, closest_page, first_hit_rect};
+  use crate::{math::Rect, pdf::PdfSearchHit};
+
+  fn rect(size: [f32; 2], center: [f32; 2]) -> Rect {
+    Rect::from_size_center(size.into(), center.into())
+  }
+
+  /// two pages of 2×2 below each other with a gap
+  fn pages() -> [Rect; 2] {
+    [rect([2.0, 2.0], [0.0, 0.0]), rect([2.0, 2.0], [0.0, 3.0])]
+  }
+
+  #[test]
+  fn finds_the_closest_page() {
+    assert_eq!(closest_page(&pages(), na::point![5.0, -1.0]), Some(0));
+    // in the gap, closer to the second page
+    assert_eq!(closest_page(&pages(), na::point![0.0, 1.6]), Some(1));
+    assert_eq!(closest_page(&[], na::point![0.0, 0.0]), None);
+  }
+
+  #[test]
+  fn clamps_pages_to_the_last() {
+    assert_eq!(clamped_page_rect(&pages(), 0), Some(pages()[0]));
+    assert_eq!(clamped_page_rect(&pages(), 7), Some(pages()[1]));
+    assert_eq!(clamped_page_rect(&[], 0), None);
+  }
+
+  #[test]
+  fn goes_to_the_first_line_of_hits() {
+    let hit = PdfSearchHit {
+      page: 1,
+      rects_page_norm: vec![
+        Rect::from_size_min(na::vector![0.5, 0.25], na::point![0.0, 0.0]),
+        Rect::from_size_min(na::vector![0.5, 0.25], na::point![0.0, 0.5]),
+      ],
+      context: String::new(),
+    };
+    let expected = Rect::from_size_min(na::vector![1.0, 0.5], na::point![-1.0, 2.0]);
+    assert_eq!(first_hit_rect(&pages(), &hit), Some(expected));
+    // pages removed since the search
+    assert_eq!(first_hit_rect(&pages()[..1], &hit), None);
+  }
+}
@@ -0,0 +1,152 @@
+use super::{worker::PdfSource, PdfPageInfo};
+
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+
+/// The pages of the document in display order.
+/// Pages are taken from any number of pdfs or are blank note pages.
+///
+/// Part of the content, so edits are undoable and saved with the project.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PageList {
+  sources: Vec<PdfSource>,
+  pages: Vec<PageEntry>,
+  /// never reset, so ids of removed pages are not handed out again
+  next_id: u64,
+}
+
+/// identifies a page independent of its position in the list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct PageId(u64);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PageEntry {
+  pub id: PageId,
+  pub kind: PageKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum PageKind {
+  /// page `page` of the pdf `PageList::sources()[source]`
+  Pdf {
+    source: usize,
+    page: usize,
+  },
+  Blank(PdfPageInfo),
+}
+
+impl PageList {
+  pub fn sources(&self) -> &[PdfSource] {
+    &self.sources
+  }
+
+  pub fn pages(&self) -> &[PageEntry] {
+    &self.pages
+  }
+
+  pub fn npages(&self) -> usize {
+    self.pages.len()
+  }
+
+  /// Inserts the pages `pdf_pages` of the pdf before `index`.
+  pub fn insert_pdf(&mut self, index: usize, source: PdfSource, pdf_pages: Range<usize>) {
+    let source = match self.sources.iter().position(|s| *s == source) {
+      Some(source) => source,
+      None => {
+        self.sources.push(source);
+        self.sources.len() - 1
+      }
+    };
+    let entries: Vec<_> = pdf_pages
+      .map(|page| self.new_entry(PageKind::Pdf { source, page }))
+      .collect();
+    let index = index.min(self.pages.len());
+    self.pages.splice(index..index, entries);
+  }
+
+  pub fn insert_blank(&mut self, index: usize, info: PdfPageInfo) {
+    let entry = self.new_entry(PageKind::Blank(info));
+    let index = index.min(self.pages.len());
+    self.pages.insert(index, entry);
+  }
+
+  pub fn remove(&mut self, index: usize) {
+    if index < self.pages.len() {
+      self.pages.remove(index);
+    }
+    self.remove_unused_sources();
+  }
+
+  /// Moves the page at `from` so it ends up at `to`.
+  pub fn reorder_page(&mut self, from: usize, to: usize) {
+    if from < self.pages.len() && to < self.pages.len() {
+      let entry = self.pages.remove(from);
+      self.pages.insert(to, entry);
+    }
+  }
+
+  /// Replaces the sources `rename` returns a new one for.
+  #[cfg(not(target_arch = "wasm32"))]
+  pub fn rename_sources(&mut self, rename: &impl Fn(&PdfSource) -> Option<PdfSource>) {
+    for source in &mut self.sources {
+      if let Some(renamed) = rename(source) {
+        *source = renamed;
+      }
+    }
+  }
+
+  /// Makes the pages of the pdfs, each with its number of pages, the pages of the list.
+  /// The pages keep their ids by position, so what is on a page stays on the new one there.
+  /// Pages past the end of the pdfs become blank pages of size `keep(index)`,
+  /// or are removed if that is `None`.
+  #[cfg(not(target_arch = "wasm32"))]
+  pub fn replace_pdfs(
+    &mut self,
+    pdfs: Vec<(PdfSource, usize)>,
+    keep: impl Fn(usize) -> Option<PdfPageInfo>,
+  ) {
+    let old_pages = std::mem::take(&mut self.pages);
+    self.sources.clear();
+    for (source, npages) in pdfs {
+      self.insert_pdf(self.pages.len(), source, 0..npages);
+    }
+    for (entry, old_entry) in self.pages.iter_mut().zip(&old_pages) {
+      entry.id = old_entry.id;
+    }
+    let surplus = old_pages.into_iter().enumerate().skip(self.pages.len());
+    self.pages.extend(surplus.filter_map(|(index, old_entry)| {
+      Some(PageEntry {
+        id: old_entry.id,
+        kind: PageKind::Blank(keep(index)?),
+      })
+    }));
+  }
+
+  fn new_entry(&mut self, kind: PageKind) -> PageEntry {
+    let id = PageId(self.next_id);
+    self.next_id += 1;
+    PageEntry { id, kind }
+  }
+
+  fn remove_unused_sources(&mut self) {
+    let mut isource = 0;
+    while isource < self.sources.len() {
+      let is_used = self
+        .pages
+        .iter()
+        .any(|entry| matches!(entry.kind, PageKind::Pdf { source, .. } if source == isource));
+      if is_used {
+        isource += 1;
+        continue;
+      }
+      self.sources.remove(isource);
+      for entry in &mut self.pages {
+        if let PageKind::Pdf { source, .. } = &mut entry.kind {
+          if *source > isource {
+            *source -= 1;
+          }
+        }
+      }
+    }
+  }
+}
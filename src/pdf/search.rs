@@ -0,0 +1,162 @@
+#[cfg(feature = "app")]
+use super::page_norm_to_canvas_rect;
+use super::text::{fold, PageText};
+
+use crate::math::Rect;
+
+/// number of chars shown around a hit in the result list
+const CONTEXT_NCHARS: usize = 24;
+
+pub struct PdfSearchResults {
+  pub query: String,
+  pub hits: Vec<PdfSearchHit>,
+}
+
+#[derive(Clone)]
+pub struct PdfSearchHit {
+  /// the page in the page list
+  pub page: usize,
+  /// one rect per line of the hit.
+  /// in page coordinates normalized to [0,1]x[0,1] with the origin in the upper left corner.
+  pub rects_page_norm: Vec<Rect>,
+  /// the hit with some surrounding text
+  pub context: String,
+}
+
+#[cfg(feature = "app")]
+impl PdfSearchHit {
+  pub fn rects_canvas(&self, page_rect_canvas: &Rect) -> Vec<Rect> {
+    self
+      .rects_page_norm
+      .iter()
+      .map(|rect| page_norm_to_canvas_rect(page_rect_canvas, rect))
+      .collect()
+  }
+}
+
+/// a hit in one document, before it is placed in the page list
+#[derive(Clone)]
+pub struct DocumentSearchHit {
+  /// the page in the document
+  pub page: usize,
+  pub rects_page_norm: Vec<Rect>,
+  pub context: String,
+}
+
+impl DocumentSearchHit {
+  /// the hit on a page of the page list showing its document page
+  pub fn placed_on(&self, page: usize) -> PdfSearchHit {
+    PdfSearchHit {
+      page,
+      rects_page_norm: self.rects_page_norm.clone(),
+      context: self.context.clone(),
+    }
+  }
+}
+
+/// case insensitive search through all pages of a document.
+/// whitespace in the query matches any whitespace in the text.
+pub fn search(pages: &[PageText], query: &str) -> Vec<DocumentSearchHit> {
+  let mut needle: Vec<char> = Vec::new();
+  for c in query.trim().chars().map(fold) {
+    if c != ' ' || needle.last() != Some(&' ') {
+      needle.push(c);
+    }
+  }
+  if needle.is_empty() {
+    return Vec::new();
+  }
+
+  let mut hits = Vec::new();
+  for (ipage, page) in pages.iter().enumerate() {
+    let mut start = 0;
+    let folded = page.folded();
+    while start + needle.len() <= folded.len() {
+      let end = start + needle.len();
+      if folded[start..end] != needle[..] {
+        start += 1;
+        continue;
+      }
+
+      let context_start = start.saturating_sub(CONTEXT_NCHARS);
+      let context_end = (end + CONTEXT_NCHARS).min(page.nchars());
+      hits.push(DocumentSearchHit {
+        page: ipage,
+        rects_page_norm: page.rects(start..end),
+        context: page.text(context_start..context_end),
+      });
+      start = end;
+    }
+  }
+  hits
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{search, PdfSearchHit, CONTEXT_NCHARS};
+  use crate::{math::Rect, pdf::text::PageText};
+
+  #[test]
+  fn finds_hits_on_every_page() {
+    let pages = [
+      PageText::from_lines(&["nothing here"]),
+      PageText::from_lines(&["The Query", "and the query"]),
+    ];
+    let hits = search(&pages, "query");
+    let found: Vec<_> = hits
+      .iter()
+      .map(|hit| (hit.page, hit.context.as_str()))
+      .collect();
+    assert_eq!(
+      found,
+      [
+        (1, "The Query and the query"),
+        (1, "The Query and the query")
+      ]
+    );
+  }
+
+  #[test]
+  fn matches_any_whitespace() {
+    let pages = [PageText::from_lines(&["over the", "line break"])];
+    let hits = search(&pages, "  THE \t  LINE ");
+    assert_eq!(hits.len(), 1);
+    // one rect per line
+    assert_eq!(hits[0].rects_page_norm.len(), 2);
+    assert!(search(&pages, " \n ").is_empty());
+  }
+
+  #[test]
+  fn hits_do_not_overlap() {
+    let pages = [PageText::from_lines(&["aaaaa"])];
+    assert_eq!(search(&pages, "aa").len(), 2);
+  }
+
+  #[test]
+  fn cuts_the_context() {
+    let line = format!("{}needle{}", "a".repeat(40), "b".repeat(40));
+    let pages = [PageText::from_lines(&[&line])];
+    let hits = search(&pages, "needle");
+    let context = format!(
+      "{}needle{}",
+      "a".repeat(CONTEXT_NCHARS),
+      "b".repeat(CONTEXT_NCHARS)
+    );
+    assert_eq!(hits[0].context, context);
+  }
+
+  #[test]
+  fn places_hit_rects_on_the_page() {
+    let hit = PdfSearchHit {
+      page: 0,
+      rects_page_norm: vec![Rect::from_size_min(
+        na::vector![0.5, 0.25],
+        na::point![0.5, 0.25],
+      )],
+      context: String::new(),
+    };
+    let page_rect_canvas = Rect::from_size_center(na::vector![2.0, 4.0], na::point![10.0, 0.0]);
+    let expected = Rect::from_size_min(na::vector![1.0, 1.0], na::point![10.0, -1.0]);
+    assert_eq!(hit.rects_canvas(&page_rect_canvas), [expected]);
+  }
+}
@@ -0,0 +1,229 @@
+use crate::math::Rect;
+
+use pdfium_render::prelude::*;
+use std::ops::Range;
+
+/// normalized bounds of a char as `(min, max)`
+type CharBounds = (na::Point2<f32>, na::Point2<f32>);
+
+/// The text of a page with the position of every char.
+/// Runs of whitespace are collapsed into a single space, so text can be matched across line breaks.
+///
+/// Positions are in page coordinates normalized to [0,1]x[0,1] with the origin in the upper left corner.
+#[derive(Debug, Clone, Default)]
+pub struct PageText {
+  chars: Vec<char>,
+  /// lowercase version of `chars` for case insensitive matching
+  folded: Vec<char>,
+  /// `None` for chars pdfium made up (e.g. line breaks)
+  bounds: Vec<Option<CharBounds>>,
+}
+
+impl PageText {
+  pub fn new(page: &PdfPage) -> Self {
+    let mut this = Self::default();
+    let Ok(text) = page.text() else {
+      return this;
+    };
+
+    let page_size = na::vector![page.width().value, page.height().value];
+    for text_char in text.chars().iter() {
+      let Some(c) = text_char.unicode_char() else {
+        continue;
+      };
+      let bounds = text_char.loose_bounds().ok().and_then(|rect| {
+        let min = na::point![rect.left.value, page_size.y - rect.top.value];
+        let max = na::point![rect.right.value, page_size.y - rect.bottom.value];
+        let is_empty = max.x <= min.x || max.y <= min.y;
+        (!is_empty).then(|| {
+          let norm = |p: na::Point2<f32>| na::Point2::from(p.coords.component_div(&page_size));
+          (norm(min), norm(max))
+        })
+      });
+
+      this.push(c, bounds);
+    }
+    this
+  }
+
+  /// lines of chars 0.01 wide and 0.05 high, 0.1 apart, joined by line breaks without bounds
+  #[cfg(test)]
+  pub fn from_lines(lines: &[&str]) -> Self {
+    let mut this = Self::default();
+    for (iline, line) in lines.iter().enumerate() {
+      if iline > 0 {
+        this.push('\n', None);
+      }
+      let y = iline as f32 * 0.1;
+      for (ichar, c) in line.chars().enumerate() {
+        let x = ichar as f32 * 0.01;
+        this.push(c, Some((na::point![x, y], na::point![x + 0.01, y + 0.05])));
+      }
+    }
+    this
+  }
+
+  fn push(&mut self, c: char, bounds: Option<CharBounds>) {
+    let c = if c.is_whitespace() { ' ' } else { c };
+    if c == ' ' && self.chars.last() == Some(&' ') {
+      return;
+    }
+    self.chars.push(c);
+    self.folded.push(fold(c));
+    self.bounds.push(bounds);
+  }
+
+  pub fn nchars(&self) -> usize {
+    self.chars.len()
+  }
+
+  pub fn folded(&self) -> &[char] {
+    &self.folded
+  }
+
+  pub fn text(&self, range: Range<usize>) -> String {
+    self.chars[range].iter().collect()
+  }
+
+  /// one rect per line covered by the chars.
+  pub fn rects(&self, range: Range<usize>) -> Vec<Rect> {
+    line_rects(self.bounds[range].iter().flatten().copied())
+  }
+
+  /// the char whose bounds contain the point.
+  #[cfg(feature = "app")]
+  pub fn char_at(&self, point_page_norm: na::Point2<f32>) -> Option<usize> {
+    self.bounds.iter().position(|bounds| {
+      bounds.map_or(false, |(min, max)| {
+        min.x <= point_page_norm.x
+          && point_page_norm.x <= max.x
+          && min.y <= point_page_norm.y
+          && point_page_norm.y <= max.y
+      })
+    })
+  }
+
+  /// the char with the bounds closest to the point, preferring chars on the same line.
+  #[cfg(feature = "app")]
+  pub fn nearest_char(&self, point_page_norm: na::Point2<f32>) -> Option<usize> {
+    let distance = |(min, max): &CharBounds| {
+      let dx = (min.x - point_page_norm.x)
+        .max(point_page_norm.x - max.x)
+        .max(0.0);
+      let dy = (min.y - point_page_norm.y)
+        .max(point_page_norm.y - max.y)
+        .max(0.0);
+      // vertical distance counts more, so the closest line wins over the closest column
+      dx + 10.0 * dy
+    };
+    self
+      .bounds
+      .iter()
+      .enumerate()
+      .filter_map(|(i, bounds)| bounds.as_ref().map(|bounds| (i, distance(bounds))))
+      .min_by(|(_, a), (_, b)| a.total_cmp(b))
+      .map(|(i, _)| i)
+  }
+}
+
+pub fn fold(c: char) -> char {
+  if c.is_whitespace() {
+    ' '
+  } else {
+    c.to_lowercase().next().unwrap_or(c)
+  }
+}
+
+/// merges the bounds of consecutive chars on the same line.
+fn line_rects(bounds: impl Iterator<Item = CharBounds>) -> Vec<Rect> {
+  let mut lines: Vec<CharBounds> = Vec::new();
+  for (min, max) in bounds {
+    match lines.last_mut() {
+      Some((line_min, line_max)) if is_same_line((*line_min, *line_max), (min, max)) => {
+        *line_min = line_min.inf(&min);
+        *line_max = line_max.sup(&max);
+      }
+      _ => lines.push((min, max)),
+    }
+  }
+  lines
+    .into_iter()
+    .map(|(min, max)| Rect::from_size_min(max - min, min))
+    .collect()
+}
+
+/// whether the vertical extents overlap by at least half the smaller height.
+fn is_same_line(a: CharBounds, b: CharBounds) -> bool {
+  let overlap = a.1.y.min(b.1.y) - a.0.y.max(b.0.y);
+  let min_height = (a.1.y - a.0.y).min(b.1.y - b.0.y);
+  overlap >= min_height / 2.0
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{is_same_line, line_rects, CharBounds, PageText};
+  use crate::math::Rect;
+
+  fn bounds(min: [f32; 2], max: [f32; 2]) -> CharBounds {
+    (min.into(), max.into())
+  }
+
+  fn assert_rect(rect: &Rect, min: [f32; 2], max: [f32; 2]) {
+    let expected = Rect::from_size_min(na::Point2::from(max) - na::Point2::from(min), min.into());
+    let close = (rect.center - expected.center).norm() < 1e-5
+      && (rect.extents_half - expected.extents_half).norm() < 1e-5;
+    assert!(close, "{rect:?} != {expected:?}");
+  }
+
+  #[test]
+  fn collapses_whitespace() {
+    let text = PageText::from_lines(&["Hello  World", " again"]);
+    assert_eq!(text.text(0..text.nchars()), "Hello World again");
+    assert_eq!(text.folded()[..5], ['h', 'e', 'l', 'l', 'o']);
+  }
+
+  #[test]
+  fn merges_chars_on_a_line() {
+    let rects = line_rects(
+      [
+        bounds([0.0, 0.0], [0.1, 0.1]),
+        bounds([0.1, 0.02], [0.2, 0.1]),
+        // a superscript still overlaps half of its height
+        bounds([0.2, -0.02], [0.25, 0.04]),
+        bounds([0.0, 0.2], [0.1, 0.3]),
+      ]
+      .into_iter(),
+    );
+    assert_eq!(rects.len(), 2);
+    assert_rect(&rects[0], [0.0, -0.02], [0.25, 0.1]);
+    assert_rect(&rects[1], [0.0, 0.2], [0.1, 0.3]);
+  }
+
+  #[test]
+  fn splits_lines_overlapping_less_than_half() {
+    let a = bounds([0.0, 0.0], [0.1, 0.1]);
+    assert!(is_same_line(a, bounds([0.1, 0.05], [0.2, 0.15])));
+    assert!(!is_same_line(a, bounds([0.1, 0.06], [0.2, 0.16])));
+    assert!(!is_same_line(a, bounds([0.0, 0.1], [0.1, 0.2])));
+  }
+
+  #[test]
+  fn rects_skip_chars_without_bounds() {
+    let text = PageText::from_lines(&["ab", "cd"]);
+    // from b over the line break to c
+    let rects = text.rects(1..4);
+    assert_eq!(rects.len(), 2);
+    assert_rect(&rects[0], [0.01, 0.0], [0.02, 0.05]);
+    assert_rect(&rects[1], [0.0, 0.1], [0.01, 0.15]);
+  }
+
+  #[test]
+  fn finds_chars_at_points() {
+    let text = PageText::from_lines(&["ab", "cd"]);
+    assert_eq!(text.char_at(na::point![0.015, 0.02]), Some(1));
+    assert_eq!(text.char_at(na::point![0.5, 0.02]), None);
+    // the line wins over a closer column
+    assert_eq!(text.nearest_char(na::point![0.5, 0.12]), Some(4));
+    assert_eq!(text.nearest_char(na::point![-0.1, 0.02]), Some(0));
+  }
+}
@@ -0,0 +1,113 @@
+use super::CANVAS_UNITS_PER_PDF_POINT;
+
+use crate::{math::Rect, natrans};
+
+use pdfium_render::prelude::*;
+
+/// edge length of a square tile in physical pixels
+pub const TILE_SIZE_PHYSICAL: u16 = 256;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TileKey {
+  pub page: usize,
+  /// tiles of zoom level `l` have a resolution of `2^l` physical pixels per canvas unit
+  pub zoom_level: i32,
+  pub x: u32,
+  pub y: u32,
+}
+
+impl TileKey {
+  pub fn tile_size_canvas(zoom_level: i32) -> f32 {
+    TILE_SIZE_PHYSICAL as f32 / 2f32.powi(zoom_level)
+  }
+
+  /// the tile `nlevels` zoom levels coarser, which covers this one
+  #[cfg(feature = "app")]
+  pub fn ancestor(&self, nlevels: i32) -> Self {
+    Self {
+      page: self.page,
+      zoom_level: self.zoom_level - nlevels,
+      x: self.x >> nlevels,
+      y: self.y >> nlevels,
+    }
+  }
+
+  /// the area covered by this tile, relative to the page `page_rect_canvas`.
+  /// might extend beyond the page.
+  pub fn rect_canvas(&self, page_rect_canvas: &Rect) -> Rect {
+    let tile_size_canvas = Self::tile_size_canvas(self.zoom_level);
+    let min = page_rect_canvas.center - page_rect_canvas.extents_half
+      + na::vector![self.x as f32, self.y as f32].scale(tile_size_canvas);
+    Rect::from_size_min(na::Vector2::repeat(tile_size_canvas), min)
+  }
+}
+
+/// renders the part of `page` covered by the tile into rgba bytes.
+pub fn rasterize_tile(
+  page: &PdfPage,
+  page_rect_canvas: &Rect,
+  key: &TileKey,
+) -> Result<Vec<u8>, PdfiumError> {
+  let tile_rect_canvas = key.rect_canvas(page_rect_canvas);
+  let transform =
+    page_to_tile_renderer_transform(page, page_rect_canvas, &tile_rect_canvas, key.zoom_level);
+
+  let render_config = PdfRenderConfig::default()
+    .set_target_size(TILE_SIZE_PHYSICAL, TILE_SIZE_PHYSICAL)
+    .set_maximum_width(TILE_SIZE_PHYSICAL)
+    .set_maximum_height(TILE_SIZE_PHYSICAL)
+    .set_reverse_byte_order(true)
+    .transform(
+      transform.m11,
+      transform.m21,
+      transform.m12,
+      transform.m22,
+      transform.m13,
+      transform.m23,
+    )?;
+
+  let mut bitmap = PdfBitmap::empty(
+    TILE_SIZE_PHYSICAL,
+    TILE_SIZE_PHYSICAL,
+    PdfBitmapFormat::default(),
+    page.bindings(),
+  )?;
+  page.render_into_bitmap_with_config(&mut bitmap, &render_config)?;
+  Ok(bitmap.as_bytes().to_vec())
+}
+
+fn page_to_tile_renderer_transform(
+  page: &PdfPage,
+  page_rect_canvas: &Rect,
+  tile_rect_canvas: &Rect,
+  zoom_level: i32,
+) -> na::Matrix3<f32> {
+  let page_anchor = na::Point2::new(page.width().value / 2.0, page.height().value / 2.0);
+  let page_to_canvas = natrans!(na::Translation2::from(page_rect_canvas.center))
+    * natrans!(na::Scale2::new(
+      CANVAS_UNITS_PER_PDF_POINT,
+      CANVAS_UNITS_PER_PDF_POINT
+    ))
+    * natrans!(na::Translation::from(-page_anchor));
+
+  let canvas_to_tile = {
+    let tile_min = tile_rect_canvas.center - tile_rect_canvas.extents_half;
+    let translation = na::Translation2::from(-tile_min.coords);
+    let physical_per_canvas = 2f32.powi(zoom_level);
+    let scale = na::Scale2::new(physical_per_canvas, physical_per_canvas);
+    natrans!(scale) * natrans!(translation)
+  };
+
+  // the transformation we want to do from page to the tile
+  let page_to_tile = canvas_to_tile * page_to_canvas;
+
+  // for reverting what the pdf renderer is going to do automatically
+  let tile_size = TILE_SIZE_PHYSICAL as f32;
+  let page_to_tile_scale = natrans!(na::Scale2::new(
+    page.width().value / tile_size,
+    page.height().value / tile_size
+  ));
+  // the transform to give to the renderer
+  let transform = page_to_tile_scale * page_to_tile;
+  transform.to_homogeneous()
+}
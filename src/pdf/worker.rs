@@ -0,0 +1,836 @@
+use super::{
+  export::{self, ExportPage},
+  links,
+  tile::{self, TileKey},
+  PdfDocumentId, PdfDocumentInfo, PdfOutlineItem, PdfPageInfo,
+};
+
+use crate::math::Rect;
+
+#[cfg(feature = "app")]
+use super::{
+  search::{self, DocumentSearchHit},
+  text::PageText,
+};
+
+use pdfium_render::prelude::*;
+use std::{
+  collections::{HashMap, HashSet, VecDeque},
+  fmt,
+};
+
+#[cfg(not(target_arch = "wasm32"))]
+pub type PdfSource = std::path::PathBuf;
+#[cfg(target_arch = "wasm32")]
+pub type PdfSource = String;
+
+#[derive(Debug, Clone)]
+pub enum PdfError {
+  /// the pdf is missing, unreadable or malformed
+  Load { source: PdfSource, reason: String },
+  /// the pdfium library could not be found or bound to
+  Binding(String),
+  /// pdfium failed to build the exported pdf
+  Export(String),
+  /// pdfium failed to render the page of the pdf, it stays a placeholder
+  Render {
+    source: PdfSource,
+    page: usize,
+    reason: String,
+  },
+  /// pdfium failed to put the image into a pdf
+  #[cfg(feature = "app")]
+  #[cfg(not(target_arch = "wasm32"))]
+  Image(String),
+}
+
+impl fmt::Display for PdfError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      #[cfg(not(target_arch = "wasm32"))]
+      PdfError::Load { source, reason } => {
+        write!(f, "failed to load {}: {reason}", source.display())
+      }
+      #[cfg(target_arch = "wasm32")]
+      PdfError::Load { source, reason } => write!(f, "failed to load {source}: {reason}"),
+      PdfError::Binding(reason) => write!(f, "failed to load the pdfium library: {reason}"),
+      PdfError::Export(reason) => write!(f, "failed to export the pdf: {reason}"),
+      #[cfg(not(target_arch = "wasm32"))]
+      PdfError::Render {
+        source,
+        page,
+        reason,
+      } => {
+        let page = page + 1;
+        write!(
+          f,
+          "failed to render page {page} of {}: {reason}",
+          source.display()
+        )
+      }
+      #[cfg(target_arch = "wasm32")]
+      PdfError::Render {
+        source,
+        page,
+        reason,
+      } => write!(
+        f,
+        "failed to render page {} of {source}: {reason}",
+        page + 1
+      ),
+      #[cfg(feature = "app")]
+      #[cfg(not(target_arch = "wasm32"))]
+      PdfError::Image(reason) => write!(f, "failed to convert the image: {reason}"),
+    }
+  }
+}
+
+impl std::error::Error for PdfError {}
+
+/// size and rgba bytes of a rendered page
+type RenderedPage = ([usize; 2], Vec<u8>);
+
+/// width of the rendered page thumbnails in physical pixels
+const THUMBNAIL_WIDTH_PHYSICAL: u16 = 192;
+
+#[derive(Debug, Clone)]
+pub struct RasterRequest {
+  pub document: PdfDocumentId,
+  /// the page in the document, `tile.page` is the page in the page list
+  pub page: usize,
+  pub tile: TileKey,
+  pub page_rect_canvas: Rect,
+}
+
+pub struct RasterResponse {
+  pub document: PdfDocumentId,
+  pub page: usize,
+  pub tile: TileKey,
+  /// rgba bytes
+  pub data: Vec<u8>,
+}
+
+pub struct PdfThumbnail {
+  pub document: PdfDocumentId,
+  pub page: usize,
+  pub size: [usize; 2],
+  /// rgba bytes
+  pub data: Vec<u8>,
+}
+
+enum WorkerRequest {
+  Load {
+    document: PdfDocumentId,
+    source: PdfSource,
+  },
+  Unload(PdfDocumentId),
+  /// replaces all outstanding raster requests
+  #[cfg(feature = "app")]
+  Rasterize(Vec<RasterRequest>),
+  /// thumbnails are queued up behind the tiles
+  #[cfg(feature = "app")]
+  Thumbnails {
+    document: PdfDocumentId,
+    pages: Vec<usize>,
+  },
+  #[cfg(feature = "app")]
+  Search {
+    documents: Vec<PdfDocumentId>,
+    query: String,
+  },
+  #[cfg(feature = "app")]
+  PageText {
+    document: PdfDocumentId,
+    page: usize,
+  },
+  Export(Vec<ExportPage>),
+  #[cfg(feature = "app")]
+  #[cfg(not(target_arch = "wasm32"))]
+  ImagePdf {
+    image: image::DynamicImage,
+    size: PdfPageInfo,
+  },
+  RenderPage {
+    document: PdfDocumentId,
+    page: usize,
+    width: u16,
+  },
+}
+
+pub enum WorkerResponse {
+  Loaded {
+    document: PdfDocumentId,
+    result: Result<PdfDocumentInfo, PdfError>,
+  },
+  Rasterized(RasterResponse),
+  Thumbnail(PdfThumbnail),
+  /// a tile or thumbnail of the page in the document could not be rendered
+  RenderFailed {
+    document: PdfDocumentId,
+    page: usize,
+    reason: String,
+  },
+  #[cfg(feature = "app")]
+  Searched {
+    query: String,
+    hits: Vec<(PdfDocumentId, DocumentSearchHit)>,
+  },
+  #[cfg(feature = "app")]
+  PageText {
+    document: PdfDocumentId,
+    page: usize,
+    text: PageText,
+  },
+  /// the bytes of the exported pdf
+  Exported(Result<Vec<u8>, PdfError>),
+  /// the bytes of the pdf showing the image
+  #[cfg(feature = "app")]
+  #[cfg(not(target_arch = "wasm32"))]
+  ImagePdf(Result<Vec<u8>, PdfError>),
+  /// `None` if the page is missing
+  RenderedPage(Result<Option<RenderedPage>, String>),
+}
+
+/// The pdfium side of the worker, independent of how it is driven.
+struct WorkerState<'a> {
+  pdfium: &'a Pdfium,
+  documents: HashMap<PdfDocumentId, PdfDocument<'a>>,
+  queue: VecDeque<RasterRequest>,
+  thumbnail_queue: VecDeque<(PdfDocumentId, usize)>,
+  /// extracted on the first search in a document
+  #[cfg(feature = "app")]
+  texts: HashMap<PdfDocumentId, Vec<PageText>>,
+}
+
+impl<'a> WorkerState<'a> {
+  fn new(pdfium: &'a Pdfium) -> Self {
+    Self {
+      pdfium,
+      documents: HashMap::default(),
+      queue: VecDeque::default(),
+      thumbnail_queue: VecDeque::default(),
+      #[cfg(feature = "app")]
+      texts: HashMap::default(),
+    }
+  }
+
+  fn is_idle(&self) -> bool {
+    self.queue.is_empty() && self.thumbnail_queue.is_empty()
+  }
+
+  fn handle(&mut self, request: WorkerRequest) -> Option<WorkerResponse> {
+    match request {
+      WorkerRequest::Load { document, source } => {
+        let pdf_document = match load_pdf(self.pdfium, source.clone()) {
+          Ok(pdf_document) => pdf_document,
+          Err(error) => {
+            let reason = error.to_string();
+            let result = Err(PdfError::Load { source, reason });
+            return Some(WorkerResponse::Loaded { document, result });
+          }
+        };
+        let pages: Vec<_> = pdf_document
+          .pages()
+          .iter()
+          .map(|page| PdfPageInfo {
+            width_points: page.width().value,
+            height_points: page.height().value,
+          })
+          .collect();
+        let outline = read_outline(&pdf_document);
+        let links = pdf_document
+          .pages()
+          .iter()
+          .map(|page| links::read_links(&pdf_document, &page, &pages))
+          .collect();
+        self.documents.insert(document, pdf_document);
+        let result = Ok(PdfDocumentInfo {
+          id: document,
+          pages,
+          outline,
+          links,
+        });
+        Some(WorkerResponse::Loaded { document, result })
+      }
+      WorkerRequest::Unload(document) => {
+        self.documents.remove(&document);
+        #[cfg(feature = "app")]
+        self.texts.remove(&document);
+        self.queue.retain(|r| r.document != document);
+        self.thumbnail_queue.retain(|(d, _)| *d != document);
+        None
+      }
+      #[cfg(feature = "app")]
+      WorkerRequest::Rasterize(requests) => {
+        self.queue = requests.into();
+        None
+      }
+      #[cfg(feature = "app")]
+      WorkerRequest::Thumbnails { document, pages } => {
+        self
+          .thumbnail_queue
+          .extend(pages.into_iter().map(|page| (document, page)));
+        None
+      }
+      #[cfg(feature = "app")]
+      WorkerRequest::Search { documents, query } => {
+        let mut hits = Vec::new();
+        for document in documents {
+          let Some(pdf_document) = self.documents.get(&document) else {
+            continue;
+          };
+          let texts = self.texts.entry(document).or_insert_with(|| {
+            pdf_document
+              .pages()
+              .iter()
+              .map(|page| PageText::new(&page))
+              .collect()
+          });
+          hits.extend(
+            search::search(texts, &query)
+              .into_iter()
+              .map(|hit| (document, hit)),
+          );
+        }
+        Some(WorkerResponse::Searched { query, hits })
+      }
+      #[cfg(feature = "app")]
+      WorkerRequest::PageText { document, page } => {
+        let text = match self.texts.get(&document) {
+          Some(texts) => texts.get(page)?.clone(),
+          None => PageText::new(
+            &self
+              .documents
+              .get(&document)?
+              .pages()
+              .get(page as u16)
+              .ok()?,
+          ),
+        };
+        Some(WorkerResponse::PageText {
+          document,
+          page,
+          text,
+        })
+      }
+      WorkerRequest::Export(pages) => {
+        let bytes = export::build_pdf(
+          self.pdfium,
+          |document| self.documents.get(&document),
+          &pages,
+        )
+        .map_err(|error| PdfError::Export(format!("{error:?}")));
+        Some(WorkerResponse::Exported(bytes))
+      }
+      #[cfg(feature = "app")]
+      #[cfg(not(target_arch = "wasm32"))]
+      WorkerRequest::ImagePdf { image, size } => {
+        let bytes = export::build_image_pdf(self.pdfium, &image, size)
+          .map_err(|error| PdfError::Image(format!("{error:?}")));
+        Some(WorkerResponse::ImagePdf(bytes))
+      }
+      WorkerRequest::RenderPage {
+        document,
+        page,
+        width,
+      } => {
+        let page = self
+          .documents
+          .get(&document)
+          .and_then(|document| document.pages().get(page as u16).ok());
+        let rendered = page
+          .map(|page| render_page(&page, width))
+          .transpose()
+          .map_err(|error| format!("{error:?}"));
+        Some(WorkerResponse::RenderedPage(rendered))
+      }
+    }
+  }
+
+  /// processes the next queued request, tiles before thumbnails.
+  fn work_next(&mut self) -> Option<WorkerResponse> {
+    while let Some(request) = self.queue.pop_front() {
+      let Some(document) = self.documents.get(&request.document) else {
+        continue;
+      };
+      let Ok(page) = document.pages().get(request.page as u16) else {
+        continue;
+      };
+      let response = match tile::rasterize_tile(&page, &request.page_rect_canvas, &request.tile) {
+        Ok(data) => WorkerResponse::Rasterized(RasterResponse {
+          document: request.document,
+          page: request.page,
+          tile: request.tile,
+          data,
+        }),
+        Err(error) => WorkerResponse::RenderFailed {
+          document: request.document,
+          page: request.page,
+          reason: format!("{error:?}"),
+        },
+      };
+      return Some(response);
+    }
+    while let Some((document_id, ipage)) = self.thumbnail_queue.pop_front() {
+      let Some(document) = self.documents.get(&document_id) else {
+        continue;
+      };
+      let Ok(page) = document.pages().get(ipage as u16) else {
+        continue;
+      };
+      let response = match render_page(&page, THUMBNAIL_WIDTH_PHYSICAL) {
+        Ok((size, data)) => WorkerResponse::Thumbnail(PdfThumbnail {
+          document: document_id,
+          page: ipage,
+          size,
+          data,
+        }),
+        Err(error) => WorkerResponse::RenderFailed {
+          document: document_id,
+          page: ipage,
+          reason: format!("{error:?}"),
+        },
+      };
+      return Some(response);
+    }
+    None
+  }
+}
+
+/// renders the whole page `width` pixels wide, returns the size and rgba bytes
+fn render_page(page: &PdfPage, width: u16) -> Result<RenderedPage, PdfiumError> {
+  let render_config = PdfRenderConfig::default()
+    .set_target_width(width)
+    .set_reverse_byte_order(true);
+  let bitmap = page.render_with_config(&render_config)?;
+  let size = [bitmap.width() as usize, bitmap.height() as usize];
+  Ok((size, bitmap.as_bytes().to_vec()))
+}
+
+/// flattens the bookmark tree in depth first order.
+///
+/// the safe bookmark api of pdfium-render does not expose the target page,
+/// so the tree is walked with the raw bindings.
+fn read_outline(document: &PdfDocument) -> Vec<PdfOutlineItem> {
+  let bindings = document.bindings();
+  let handle = bindings.get_handle_from_document(document);
+
+  let mut outline = Vec::new();
+  // malformed documents can contain cycles
+  let mut visited = HashSet::new();
+  let mut stack = vec![(
+    bindings.FPDFBookmark_GetFirstChild(handle, std::ptr::null_mut()),
+    0,
+  )];
+  while let Some((bookmark, depth)) = stack.pop() {
+    if bookmark.is_null() || !visited.insert(bookmark as usize) {
+      continue;
+    }
+    // the sibling is processed after all descendants
+    stack.push((
+      bindings.FPDFBookmark_GetNextSibling(handle, bookmark),
+      depth,
+    ));
+    stack.push((
+      bindings.FPDFBookmark_GetFirstChild(handle, bookmark),
+      depth + 1,
+    ));
+
+    let title = {
+      let length = bindings.FPDFBookmark_GetTitle(bookmark, std::ptr::null_mut(), 0);
+      let mut buffer = vec![0u8; length as usize];
+      bindings.FPDFBookmark_GetTitle(bookmark, buffer.as_mut_ptr().cast(), length);
+      bindings
+        .get_string_from_pdfium_utf16le_bytes(buffer)
+        .unwrap_or_default()
+    };
+
+    let mut destination = bindings.FPDFBookmark_GetDest(handle, bookmark);
+    if destination.is_null() {
+      let action = bindings.FPDFBookmark_GetAction(bookmark);
+      if !action.is_null() {
+        destination = bindings.FPDFAction_GetDest(handle, action);
+      }
+    }
+    let page = match destination.is_null() {
+      true => None,
+      false => usize::try_from(bindings.FPDFDest_GetDestPageIndex(handle, destination)).ok(),
+    };
+
+    outline.push(PdfOutlineItem { title, page, depth });
+  }
+  outline
+}
+
+/// The environment variable with the path of the pdfium library to use instead of the system one.
+#[cfg(not(target_arch = "wasm32"))]
+const PDFIUM_LIBRARY_VAR: &str = "CARVEOUT_PDFIUM";
+
+fn bind_pdfium() -> Result<Pdfium, PdfError> {
+  cfg_if::cfg_if! {
+    if #[cfg(target_arch = "wasm32")] {
+      let bindings = Pdfium::bind_to_system_library();
+    } else {
+      let bindings = match std::env::var(PDFIUM_LIBRARY_VAR) {
+        Ok(path) => Pdfium::bind_to_library(path),
+        Err(_) => Pdfium::bind_to_system_library(),
+      };
+    }
+  }
+  bindings
+    .map(Pdfium::new)
+    .map_err(|error| PdfError::Binding(format!("{error:?}")))
+}
+
+/// The answer to the request without pdfium, for the requests something waits on.
+fn respond_unbound(request: WorkerRequest, error: &PdfError) -> Option<WorkerResponse> {
+  match request {
+    WorkerRequest::Load { document, .. } => Some(WorkerResponse::Loaded {
+      document,
+      result: Err(error.clone()),
+    }),
+    WorkerRequest::Export(_) => Some(WorkerResponse::Exported(Err(error.clone()))),
+    #[cfg(feature = "app")]
+    #[cfg(not(target_arch = "wasm32"))]
+    WorkerRequest::ImagePdf { .. } => Some(WorkerResponse::ImagePdf(Err(error.clone()))),
+    WorkerRequest::RenderPage { .. } => Some(WorkerResponse::RenderedPage(Ok(None))),
+    _ => None,
+  }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn load_pdf(pdfium: &Pdfium, path: PdfSource) -> Result<PdfDocument, PdfiumError> {
+  pdfium.load_pdf_from_file(&path, None)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn load_pdf(pdfium: &Pdfium, url: PdfSource) -> Result<PdfDocument, PdfiumError> {
+  futures::executor::block_on(pdfium.load_pdf_from_fetch(url, None))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use self::native::PdfWorker;
+#[cfg(target_arch = "wasm32")]
+pub use self::wasm::PdfWorker;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod native {
+  use super::*;
+
+  use std::{
+    cell::RefCell,
+    sync::mpsc::{self, Receiver, Sender, TryRecvError},
+    thread,
+  };
+
+  /// Runs pdfium on a dedicated thread.
+  ///
+  /// The `Pdfium` instance and all documents borrowing from it live on the stack of that thread,
+  /// so none of them have to be `Send`.
+  pub struct PdfWorker {
+    requests: Sender<WorkerRequest>,
+    responses: Receiver<WorkerResponse>,
+    /// responses that came in while blocking on another one, for the next `receive`
+    pending: RefCell<Vec<WorkerResponse>>,
+  }
+
+  impl PdfWorker {
+    pub fn spawn() -> Self {
+      let (request_sender, request_receiver) = mpsc::channel();
+      let (response_sender, response_receiver) = mpsc::channel();
+      thread::Builder::new()
+        .name("pdf_worker".into())
+        .spawn(move || run(request_receiver, response_sender))
+        .expect("Fatal error: Failed to spawn pdf worker thread.");
+
+      Self {
+        requests: request_sender,
+        responses: response_receiver,
+        pending: RefCell::default(),
+      }
+    }
+
+    /// Blocks until the document is loaded.
+    pub fn load(
+      &self,
+      document: PdfDocumentId,
+      source: PdfSource,
+    ) -> Result<PdfDocumentInfo, PdfError> {
+      let id = document;
+      self.send(WorkerRequest::Load { document, source });
+      self.wait_for(|response| match response {
+        WorkerResponse::Loaded { document, result } if document == id => Ok(result),
+        response => Err(response),
+      })
+    }
+
+    pub fn unload(&self, document: PdfDocumentId) {
+      self.send(WorkerRequest::Unload(document));
+    }
+
+    #[cfg(feature = "app")]
+    pub fn rasterize(&self, requests: Vec<RasterRequest>) {
+      self.send(WorkerRequest::Rasterize(requests));
+    }
+
+    #[cfg(feature = "app")]
+    pub fn render_thumbnails(&self, document: PdfDocumentId, pages: Vec<usize>) {
+      self.send(WorkerRequest::Thumbnails { document, pages });
+    }
+
+    #[cfg(feature = "app")]
+    pub fn search(&self, documents: Vec<PdfDocumentId>, query: String) {
+      self.send(WorkerRequest::Search { documents, query });
+    }
+
+    #[cfg(feature = "app")]
+    pub fn extract_text(&self, document: PdfDocumentId, page: usize) {
+      self.send(WorkerRequest::PageText { document, page });
+    }
+
+    /// Blocks until the pdf is built.
+    pub fn export(&self, pages: Vec<ExportPage>) -> Result<Vec<u8>, PdfError> {
+      self.send(WorkerRequest::Export(pages));
+      self.wait_for(|response| match response {
+        WorkerResponse::Exported(bytes) => Ok(bytes),
+        response => Err(response),
+      })
+    }
+
+    /// Blocks until the pdf is built.
+    #[cfg(feature = "app")]
+    pub fn image_pdf(
+      &self,
+      image: image::DynamicImage,
+      size: PdfPageInfo,
+    ) -> Result<Vec<u8>, PdfError> {
+      self.send(WorkerRequest::ImagePdf { image, size });
+      self.wait_for(|response| match response {
+        WorkerResponse::ImagePdf(bytes) => Ok(bytes),
+        response => Err(response),
+      })
+    }
+
+    /// Blocks until the page is rendered.
+    pub fn render_page(
+      &self,
+      document: PdfDocumentId,
+      page: usize,
+      width: u16,
+    ) -> Result<Option<RenderedPage>, String> {
+      self.send(WorkerRequest::RenderPage {
+        document,
+        page,
+        width,
+      });
+      self.wait_for(|response| match response {
+        WorkerResponse::RenderedPage(rendered) => Ok(rendered),
+        response => Err(response),
+      })
+    }
+
+    #[cfg(feature = "app")]
+    pub fn receive(&self) -> Vec<WorkerResponse> {
+      let mut responses = std::mem::take(&mut *self.pending.borrow_mut());
+      responses.extend(self.responses.try_iter());
+      responses
+    }
+
+    /// Blocks until `accept` takes a response, keeping the ones it hands back for `receive`.
+    fn wait_for<T>(
+      &self,
+      mut accept: impl FnMut(WorkerResponse) -> Result<T, WorkerResponse>,
+    ) -> T {
+      loop {
+        let response = self.responses.recv().expect("pdf worker died");
+        match accept(response) {
+          Ok(value) => return value,
+          Err(response) => self.pending.borrow_mut().push(response),
+        }
+      }
+    }
+
+    fn send(&self, request: WorkerRequest) {
+      self.requests.send(request).expect("pdf worker died");
+    }
+  }
+
+  fn run(requests: Receiver<WorkerRequest>, responses: Sender<WorkerResponse>) {
+    let pdfium = match bind_pdfium() {
+      Ok(pdfium) => pdfium,
+      Err(error) => {
+        tracing::error!("{error}");
+        // every document fails to load
+        for request in requests {
+          if let Some(response) = respond_unbound(request, &error) {
+            if responses.send(response).is_err() {
+              return;
+            }
+          }
+        }
+        return;
+      }
+    };
+    let mut state = WorkerState::new(&pdfium);
+
+    loop {
+      // sleep while there is nothing to do
+      if state.is_idle() {
+        let Ok(request) = requests.recv() else {
+          return;
+        };
+        if let Some(response) = state.handle(request) {
+          if responses.send(response).is_err() {
+            return;
+          }
+        }
+      }
+
+      // process everything that came in meanwhile, so that stale raster requests get dropped
+      loop {
+        match requests.try_recv() {
+          Ok(request) => {
+            if let Some(response) = state.handle(request) {
+              if responses.send(response).is_err() {
+                return;
+              }
+            }
+          }
+          Err(TryRecvError::Empty) => break,
+          Err(TryRecvError::Disconnected) => return,
+        }
+      }
+
+      if let Some(response) = state.work_next() {
+        if responses.send(response).is_err() {
+          return;
+        }
+      }
+    }
+  }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod wasm {
+  use super::*;
+
+  use std::cell::RefCell;
+
+  /// Without threads the requests are processed in place, a few per frame.
+  pub struct PdfWorker {
+    /// the error if pdfium is not available
+    inner: Result<RefCell<InlineWorker>, PdfError>,
+    /// immediate responses to hand out with the next `receive`
+    responses: RefCell<Vec<WorkerResponse>>,
+  }
+
+  #[ouroboros::self_referencing]
+  struct InlineWorker {
+    pdfium: Pdfium,
+
+    #[borrows(pdfium)]
+    #[not_covariant]
+    state: WorkerState<'this>,
+  }
+
+  impl PdfWorker {
+    const NTILES_PER_FRAME: usize = 2;
+
+    pub fn spawn() -> Self {
+      let inner = bind_pdfium()
+        .map(|pdfium| {
+          let inner = InlineWorkerBuilder {
+            pdfium,
+            state_builder: |pdfium| WorkerState::new(pdfium),
+          }
+          .build();
+          RefCell::new(inner)
+        })
+        .map_err(|error| {
+          tracing::error!("{error}");
+          error
+        });
+      Self {
+        inner,
+        responses: RefCell::default(),
+      }
+    }
+
+    pub fn load(
+      &self,
+      document: PdfDocumentId,
+      source: PdfSource,
+    ) -> Result<PdfDocumentInfo, PdfError> {
+      let response = self.handle(WorkerRequest::Load { document, source });
+      match response {
+        Some(WorkerResponse::Loaded { result, .. }) => result,
+        _ => unreachable!(),
+      }
+    }
+
+    pub fn unload(&self, document: PdfDocumentId) {
+      self.handle(WorkerRequest::Unload(document));
+    }
+
+    pub fn rasterize(&self, requests: Vec<RasterRequest>) {
+      self.handle(WorkerRequest::Rasterize(requests));
+    }
+
+    pub fn render_thumbnails(&self, document: PdfDocumentId, pages: Vec<usize>) {
+      self.handle(WorkerRequest::Thumbnails { document, pages });
+    }
+
+    pub fn search(&self, documents: Vec<PdfDocumentId>, query: String) {
+      let response = self.handle(WorkerRequest::Search { documents, query });
+      self.responses.borrow_mut().extend(response);
+    }
+
+    pub fn extract_text(&self, document: PdfDocumentId, page: usize) {
+      let response = self.handle(WorkerRequest::PageText { document, page });
+      self.responses.borrow_mut().extend(response);
+    }
+
+    pub fn export(&self, pages: Vec<ExportPage>) -> Result<Vec<u8>, PdfError> {
+      match self.handle(WorkerRequest::Export(pages)) {
+        Some(WorkerResponse::Exported(bytes)) => bytes,
+        _ => unreachable!(),
+      }
+    }
+
+    pub fn render_page(
+      &self,
+      document: PdfDocumentId,
+      page: usize,
+      width: u16,
+    ) -> Result<Option<RenderedPage>, String> {
+      let response = self.handle(WorkerRequest::RenderPage {
+        document,
+        page,
+        width,
+      });
+      match response {
+        Some(WorkerResponse::RenderedPage(rendered)) => rendered,
+        _ => unreachable!(),
+      }
+    }
+
+    pub fn receive(&self) -> Vec<WorkerResponse> {
+      let mut responses = std::mem::take(&mut *self.responses.borrow_mut());
+      if let Ok(inner) = &self.inner {
+        inner.borrow_mut().with_state_mut(|state| {
+          responses.extend((0..Self::NTILES_PER_FRAME).map_while(|_| state.work_next()));
+        });
+      }
+      responses
+    }
+
+    fn handle(&self, request: WorkerRequest) -> Option<WorkerResponse> {
+      match &self.inner {
+        Ok(inner) => inner
+          .borrow_mut()
+          .with_state_mut(|state| state.handle(request)),
+        Err(error) => respond_unbound(request, error),
+      }
+    }
+  }
+}
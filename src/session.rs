@@ -0,0 +1,55 @@
+use crate::{
+  spaces::Camera,
+  tools::{PenConfig, ToolEnum},
+};
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// how many recent files are remembered
+const MAX_RECENT_FILES: usize = 10;
+
+/// What is restored on the next start.
+///
+/// Kept in the config dir, written when carveout quits, see `file::save_session`.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Session {
+  /// the project last opened or saved
+  pub document: Option<PathBuf>,
+  /// the pdf last opened, reopened if there is no project
+  pub pdf: Option<PathBuf>,
+  pub camera: Camera,
+  pub tool: ToolEnum,
+  pub pen: PenConfig,
+  /// projects, the most recent first
+  pub recent_files: Vec<PathBuf>,
+}
+
+impl Session {
+  /// Remembers the project as the open document.
+  pub fn set_document(&mut self, path: PathBuf) {
+    self.forget_recent_file(&path);
+    self.recent_files.insert(0, path.clone());
+    self.recent_files.truncate(MAX_RECENT_FILES);
+    self.document = Some(path);
+  }
+
+  pub fn forget_recent_file(&mut self, path: &Path) {
+    self.recent_files.retain(|recent| recent != path);
+  }
+
+  /// where file dialogs start, the folder of the open document if any
+  pub fn dialog_dir(&self) -> PathBuf {
+    self
+      .document
+      .iter()
+      .chain(&self.pdf)
+      .find_map(|path| path.parent())
+      .filter(|dir| dir.is_dir())
+      .map_or_else(
+        || crate::util::USER_DIRS.home_dir().to_owned(),
+        Path::to_owned,
+      )
+  }
+}
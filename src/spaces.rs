@@ -1,6 +1,6 @@
 mod camera;
 
-pub use self::camera::Camera;
+pub use self::camera::{Camera, CameraAnimation};
 
 use crate::{input::InputManager, math::Rect, natrans};
 
@@ -45,15 +45,56 @@ pub enum Space {
 #[derive(Debug, Default)]
 pub struct SpaceManager {
   camera: Camera,
+  camera_animation: Option<CameraAnimation>,
   screen_rect_window_logical: Rect,
   scale_factor: f32,
 }
 
 impl SpaceManager {
   pub fn update_camera_controller(&mut self, input_manager: &InputManager) {
+    if let Some(animation) = &mut self.camera_animation {
+      // anything else moving the camera takes over
+      if !self.camera.approx_eq(animation.last()) {
+        self.camera_animation = None;
+      } else {
+        match animation.step() {
+          Some(camera) => self.camera = camera,
+          None => {
+            self.camera = animation.target().clone();
+            self.camera_animation = None;
+          }
+        }
+      }
+    }
     camera::controller::update(self, input_manager);
   }
 
+  pub fn animate_camera(&mut self, target: Camera) {
+    self.camera_animation = Some(CameraAnimation::new(self.camera.clone(), target));
+  }
+
+  pub fn is_camera_animating(&self) -> bool {
+    self.camera_animation.is_some()
+  }
+
+  /// the camera showing all of `rect_canvas` as large as possible, keeping the current rotation.
+  pub fn camera_fitting_rect(&self, rect_canvas: &Rect) -> Camera {
+    const MARGIN_FACTOR: f32 = 0.95;
+    let rotation = na::Rotation2::new(rect_canvas.angle - self.camera.angle);
+    let extents_half_view = rotation.matrix().abs() * rect_canvas.extents_half;
+    // the half extents of the screen in view space at zoom 1
+    let screen_extents_half_view = self.screen_rect_window_logical.size_norm_w();
+    let zoom = screen_extents_half_view
+      .component_div(&extents_half_view)
+      .min()
+      * MARGIN_FACTOR;
+    Camera {
+      position_canvas: rect_canvas.center,
+      angle: self.camera.angle,
+      zoom,
+    }
+  }
+
   pub fn update_screen_rect(&mut self, new_egui_rect: egui::Rect) {
     let size_logical = mint::Vector2::from(new_egui_rect.size()).into();
     let center_window_logical = mint::Point2::from(new_egui_rect.center()).into();
@@ -170,6 +211,7 @@ impl SpaceManager {
         natrans!(self.window_to_screen_logical()) * natrans!(self.physical_to_logical()) * point
       }
       [ScreenLogical, WindowLogical] => natrans!(self.screen_to_window_logical()) * point,
+      [WindowLogical, ScreenLogical] => natrans!(self.window_to_screen_logical()) * point,
       _ => unimplemented!("`transform_point` from {src:?} to {dst:?} unimplemented.",),
     }
   }
@@ -1,4 +1,7 @@
-#[derive(Debug, Clone, PartialEq)]
+use instant::{Duration, Instant};
+use std::f32::consts::TAU;
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct Camera {
   pub position_canvas: na::Point2<f32>,
   pub angle: f32,
@@ -34,6 +37,75 @@ impl Camera {
     self.position_canvas = center_canvas + vector_canvas;
     self.zoom *= zoom;
   }
+
+  /// `t` in [0,1] goes from `self` to `other`.
+  /// the zoom is interpolated geometrically and the angle along the shorter direction.
+  pub fn interpolate(&self, other: &Camera, t: f32) -> Camera {
+    let position_canvas = self.position_canvas + (other.position_canvas - self.position_canvas) * t;
+    let zoom = self.zoom * (other.zoom / self.zoom).powf(t);
+    let angle_difference = (other.angle - self.angle + TAU / 2.0).rem_euclid(TAU) - TAU / 2.0;
+    let angle = (self.angle + angle_difference * t).rem_euclid(TAU);
+    Camera {
+      position_canvas,
+      angle,
+      zoom,
+    }
+  }
+
+  /// whether the cameras show the same view, up to floating point noise.
+  pub fn approx_eq(&self, other: &Camera) -> bool {
+    const EPSILON: f32 = 1e-4;
+    let position_difference =
+      (self.position_canvas - other.position_canvas).magnitude() * self.zoom;
+    let zoom_difference = (self.zoom / other.zoom - 1.0).abs();
+    let angle_difference = (self.angle - other.angle).rem_euclid(TAU);
+    let angle_difference = angle_difference.min(TAU - angle_difference);
+    position_difference < EPSILON && zoom_difference < EPSILON && angle_difference < EPSILON
+  }
+}
+
+/// Smoothly moves the camera towards a target.
+#[derive(Debug, Clone)]
+pub struct CameraAnimation {
+  from: Camera,
+  to: Camera,
+  start: Instant,
+  /// what the animation last set the camera to, to notice interference
+  last: Camera,
+}
+
+impl CameraAnimation {
+  const DURATION: Duration = Duration::from_millis(300);
+
+  pub fn new(from: Camera, to: Camera) -> Self {
+    Self {
+      last: from.clone(),
+      from,
+      to,
+      start: Instant::now(),
+    }
+  }
+
+  pub fn last(&self) -> &Camera {
+    &self.last
+  }
+
+  /// the camera for the current point in time.
+  /// `None` once the animation is over.
+  pub fn step(&mut self) -> Option<Camera> {
+    let t = self.start.elapsed().as_secs_f32() / Self::DURATION.as_secs_f32();
+    if t >= 1.0 {
+      return None;
+    }
+    // smoothstep easing
+    let t = t * t * (3.0 - 2.0 * t);
+    self.last = self.from.interpolate(&self.to, t);
+    Some(self.last.clone())
+  }
+
+  pub fn target(&self) -> &Camera {
+    &self.to
+  }
 }
 
 pub mod controller {
@@ -132,6 +204,9 @@ pub mod controller {
         }
       }
     }
+    if let Some(zoom_factor) = input.zoom_factor {
+      scale *= zoom_factor;
+    }
 
     if translation_screen_norm != na::Vector2::zeros() {
       let translation_canvas =
@@ -1,3 +1,5 @@
+use crate::math::Rect;
+#[cfg(feature = "app")]
 use crate::{
   content::{
     access::{ContentAccess, StrokeDelta},
@@ -7,14 +9,17 @@ use crate::{
 };
 
 use palette::LinSrgba;
+#[cfg(feature = "app")]
 use std::collections::HashMap;
 
+#[cfg(feature = "app")]
 #[derive(Default)]
 pub struct StrokeManager {
   data: StrokeData,
   tessellator: StrokeTessellator,
 }
 
+#[cfg(feature = "app")]
 impl StrokeManager {
   pub fn data(&self) -> &StrokeData {
     &self.data
@@ -56,9 +61,21 @@ impl StrokeManager {
       self.data.parry_meshes.remove(stroke_id);
     }
   }
+
+  /// Tessellates all strokes of the content anew, dropping the meshes of any other strokes.
+  #[cfg(not(target_arch = "wasm32"))]
+  pub fn rebuild_strokes(&mut self, content: ContentAccess, device: &wgpu::Device) {
+    self.data = StrokeData::default();
+    let stroke_delta = StrokeDelta {
+      added: content.strokes().map(|(id, _)| id).collect(),
+      ..Default::default()
+    };
+    self.update_strokes(content, &stroke_delta, device);
+  }
 }
 
 // TODO: consider using a BTreeMap instead of a HashMap
+#[cfg(feature = "app")]
 #[derive(Default)]
 pub struct StrokeData {
   pub meshes: HashMap<StrokeId, StrokeMeshGpu>,
@@ -68,8 +85,10 @@ pub struct StrokeData {
 #[derive(Clone, serde::Serialize, serde::Deserialize)]
 pub struct Stroke {
   /// at least two points
+  #[serde(with = "crate::file::serde_points")]
   points_canvas: Vec<na::Point2<f32>>,
   width_multiplier: f32,
+  #[serde(with = "crate::util::serde_color")]
   color: palette::LinSrgba,
 }
 impl Stroke {
@@ -90,6 +109,12 @@ impl Stroke {
     self.points_canvas.push(point);
   }
 
+  pub fn translate(&mut self, translation: na::Vector2<f32>) {
+    for point in &mut self.points_canvas {
+      *point += translation;
+    }
+  }
+
   pub fn points(&self) -> &[na::Point2<f32>] {
     &self.points_canvas
   }
@@ -101,4 +126,12 @@ impl Stroke {
   pub fn color(&self) -> LinSrgba {
     self.color
   }
+
+  /// axis aligned bounds of the points, without the width.
+  pub fn bounds_canvas(&self) -> Rect {
+    let points = &self.points_canvas;
+    let min = points.iter().fold(points[0], |a, b| a.inf(b));
+    let max = points.iter().fold(points[0], |a, b| a.sup(b));
+    Rect::from_size_min(max - min, min)
+  }
 }
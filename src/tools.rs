@@ -1,4 +1,7 @@
 mod eraser;
+mod highlighter;
+mod link_follower;
+mod page_tool;
 mod pen;
 mod rotate_tool;
 mod select_loop;
@@ -7,12 +10,14 @@ mod zoom_tool;
 
 pub use self::select_loop::SelectLoop;
 use self::{
-  eraser::update_eraser, pen::Pen, rotate_tool::update_rotate_tool,
+  eraser::update_eraser, highlighter::Highlighter, link_follower::LinkFollower,
+  page_tool::PageTool, pen::Pen, rotate_tool::update_rotate_tool,
   translate_tool::update_translate_tool, zoom_tool::update_zoom_tool,
 };
 
 use crate::{
-  content::ContentManager, input::InputManager, spaces::SpaceManager, stroke::StrokeManager,
+  content::ContentManager, input::InputManager, pdf::PdfManager, spaces::SpaceManager,
+  stroke::StrokeManager,
 };
 
 #[derive(Default)]
@@ -22,9 +27,12 @@ pub struct ToolManager {
 
   pub pen: Pen,
   pub select_loop: SelectLoop,
+  pub page_tool: PageTool,
+  pub highlighter: Highlighter,
+  pub link_follower: LinkFollower,
 }
 
-#[derive(Default, Clone, Copy, PartialEq, Eq)]
+#[derive(Default, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub enum ToolEnum {
   #[default]
   Pen,
@@ -35,16 +43,29 @@ pub enum ToolEnum {
   Translate,
   Rotate,
   Zoom,
+
+  Page,
+  Highlighter,
+}
+
+impl ToolEnum {
+  /// tools that only move the view, with which pdf links can be followed.
+  pub fn is_navigation(&self) -> bool {
+    matches!(self, Self::Translate | Self::Rotate | Self::Zoom)
+  }
 }
 
 #[derive(Default)]
 pub struct ToolConfigs {
   pub pen: PenConfig,
+  pub select_loop: SelectLoopConfig,
+  pub highlighter: HighlighterConfig,
 }
 
-#[derive(Clone)]
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
 pub struct PenConfig {
   pub width: f32,
+  #[serde(with = "crate::util::serde_color")]
   pub color: palette::LinSrgba,
 }
 impl Default for PenConfig {
@@ -56,6 +77,34 @@ impl Default for PenConfig {
   }
 }
 
+#[derive(Clone)]
+pub struct SelectLoopConfig {
+  /// without it the selection is kept, e.g. for exporting
+  pub erase_selection: bool,
+}
+impl Default for SelectLoopConfig {
+  fn default() -> Self {
+    Self {
+      erase_selection: true,
+    }
+  }
+}
+
+#[derive(Clone)]
+pub struct HighlighterConfig {
+  pub color: palette::LinSrgba,
+  /// without it the highlighter only selects text, e.g. for copying
+  pub highlight_on_release: bool,
+}
+impl Default for HighlighterConfig {
+  fn default() -> Self {
+    Self {
+      color: palette::LinSrgba::new(1.0, 0.85, 0.0, 0.35),
+      highlight_on_release: true,
+    }
+  }
+}
+
 impl ToolManager {
   pub fn update(
     &mut self,
@@ -63,20 +112,38 @@ impl ToolManager {
     input: &InputManager,
     content_manager: &mut ContentManager,
     stroke_manager: &StrokeManager,
+    pdf_manager: &mut PdfManager,
   ) {
     match self.selected {
       ToolEnum::Pen => self
         .pen
         .update(input, content_manager, &self.configs.pen, spaces),
-      ToolEnum::Eraser => update_eraser(input, content_manager, stroke_manager, spaces),
-      ToolEnum::SelectLoop => {
-        self
-          .select_loop
-          .update(spaces, input, content_manager, stroke_manager)
+      ToolEnum::Eraser => {
+        update_eraser(input, content_manager, stroke_manager, spaces, pdf_manager)
       }
+      ToolEnum::SelectLoop => self.select_loop.update(
+        spaces,
+        input,
+        content_manager,
+        stroke_manager,
+        &self.configs.select_loop,
+      ),
       ToolEnum::Translate => update_translate_tool(input, spaces),
       ToolEnum::Rotate => update_rotate_tool(input, spaces),
       ToolEnum::Zoom => update_zoom_tool(input, spaces),
+      ToolEnum::Page => self
+        .page_tool
+        .update(input, spaces, content_manager, pdf_manager),
+      ToolEnum::Highlighter => self.highlighter.update(
+        input,
+        spaces,
+        content_manager,
+        &self.configs.highlighter,
+        pdf_manager,
+      ),
+    }
+    if self.selected.is_navigation() {
+      self.link_follower.update(input, spaces, pdf_manager);
     }
   }
 }
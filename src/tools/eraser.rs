@@ -1,6 +1,10 @@
 use crate::{
-  content::{command::RemoveStrokesCommand, ContentManager, StrokeId},
+  content::{
+    command::{RemoveHighlightCommand, RemoveStrokesCommand},
+    ContentManager, HighlightId, StrokeId,
+  },
   input::InputManager,
+  pdf::PdfManager,
   spaces::{Space, SpaceManager},
   stroke::StrokeManager,
 };
@@ -12,6 +16,7 @@ pub fn update_eraser(
   content_manager: &mut ContentManager,
   stroke_manager: &StrokeManager,
   spaces: &SpaceManager,
+  pdf_manager: &PdfManager,
 ) {
   if !input.is_clicked(winit::event::MouseButton::Left) {
     return;
@@ -34,5 +39,28 @@ pub fn update_eraser(
     for id in remove_list {
       content_manager.run_cmd(RemoveStrokesCommand::single(id))
     }
+
+    // highlights are erased as a whole, like strokes
+    let page_rects_canvas = pdf_manager.page_rects_canvas();
+    let remove_list: Vec<HighlightId> = content_manager
+      .access()
+      .highlights()
+      .filter(|(_, highlight)| {
+        pdf_manager
+          .page_index(highlight.page)
+          .and_then(|page| page_rects_canvas.get(page))
+          .map_or(false, |page_rect_canvas| {
+            highlight
+              .rects_canvas(page_rect_canvas)
+              .iter()
+              .any(|rect| rect.contains_point(pos_canvas))
+          })
+      })
+      .map(|(id, _)| id)
+      .collect();
+
+    for id in remove_list {
+      content_manager.run_cmd(RemoveHighlightCommand::new(id))
+    }
   }
 }
@@ -0,0 +1,108 @@
+use crate::{
+  content::{command::AddHighlightCommand, ContentManager},
+  input::InputManager,
+  pdf::{canvas_to_page_norm_point, PdfHighlight, PdfManager},
+  spaces::{Space, SpaceManager},
+  tools::HighlighterConfig,
+};
+
+use std::ops::Range;
+use winit::event::MouseButton;
+
+/// Selects pdf text by dragging over it and highlights the selected chars.
+#[derive(Default)]
+pub struct Highlighter {
+  selection: Option<TextSelection>,
+  is_dragging: bool,
+}
+
+/// A range of chars on a page, between the char the drag started on and the current one.
+#[derive(Clone, Copy)]
+struct TextSelection {
+  page: usize,
+  anchor: usize,
+  head: usize,
+}
+
+impl TextSelection {
+  fn range(&self) -> Range<usize> {
+    self.anchor.min(self.head)..self.anchor.max(self.head) + 1
+  }
+}
+
+impl Highlighter {
+  pub fn update(
+    &mut self,
+    input: &InputManager,
+    spaces: &SpaceManager,
+    content_manager: &mut ContentManager,
+    config: &HighlighterConfig,
+    pdf_manager: &mut PdfManager,
+  ) {
+    let Some(cursor_screen_logical) = input.curr.cursor_pos_screen_logical else {
+      return;
+    };
+    let cursor_canvas =
+      spaces.transform_point(cursor_screen_logical, Space::ScreenLogical, Space::Canvas);
+    let page_rects_canvas = pdf_manager.page_rects_canvas();
+    let hovered_page = pdf_manager.page_at(cursor_canvas);
+    if let Some(page) = hovered_page {
+      // requests the text while hovering, so it is there once the drag starts
+      pdf_manager.page_text(page);
+    }
+
+    if input.got_clicked(MouseButton::Left) {
+      self.selection = hovered_page.and_then(|page| {
+        let point_page_norm = canvas_to_page_norm_point(&page_rects_canvas[page], cursor_canvas);
+        let char = pdf_manager.page_text(page)?.char_at(point_page_norm)?;
+        Some(TextSelection {
+          page,
+          anchor: char,
+          head: char,
+        })
+      });
+      self.is_dragging = self.selection.is_some();
+    } else if self.is_dragging && input.is_clicked(MouseButton::Left) {
+      let Some(selection) = &mut self.selection else {
+        return;
+      };
+      let Some(page_rect_canvas) = page_rects_canvas.get(selection.page) else {
+        return;
+      };
+      let point_page_norm = canvas_to_page_norm_point(page_rect_canvas, cursor_canvas);
+      if let Some(char) = pdf_manager
+        .page_text(selection.page)
+        .and_then(|text| text.nearest_char(point_page_norm))
+      {
+        selection.head = char;
+      }
+    }
+
+    if self.is_dragging && input.got_unclicked(MouseButton::Left) {
+      self.is_dragging = false;
+      if config.highlight_on_release {
+        if let Some(highlight) = self.selected_highlight(pdf_manager, config.color) {
+          content_manager.run_cmd(AddHighlightCommand::new(highlight));
+        }
+      }
+    }
+  }
+
+  /// The selected chars as a highlight, `None` if there is no selection or the text is missing.
+  pub fn selected_highlight(
+    &self,
+    pdf_manager: &mut PdfManager,
+    color: palette::LinSrgba,
+  ) -> Option<PdfHighlight> {
+    let selection = self.selection?;
+    let range = selection.range();
+    let page = pdf_manager.page_id(selection.page)?;
+    let text = pdf_manager.page_text(selection.page)?;
+    Some(PdfHighlight {
+      page,
+      rects_page_norm: text.rects(range.clone()),
+      text: text.text(range).trim().to_owned(),
+      color,
+    })
+  }
+}
@@ -0,0 +1,58 @@
+use crate::{
+  input::InputManager,
+  pdf::{navigation, PdfLinkTarget, PdfManager},
+  spaces::{Space, SpaceManager},
+};
+
+use winit::event::MouseButton;
+
+/// how far the cursor may move between press and release for a click, in logical pixels
+const CLICK_TOLERANCE: f32 = 4.0;
+
+/// Follows the pdf links clicked with a navigation tool.
+#[derive(Default)]
+pub struct LinkFollower {
+  /// where the button went down, to tell clicks from drags
+  pressed_screen_logical: Option<na::Point2<f32>>,
+  opened_url: Option<String>,
+}
+
+impl LinkFollower {
+  pub fn update(
+    &mut self,
+    input: &InputManager,
+    spaces: &mut SpaceManager,
+    pdf_manager: &PdfManager,
+  ) {
+    if input.got_clicked(MouseButton::Left) {
+      self.pressed_screen_logical = input.curr.cursor_pos_screen_logical;
+    }
+    if !input.got_unclicked(MouseButton::Left) {
+      return;
+    }
+    let (Some(pressed_screen_logical), Some(cursor_screen_logical)) = (
+      self.pressed_screen_logical.take(),
+      input.curr.cursor_pos_screen_logical,
+    ) else {
+      return;
+    };
+    if (cursor_screen_logical - pressed_screen_logical).magnitude() > CLICK_TOLERANCE {
+      return;
+    }
+
+    let cursor_canvas =
+      spaces.transform_point(cursor_screen_logical, Space::ScreenLogical, Space::Canvas);
+    let Some(target) = pdf_manager.link_at(cursor_canvas) else {
+      return;
+    };
+    match target {
+      PdfLinkTarget::Uri(url) => self.opened_url = Some(url),
+      target @ PdfLinkTarget::Page { .. } => navigation::follow_link(pdf_manager, spaces, &target),
+    }
+  }
+
+  /// The external url last clicked, for the ui to hand to the platform.
+  pub fn take_opened_url(&mut self) -> Option<String> {
+    self.opened_url.take()
+  }
+}
@@ -0,0 +1,52 @@
+use crate::{
+  content::{command::ArrangePagesCommand, ContentManager},
+  input::InputManager,
+  pdf::PdfManager,
+  spaces::{Space, SpaceManager},
+};
+
+use winit::event::MouseButton;
+
+/// Drags pdf pages around, which switches to the free layout.
+/// The strokes on the page follow once it is dropped.
+#[derive(Default)]
+pub struct PageTool {
+  dragged_page: Option<usize>,
+}
+
+impl PageTool {
+  pub fn update(
+    &mut self,
+    input: &InputManager,
+    spaces: &SpaceManager,
+    content_manager: &mut ContentManager,
+    pdf_manager: &mut PdfManager,
+  ) {
+    // while dragging only the layout of the pdf manager changes
+    if input.got_unclicked(MouseButton::Left) && self.dragged_page.take().is_some() {
+      let layout = pdf_manager.layout().clone();
+      if layout != *content_manager.access().layout() {
+        let pages = content_manager.access().pages().clone();
+        let command =
+          ArrangePagesCommand::new(content_manager.access(), pages, layout, pdf_manager);
+        content_manager.run_cmd(command);
+      }
+    }
+    if input.got_clicked(MouseButton::Left) {
+      if let Some(cursor_screen_logical) = input.curr.cursor_pos_screen_logical {
+        let cursor_canvas =
+          spaces.transform_point(cursor_screen_logical, Space::ScreenLogical, Space::Canvas);
+        self.dragged_page = pdf_manager.page_at(cursor_canvas);
+      }
+    }
+
+    if let Some(page) = self.dragged_page {
+      if let Some(cursor_diff) = input.cursor_screen_logical_difference() {
+        let cursor_diff = spaces.transform_vector(cursor_diff, Space::ScreenLogical, Space::Canvas);
+        if cursor_diff != na::Vector2::zeros() {
+          pdf_manager.move_page(page, cursor_diff);
+        }
+      }
+    }
+  }
+}
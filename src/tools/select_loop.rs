@@ -3,6 +3,7 @@ use crate::{
   input::InputManager,
   spaces::{Space, SpaceManager},
   stroke::StrokeManager,
+  tools::SelectLoopConfig,
 };
 
 use parry2d::{
@@ -31,10 +32,12 @@ impl SelectLoop {
     input: &InputManager,
     content_manager: &mut ContentManager,
     stroke_manager: &StrokeManager,
+    config: &SelectLoopConfig,
   ) {
     if input.got_clicked(MouseButton::Left) {
       match self {
-        SelectLoop::Inactive => {
+        // a new loop replaces the kept selection
+        SelectLoop::Inactive | SelectLoop::Selected { .. } => {
           if let Some(point) = input.curr.cursor_pos_screen_logical {
             let points_screen_logical = vec![point];
             *self = SelectLoop::Selecting {
@@ -67,6 +70,9 @@ impl SelectLoop {
       }
     }
 
+    if !config.erase_selection {
+      return;
+    }
     match mem::replace(self, SelectLoop::Invalid) {
       SelectLoop::Selected {
         selected_strokes, ..
@@ -78,6 +84,14 @@ impl SelectLoop {
     }
   }
 
+  /// the strokes of the kept selection, empty if there is none
+  pub fn selected_strokes(&self) -> &[StrokeId] {
+    match self {
+      SelectLoop::Selected { selected_strokes } => selected_strokes,
+      _ => &[],
+    }
+  }
+
   fn get_selection(
     points_screen_logical: Vec<na::Point2<f32>>,
     stroke_manager: &StrokeManager,
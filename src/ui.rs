@@ -1,30 +1,60 @@
 pub mod canvas;
-mod overlay;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod file_drop;
+pub mod overlay;
+mod pdf_panel;
+#[cfg(not(target_arch = "wasm32"))]
+mod recovery;
 mod sidebar;
 
-use self::{canvas::CanvasUi, sidebar::SidebarUi};
+use self::{canvas::CanvasUi, pdf_panel::PdfPanelUi, sidebar::SidebarUi};
+#[cfg(not(target_arch = "wasm32"))]
+use self::{file_drop::FileDropUi, recovery::RecoveryUi};
 
 #[derive(Default)]
 pub struct Ui {
   sidebar: SidebarUi,
+  pdf_panel: PdfPanelUi,
   canvas: CanvasUi,
+  #[cfg(not(target_arch = "wasm32"))]
+  recovery: RecoveryUi,
+  #[cfg(not(target_arch = "wasm32"))]
+  file_drop: FileDropUi,
 }
 
 impl Ui {
   pub fn run(&mut self, ctx: &egui::Context, mut ui_access: UiAccess) {
+    #[cfg(not(target_arch = "wasm32"))]
+    self.recovery.ui(ctx, &mut ui_access);
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+      let dropped = self.file_drop.take_dropped();
+      if !dropped.is_empty() {
+        self.sidebar.open_dropped_files(&mut ui_access, dropped);
+      }
+    }
     self.sidebar.ui(ctx, &mut ui_access);
+    self.pdf_panel.ui(ctx, &mut ui_access);
     self.canvas.ui(ctx, &mut ui_access);
+    #[cfg(not(target_arch = "wasm32"))]
+    self.file_drop.ui(ctx, self.canvas.screen_rect());
   }
 
   pub fn canvas(&self) -> &CanvasUi {
     &self.canvas
   }
+
+  #[cfg(not(target_arch = "wasm32"))]
+  pub fn file_drop_mut(&mut self) -> &mut FileDropUi {
+    &mut self.file_drop
+  }
 }
 
 pub struct UiAccess<'a> {
-  pub spaces: &'a mut crate::SpaceManager,
-  pub content_manager: &'a mut crate::ContentManager,
-  pub tool_manager: &'a mut crate::ToolManager,
-  pub pdf_manager: &'a mut Option<crate::PdfManager>,
-  pub stroke_manager: &'a mut crate::StrokeManager,
+  pub spaces: &'a mut crate::spaces::SpaceManager,
+  pub content_manager: &'a mut crate::content::ContentManager,
+  pub tool_manager: &'a mut crate::tools::ToolManager,
+  pub pdf_manager: &'a mut crate::pdf::PdfManager,
+  pub stroke_manager: &'a mut crate::stroke::StrokeManager,
+  pub session: &'a mut crate::session::Session,
 }
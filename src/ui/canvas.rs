@@ -1,4 +1,7 @@
-use super::{overlay::ui_overlay, UiAccess};
+use super::{
+  overlay::{ui_overlay, OverlayAccess},
+  UiAccess,
+};
 
 pub struct CanvasUi {
   screen_rect: egui::Rect,
@@ -22,7 +25,23 @@ impl CanvasUi {
         let response = ui.allocate_rect(self.screen_rect, egui::Sense::hover());
         self.has_focus = response.hovered();
 
-        ui_overlay(ctx, ui_access, self.screen_rect);
+        let layer_id =
+          egui::LayerId::new(egui::Order::Background, egui::Id::new("canvas_ui_overlay"));
+        let overlay_ui = egui::Ui::new(
+          ctx.clone(),
+          layer_id,
+          layer_id.id,
+          self.screen_rect,
+          self.screen_rect,
+        );
+        let mut overlay_access = OverlayAccess {
+          spaces: ui_access.spaces,
+          content_manager: ui_access.content_manager,
+          tool_manager: ui_access.tool_manager,
+          pdf_manager: ui_access.pdf_manager,
+          stroke_manager: ui_access.stroke_manager,
+        };
+        ui_overlay(&overlay_ui, &mut overlay_access, self.screen_rect);
       });
   }
 
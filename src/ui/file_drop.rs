@@ -0,0 +1,97 @@
+use std::path::{Path, PathBuf};
+
+/// A file dropped onto the window.
+pub struct DroppedFile {
+  pub path: PathBuf,
+  /// `None` if the cursor was outside the canvas or not reported while dragging
+  pub cursor_pos_screen_logical: Option<na::Point2<f32>>,
+}
+
+/// What dropping a file does, by its extension.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum DropAction {
+  Open,
+  /// the pdf replaces the pages
+  LoadPdf,
+  /// ink and svg drawings are added where they are dropped, notebooks replace the document
+  Import,
+  /// raster images become a page of their own where they are dropped
+  Image,
+  Unsupported,
+}
+
+impl DropAction {
+  pub fn of(path: &Path) -> Self {
+    let extension = path
+      .extension()
+      .map(|extension| extension.to_string_lossy().to_lowercase());
+    match extension.as_deref() {
+      Some("co") => Self::Open,
+      Some("pdf") => Self::LoadPdf,
+      Some("svg" | "inkml" | "ink" | "xopp") => Self::Import,
+      Some("png" | "jpg" | "jpeg" | "gif" | "bmp" | "webp" | "tif" | "tiff") => Self::Image,
+      _ => Self::Unsupported,
+    }
+  }
+
+  pub fn describe(&self, path: &Path) -> String {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    match self {
+      DropAction::Open => format!("Open {name}"),
+      DropAction::LoadPdf => format!("Load {name} as background"),
+      DropAction::Import => format!("Import {name}"),
+      DropAction::Image => format!("Add {name} as a page"),
+      DropAction::Unsupported => format!("{name} can not be opened"),
+    }
+  }
+}
+
+/// Collects the files dragged onto the window until the ui opens them.
+#[derive(Default)]
+pub struct FileDropUi {
+  hovered: Vec<PathBuf>,
+  dropped: Vec<DroppedFile>,
+}
+
+impl FileDropUi {
+  pub fn hover(&mut self, path: PathBuf) {
+    self.hovered.push(path);
+  }
+
+  pub fn cancel_hover(&mut self) {
+    self.hovered.clear();
+  }
+
+  pub fn drop_file(&mut self, file: DroppedFile) {
+    self.hovered.retain(|path| *path != file.path);
+    self.dropped.push(file);
+  }
+
+  pub fn take_dropped(&mut self) -> Vec<DroppedFile> {
+    std::mem::take(&mut self.dropped)
+  }
+
+  /// Tells over the canvas what dropping the hovered files does.
+  pub fn ui(&self, ctx: &egui::Context, screen_rect: egui::Rect) {
+    if self.hovered.is_empty() {
+      return;
+    }
+    let layer_id = egui::LayerId::new(egui::Order::Foreground, egui::Id::new("file_drop_ui"));
+    let painter = ctx.layer_painter(layer_id);
+    painter.rect_filled(screen_rect, 0.0, egui::Color32::from_black_alpha(160));
+
+    let text = self
+      .hovered
+      .iter()
+      .map(|path| DropAction::of(path).describe(path))
+      .collect::<Vec<_>>()
+      .join("\n");
+    painter.text(
+      screen_rect.center(),
+      egui::Align2::CENTER_CENTER,
+      text,
+      egui::FontId::proportional(20.0),
+      egui::Color32::WHITE,
+    );
+  }
+}
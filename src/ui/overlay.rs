@@ -1,14 +1,30 @@
+mod highlights;
 mod indicators;
+mod links;
+mod missing_pages;
+mod search_hits;
 mod select_loop;
 
-use self::{indicators::ui_indicators, select_loop::ui_select_loop};
+use self::{
+  highlights::ui_highlights, indicators::ui_indicators, links::ui_links,
+  missing_pages::ui_missing_pages, search_hits::ui_search_hits, select_loop::ui_select_loop,
+};
 
-use super::UiAccess;
-
-pub fn ui_overlay(ctx: &egui::Context, ui_access: &mut UiAccess, screen_rect: egui::Rect) {
-  let layer_id = egui::LayerId::new(egui::Order::Background, egui::Id::new("canvas_ui_overlay"));
-  let ui = egui::Ui::new(ctx.clone(), layer_id, layer_id.id, screen_rect, screen_rect);
+/// The part of [`super::UiAccess`] the overlay needs, which the canvas widget has as well.
+pub struct OverlayAccess<'a> {
+  pub spaces: &'a mut crate::spaces::SpaceManager,
+  pub content_manager: &'a mut crate::content::ContentManager,
+  pub tool_manager: &'a mut crate::tools::ToolManager,
+  pub pdf_manager: &'a mut crate::pdf::PdfManager,
+  pub stroke_manager: &'a crate::stroke::StrokeManager,
+}
 
-  ui_select_loop(&ui, ui_access);
-  ui_indicators(&ui, ui_access, screen_rect);
+/// Draws over the canvas, which covers `screen_rect` of `ui`.
+pub fn ui_overlay(ui: &egui::Ui, ui_access: &mut OverlayAccess, screen_rect: egui::Rect) {
+  ui_missing_pages(ui, ui_access);
+  ui_highlights(ui, ui_access);
+  ui_search_hits(ui, ui_access);
+  ui_links(ui, ui_access);
+  ui_select_loop(ui, ui_access);
+  ui_indicators(ui, ui_access, screen_rect);
 }
@@ -0,0 +1,77 @@
+use super::OverlayAccess;
+
+use crate::{math::Rect, spaces::Space, tools::ToolEnum};
+
+/// Draws the pdf highlights and the text selection of the highlighter, which can be copied.
+pub fn ui_highlights(ui: &egui::Ui, ui_access: &mut OverlayAccess) {
+  let page_rects_canvas = ui_access.pdf_manager.page_rects_canvas();
+  let painter = ui.painter();
+  let draw_rect = |rect_canvas: Rect, color: egui::Color32| {
+    let points = rect_canvas
+      .vertices()
+      .map(|p| {
+        let p = ui_access
+          .spaces
+          .transform_point(p, Space::Canvas, Space::ScreenLogical);
+        let p = ui_access
+          .spaces
+          .transform_point(p, Space::ScreenLogical, Space::WindowLogical);
+        egui::Pos2::new(p.x, p.y)
+      })
+      .to_vec();
+    painter.add(egui::Shape::convex_polygon(
+      points,
+      color,
+      egui::Stroke::NONE,
+    ));
+  };
+
+  for (_, highlight) in ui_access.content_manager.access().highlights() {
+    let Some(page_rect_canvas) = ui_access
+      .pdf_manager
+      .page_index(highlight.page)
+      .and_then(|page| page_rects_canvas.get(page))
+    else {
+      continue;
+    };
+    let (r, g, b, a) = highlight.color.into_components();
+    let color = egui::Rgba::from_rgba_unmultiplied(r, g, b, a).into();
+    for rect_canvas in highlight.rects_canvas(page_rect_canvas) {
+      draw_rect(rect_canvas, color);
+    }
+  }
+
+  let tool_manager = &ui_access.tool_manager;
+  if tool_manager.selected != ToolEnum::Highlighter {
+    return;
+  }
+  let Some(selection) = tool_manager
+    .highlighter
+    .selected_highlight(ui_access.pdf_manager, tool_manager.configs.highlighter.color)
+  else {
+    return;
+  };
+  let Some(page_rect_canvas) = ui_access
+    .pdf_manager
+    .page_index(selection.page)
+    .and_then(|page| page_rects_canvas.get(page))
+  else {
+    return;
+  };
+  let color = egui::Color32::from_rgba_unmultiplied(80, 150, 255, 70);
+  for rect_canvas in selection.rects_canvas(page_rect_canvas) {
+    draw_rect(rect_canvas, color);
+  }
+
+  let is_copied = ui.input(|input| {
+    input
+      .events
+      .iter()
+      .any(|event| matches!(event, egui::Event::Copy))
+  });
+  let is_text_focused = ui.memory(|memory| memory.focus().is_some());
+  if is_copied && !is_text_focused {
+    ui.ctx()
+      .output_mut(|output| output.copied_text = selection.text);
+  }
+}
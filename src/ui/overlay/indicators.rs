@@ -1,6 +1,6 @@
-use crate::ui::UiAccess;
+use super::OverlayAccess;
 
-pub fn ui_indicators(ui: &egui::Ui, ui_access: &mut UiAccess, screen: egui::Rect) {
+pub fn ui_indicators(ui: &egui::Ui, ui_access: &mut OverlayAccess, screen: egui::Rect) {
   let painter = ui.painter();
 
   let width = 3.0;
@@ -0,0 +1,31 @@
+use super::OverlayAccess;
+
+use crate::spaces::Space;
+
+/// Shows that pdf links are clickable and opens external ones.
+pub fn ui_links(ui: &egui::Ui, ui_access: &mut OverlayAccess) {
+  if let Some(url) = ui_access.tool_manager.link_follower.take_opened_url() {
+    ui.ctx()
+      .output_mut(|output| output.open_url = Some(egui::output::OpenUrl::new_tab(url)));
+  }
+
+  if !ui_access.tool_manager.selected.is_navigation() {
+    return;
+  }
+  let Some(hover_pos) = ui.ctx().pointer_hover_pos() else {
+    return;
+  };
+  let spaces = &ui_access.spaces;
+  let cursor_screen_logical = spaces.transform_point(
+    na::point![hover_pos.x, hover_pos.y],
+    Space::WindowLogical,
+    Space::ScreenLogical,
+  );
+  let cursor_canvas =
+    spaces.transform_point(cursor_screen_logical, Space::ScreenLogical, Space::Canvas);
+  if ui.rect_contains_pointer(ui.max_rect())
+    && ui_access.pdf_manager.link_at(cursor_canvas).is_some()
+  {
+    ui.ctx().set_cursor_icon(egui::CursorIcon::PointingHand);
+  }
+}
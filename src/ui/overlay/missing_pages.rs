@@ -0,0 +1,39 @@
+use super::OverlayAccess;
+
+use crate::{pdf::source_name, spaces::Space};
+
+/// Outlines the pages whose pdf failed to load, in place of their content.
+pub fn ui_missing_pages(ui: &egui::Ui, ui_access: &mut OverlayAccess) {
+  let pdf_manager = &ui_access.pdf_manager;
+  let painter = ui.painter();
+  let stroke = egui::Stroke::new(1.0, egui::Color32::from_rgb(200, 60, 60));
+  for (page, rect_canvas) in pdf_manager.page_rects_canvas().iter().enumerate() {
+    let Some(source) = pdf_manager.missing_source(page) else {
+      continue;
+    };
+    let points = rect_canvas
+      .vertices()
+      .map(|p| {
+        let p = ui_access
+          .spaces
+          .transform_point(p, Space::Canvas, Space::ScreenLogical);
+        let p = ui_access
+          .spaces
+          .transform_point(p, Space::ScreenLogical, Space::WindowLogical);
+        egui::Pos2::new(p.x, p.y)
+      })
+      .to_vec();
+    let center = points
+      .iter()
+      .fold(egui::Vec2::ZERO, |sum, p| sum + p.to_vec2())
+      / points.len() as f32;
+    painter.add(egui::Shape::closed_line(points, stroke));
+    painter.text(
+      center.to_pos2(),
+      egui::Align2::CENTER_CENTER,
+      format!("{} is missing", source_name(source)),
+      egui::FontId::proportional(14.0),
+      stroke.color,
+    );
+  }
+}
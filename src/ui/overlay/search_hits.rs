@@ -0,0 +1,43 @@
+use super::OverlayAccess;
+
+use crate::spaces::Space;
+
+pub fn ui_search_hits(ui: &egui::Ui, ui_access: &mut OverlayAccess) {
+  let pdf_manager = &ui_access.pdf_manager;
+  let Some(results) = pdf_manager.search_results() else {
+    return;
+  };
+
+  let page_rects_canvas = pdf_manager.page_rects_canvas();
+  let painter = ui.painter();
+  let color = egui::Color32::from_rgba_unmultiplied(255, 220, 0, 80);
+  let selected_color = egui::Color32::from_rgba_unmultiplied(255, 120, 0, 120);
+  for (ihit, hit) in results.hits.iter().enumerate() {
+    let color = match pdf_manager.selected_search_hit() == Some(ihit) {
+      true => selected_color,
+      false => color,
+    };
+    let Some(page_rect_canvas) = page_rects_canvas.get(hit.page) else {
+      continue;
+    };
+    for rect_canvas in hit.rects_canvas(page_rect_canvas) {
+      let points = rect_canvas
+        .vertices()
+        .map(|p| {
+          let p = ui_access
+            .spaces
+            .transform_point(p, Space::Canvas, Space::ScreenLogical);
+          let p = ui_access
+            .spaces
+            .transform_point(p, Space::ScreenLogical, Space::WindowLogical);
+          egui::Pos2::new(p.x, p.y)
+        })
+        .to_vec();
+      painter.add(egui::Shape::convex_polygon(
+        points,
+        color,
+        egui::Stroke::NONE,
+      ));
+    }
+  }
+}
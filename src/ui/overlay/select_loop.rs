@@ -1,10 +1,18 @@
-use crate::{spaces::Space, tools::SelectLoop, ui::UiAccess};
+use super::OverlayAccess;
 
-pub fn ui_select_loop(ui: &egui::Ui, ui_access: &mut UiAccess) {
+use crate::{content::StrokeId, math::Rect, spaces::Space, tools::SelectLoop};
+
+use parry2d::bounding_volume::BoundingVolume;
+
+pub fn ui_select_loop(ui: &egui::Ui, ui_access: &mut OverlayAccess) {
   let points_screen_logical = match &ui_access.tool_manager.select_loop {
     SelectLoop::Selecting {
       points_screen_logical,
     } => points_screen_logical,
+    SelectLoop::Selected { selected_strokes } => {
+      ui_selection_bounds(ui, ui_access, selected_strokes);
+      return;
+    }
     _ => return,
   };
 
@@ -22,3 +30,31 @@ pub fn ui_select_loop(ui: &egui::Ui, ui_access: &mut UiAccess) {
   let line = egui::Shape::line(screen_points, stroke);
   painter.add(line);
 }
+
+/// outlines the bounds of the kept selection
+fn ui_selection_bounds(ui: &egui::Ui, ui_access: &OverlayAccess, selected_strokes: &[StrokeId]) {
+  let meshes = &ui_access.stroke_manager.data().parry_meshes;
+  let Some(aabb) = selected_strokes
+    .iter()
+    .filter_map(|id| meshes.get(id))
+    .map(|mesh| *mesh.local_aabb())
+    .reduce(|a, b| a.merged(&b))
+  else {
+    return;
+  };
+  let rect_canvas = Rect::from_size_min(aabb.extents(), aabb.mins);
+  let points = rect_canvas
+    .vertices()
+    .map(|p| {
+      let p = ui_access
+        .spaces
+        .transform_point(p, Space::Canvas, Space::ScreenLogical);
+      let p = ui_access
+        .spaces
+        .transform_point(p, Space::ScreenLogical, Space::WindowLogical);
+      egui::Pos2::new(p.x, p.y)
+    })
+    .to_vec();
+  let stroke = egui::Stroke::new(1.5, egui::Color32::DARK_BLUE);
+  ui.painter().add(egui::Shape::closed_line(points, stroke));
+}
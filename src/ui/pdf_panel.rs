@@ -0,0 +1,269 @@
+use super::UiAccess;
+
+use crate::pdf::{navigation, PdfDocumentId, PdfOutlineItem, PdfPageInfo};
+
+use std::collections::{HashMap, HashSet};
+
+/// the box the page thumbnails are fitted into, in logical pixels
+const THUMBNAIL_BOX_SIZE: egui::Vec2 = egui::vec2(96.0, 128.0);
+
+/// Outline, thumbnails and page navigation of the pages.
+#[derive(Default)]
+pub struct PdfPanelUi {
+  /// keyed by the document and the page in it, which stay valid when the page list changes
+  thumbnails: HashMap<(PdfDocumentId, usize), egui::TextureHandle>,
+  requested_thumbnails: HashSet<(PdfDocumentId, usize)>,
+  /// 1-based like the page numbers shown to the user
+  goto_page: usize,
+  search_query: String,
+  /// jump to the first hit once the results arrive
+  awaiting_search_results: bool,
+}
+
+impl PdfPanelUi {
+  pub fn ui(&mut self, ctx: &egui::Context, ui_access: &mut UiAccess) {
+    let npages = ui_access.pdf_manager.npages();
+    if npages == 0 {
+      return;
+    }
+    self.goto_page = self.goto_page.clamp(1, npages);
+
+    for thumbnail in ui_access.pdf_manager.receive_thumbnails() {
+      let image = egui::ColorImage::from_rgba_unmultiplied(thumbnail.size, &thumbnail.data);
+      let texture = ctx.load_texture(
+        format!("pdf_thumbnail_{:?}_{}", thumbnail.document, thumbnail.page),
+        image,
+        egui::TextureOptions::LINEAR,
+      );
+      self
+        .thumbnails
+        .insert((thumbnail.document, thumbnail.page), texture);
+    }
+
+    if self.awaiting_search_results && !ui_access.pdf_manager.is_searching() {
+      self.awaiting_search_results = false;
+      navigation::go_to_search_hit(ui_access.pdf_manager, ui_access.spaces, 0);
+    }
+    if ui_access.pdf_manager.is_searching() {
+      // the results arrive without any user input
+      ctx.request_repaint();
+    }
+
+    let pdf_manager = &*ui_access.pdf_manager;
+    let current_page = navigation::current_page(pdf_manager, ui_access.spaces);
+    let mut target_page = None;
+    let mut target_hit = None;
+    let mut submitted_query = None;
+    let mut clear_search = false;
+
+    egui::SidePanel::right("pdf_panel").show(ctx, |ui| {
+      ui.add_space(10.0);
+      ui.add(egui::Label::new(
+        egui::RichText::new("📄 PDF").text_style(egui::TextStyle::Heading),
+      ));
+      ui.add_space(10.0);
+
+      ui.group(|ui| {
+        ui.label("Page");
+        ui.horizontal_wrapped(|ui| {
+          if ui.button("⏴").clicked() {
+            target_page = current_page.map(|page| page.saturating_sub(1));
+          }
+          ui.add(
+            egui::DragValue::new(&mut self.goto_page)
+              .clamp_range(1..=npages)
+              .suffix(format!(" / {npages}")),
+          );
+          if ui.button("Go").clicked() {
+            target_page = Some(self.goto_page - 1);
+          }
+          if ui.button("⏵").clicked() {
+            target_page = current_page.map(|page| page + 1);
+          }
+        });
+      });
+
+      ui.group(|ui| {
+        ui.label("Search");
+        let results = pdf_manager.search_results();
+        let selected_hit = pdf_manager.selected_search_hit();
+        let nhits = results.map_or(0, |results| results.hits.len());
+
+        ui.horizontal(|ui| {
+          let response = ui.text_edit_singleline(&mut self.search_query);
+          let is_submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+          if is_submitted {
+            let is_same_query = results.map_or(false, |r| r.query == self.search_query);
+            if is_same_query && nhits > 0 {
+              // repeated enter steps through the hits
+              target_hit = Some(selected_hit.map_or(0, |hit| (hit + 1) % nhits));
+            } else {
+              submitted_query = Some(self.search_query.clone());
+            }
+            response.request_focus();
+          }
+          if ui.button("✖").clicked() {
+            clear_search = true;
+          }
+        });
+
+        ui.horizontal(|ui| {
+          let can_step = nhits > 0;
+          if ui.add_enabled(can_step, egui::Button::new("⏶")).clicked() {
+            target_hit = Some(selected_hit.map_or(nhits - 1, |hit| (hit + nhits - 1) % nhits));
+          }
+          if ui.add_enabled(can_step, egui::Button::new("⏷")).clicked() {
+            target_hit = Some(selected_hit.map_or(0, |hit| (hit + 1) % nhits));
+          }
+          if pdf_manager.is_searching() {
+            ui.spinner();
+          } else if let Some(results) = results {
+            match selected_hit {
+              Some(hit) => ui.label(format!("{} / {}", hit + 1, results.hits.len())),
+              None => ui.label(format!("{} hits", results.hits.len())),
+            };
+          }
+        });
+
+        if let Some(results) = results {
+          let row_height = ui.text_style_height(&egui::TextStyle::Body);
+          egui::ScrollArea::vertical()
+            .id_source("pdf_search_hits")
+            .max_height(ui.available_height() / 3.0)
+            .show_rows(ui, row_height, results.hits.len(), |ui, rows| {
+              for ihit in rows {
+                let hit = &results.hits[ihit];
+                let text = format!("p. {}: {}", hit.page + 1, hit.context);
+                if ui
+                  .selectable_label(selected_hit == Some(ihit), text)
+                  .clicked()
+                {
+                  target_hit = Some(ihit);
+                }
+              }
+            });
+        }
+      });
+
+      let outline = pdf_manager.outline();
+      if !outline.is_empty() {
+        egui::CollapsingHeader::new("Outline").show(ui, |ui| {
+          egui::ScrollArea::vertical()
+            .id_source("pdf_outline")
+            .max_height(ui.available_height() / 2.0)
+            .show(ui, |ui| outline_ui(ui, outline, 0, &mut target_page));
+        });
+      }
+
+      egui::CollapsingHeader::new("Pages")
+        .default_open(true)
+        .show(ui, |ui| {
+          let row_height = THUMBNAIL_BOX_SIZE.y;
+          egui::ScrollArea::vertical()
+            .id_source("pdf_thumbnails")
+            .show_rows(ui, row_height, npages, |ui, rows| {
+              let missing: Vec<_> = rows
+                .clone()
+                .filter(|page| {
+                  pdf_manager
+                    .pdf_page(*page)
+                    .map_or(false, |key| self.requested_thumbnails.insert(key))
+                })
+                .collect();
+              if !missing.is_empty() {
+                pdf_manager.request_thumbnails(missing);
+              }
+
+              for page in rows {
+                ui.horizontal(|ui| {
+                  ui.label(format!("{}", page + 1));
+                  let is_current = current_page == Some(page);
+                  let thumbnail = pdf_manager
+                    .pdf_page(page)
+                    .and_then(|key| self.thumbnails.get(&key));
+                  let response = match thumbnail {
+                    Some(texture) => {
+                      let size = texture.size_vec2();
+                      let scale = (THUMBNAIL_BOX_SIZE / size).min_elem();
+                      ui.add(
+                        egui::ImageButton::new(texture.id(), size * scale).selected(is_current),
+                      )
+                    }
+                    // not there yet or a blank page
+                    None => {
+                      let info = pdf_manager.page_info(page).unwrap_or(PdfPageInfo::A4);
+                      let size = egui::vec2(info.width_points, info.height_points);
+                      let scale = (THUMBNAIL_BOX_SIZE / size).min_elem();
+                      let (rect, response) =
+                        ui.allocate_exact_size(size * scale, egui::Sense::click());
+                      let visuals = ui.visuals();
+                      ui.painter().rect_filled(rect, 0.0, visuals.faint_bg_color);
+                      if is_current {
+                        ui.painter()
+                          .rect_stroke(rect, 0.0, visuals.selection.stroke);
+                      }
+                      response
+                    }
+                  };
+                  if response.clicked() {
+                    target_page = Some(page);
+                  }
+                });
+              }
+            });
+        });
+    });
+
+    if let Some(page) = target_page {
+      self.goto_page = page.min(npages - 1) + 1;
+      navigation::go_to_page(pdf_manager, ui_access.spaces, page);
+    }
+    if let Some(hit) = target_hit {
+      navigation::go_to_search_hit(ui_access.pdf_manager, ui_access.spaces, hit);
+    }
+    if let Some(query) = submitted_query {
+      ui_access.pdf_manager.search(query);
+      self.awaiting_search_results = true;
+    }
+    if clear_search {
+      ui_access.pdf_manager.clear_search();
+      self.awaiting_search_results = false;
+    }
+  }
+}
+
+/// the items in `outline` at the same depth as the first one, with their children collapsible.
+/// `offset` is the index of the first item in the whole outline.
+fn outline_ui(
+  ui: &mut egui::Ui,
+  outline: &[PdfOutlineItem],
+  offset: usize,
+  target_page: &mut Option<usize>,
+) {
+  let mut i = 0;
+  while i < outline.len() {
+    let item = &outline[i];
+    let nchildren = outline[i + 1..]
+      .iter()
+      .take_while(|child| child.depth > item.depth)
+      .count();
+    let children = &outline[i + 1..i + 1 + nchildren];
+
+    let mut header = |ui: &mut egui::Ui| {
+      let link = ui.add_enabled(item.page.is_some(), egui::Link::new(&item.title));
+      if link.clicked() {
+        *target_page = item.page;
+      }
+    };
+    if children.is_empty() {
+      header(ui);
+    } else {
+      let id = ui.make_persistent_id(("pdf_outline_item", offset + i));
+      egui::collapsing_header::CollapsingState::load_with_default_open(ui.ctx(), id, false)
+        .show_header(ui, header)
+        .body(|ui| outline_ui(ui, children, offset + i + 1, target_page));
+    }
+
+    i += 1 + nchildren;
+  }
+}
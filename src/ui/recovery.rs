@@ -0,0 +1,100 @@
+use super::UiAccess;
+
+use crate::file::{
+  autosave::{self, UnsavedSession},
+  FileError,
+};
+
+/// Offers to restore the documents that were not saved when carveout last quit or crashed.
+pub struct RecoveryUi {
+  /// empty once the user decided
+  sessions: Vec<UnsavedSession>,
+  error: Option<FileError>,
+}
+
+impl Default for RecoveryUi {
+  fn default() -> Self {
+    Self {
+      sessions: autosave::unsaved_sessions(),
+      error: None,
+    }
+  }
+}
+
+impl RecoveryUi {
+  pub fn ui(&mut self, ctx: &egui::Context, ui_access: &mut UiAccess) {
+    if self.sessions.is_empty() && self.error.is_none() {
+      return;
+    }
+
+    let mut is_open = true;
+    let mut restored = None;
+    let mut discarded = Vec::new();
+    egui::Window::new("Restore unsaved documents")
+      .collapsible(false)
+      .resizable(false)
+      .open(&mut is_open)
+      .show(ctx, |ui| {
+        if let Some(error) = &self.error {
+          ui.label(format!("Could not restore the document: {error}"));
+          ui.separator();
+        }
+        if self.sessions.is_empty() {
+          return;
+        }
+        ui.label("These documents were not saved:");
+        for (i, session) in self.sessions.iter().enumerate() {
+          ui.horizontal(|ui| {
+            ui.label(session.modified.format("%Y-%m-%d %H:%M:%S").to_string());
+            if ui
+              .button("Restore")
+              .on_hover_text("Replace the open document")
+              .clicked()
+            {
+              restored = Some(i);
+            }
+            if ui.button("Discard").clicked() {
+              discarded.push(i);
+            }
+          });
+        }
+        ui.horizontal(|ui| {
+          if ui.button("Discard all").clicked() {
+            discarded.extend(0..self.sessions.len());
+          }
+          if ui
+            .button("Later")
+            .on_hover_text("Ask again on the next start")
+            .clicked()
+          {
+            self.sessions.clear();
+          }
+        });
+      });
+
+    if let Some(i) = restored {
+      match autosave::restore(&self.sessions[i]) {
+        Ok(savefile) => {
+          ui_access
+            .content_manager
+            .replace(savefile.content, savefile.protocol);
+          ui_access.session.document = None;
+          // the others are offered again on the next start
+          self.sessions.clear();
+          self.error = None;
+        }
+        Err(error) => self.error = Some(error),
+      }
+    }
+    for i in discarded.into_iter().rev() {
+      let session = self.sessions.remove(i);
+      if let Err(error) = autosave::discard(&session) {
+        self.error = Some(error);
+      }
+    }
+    if !is_open {
+      self.sessions.clear();
+      self.error = None;
+    }
+  }
+}
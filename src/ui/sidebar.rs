@@ -1,9 +1,30 @@
 use super::UiAccess;
 
-use crate::{content::protocol::ProtocolUi, file, pdf::PdfManager, tools::ToolEnum, util};
+#[cfg(not(target_arch = "wasm32"))]
+use super::file_drop::{DropAction, DroppedFile};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::{
+  content::{command::AddStrokesCommand, protocol::Protocol},
+  import::{self, ImportReport, SvgPlacement},
+};
+use crate::{
+  content::{
+    command::{AddHighlightCommand, ArrangePagesCommand},
+    protocol::ProtocolUi,
+  },
+  export::{self, ExportRegion, PngOptions, SvgOptions},
+  file::{self, FileError, SaveFormat},
+  pdf::{navigation, PageList, PdfColorFilter, PdfError, PdfLayout, PdfPageInfo},
+  spaces::Space,
+  tools::ToolEnum,
+  util,
+};
 
 use egui_file::FileDialog;
 use palette::{FromColor, Hsv, IntoColor};
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::Path;
+use std::{io, path::PathBuf};
 
 #[derive(Default)]
 pub struct SidebarUi {
@@ -12,31 +33,419 @@ pub struct SidebarUi {
   protocol_tree_enabled: bool,
   project_file_dialog: Option<FileDialog>,
   pdf_file_dialog: Option<FileDialog>,
+  pdf_export_file_dialog: Option<FileDialog>,
+  svg_file_dialog: Option<FileDialog>,
+  inkml_file_dialog: Option<FileDialog>,
+  png_file_dialog: Option<FileDialog>,
+  #[cfg(not(target_arch = "wasm32"))]
+  import_file_dialog: Option<FileDialog>,
+  /// what the last import lost, or why it failed, until the user dismisses it
+  #[cfg(not(target_arch = "wasm32"))]
+  import_result: Option<Result<ImportReport, String>>,
+  #[cfg(not(target_arch = "wasm32"))]
+  import_settings: ImportSettings,
+  export_settings: ExportSettings,
+  /// the last failed file operation, until the user dismisses it
+  file_error: Option<(&'static str, FileError)>,
+  /// waits for the user to confirm what it would replace
+  #[cfg(not(target_arch = "wasm32"))]
+  pending_replacement: Option<Replacement>,
+  /// the pdfium failures since the user last dismissed them
+  pdf_errors: Vec<PdfError>,
+  /// whether the pdf from the dialog is inserted or replaces all pages
+  pdf_file_inserted: bool,
+  save_format: SaveFormat,
+}
+
+/// Something that replaces the whole document or its pages.
+#[cfg(not(target_arch = "wasm32"))]
+enum Replacement {
+  Open(PathBuf),
+  /// a Xournal++ notebook
+  ImportNotebook(PathBuf),
+  /// dropped pdfs with their number of pages
+  LoadPdfs(Vec<(PathBuf, usize)>),
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Replacement {
+  /// the title and text of the question, and the label of the button confirming it
+  fn question(&self) -> (&'static str, String, &'static str) {
+    let name = |path: &Path| {
+      path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .into_owned()
+    };
+    let discards = "discards the changes that are not saved.";
+    match self {
+      Replacement::Open(path) => (
+        "Unsaved changes",
+        format!("Opening {} {discards}", name(path)),
+        "Discard changes",
+      ),
+      Replacement::ImportNotebook(path) => (
+        "Unsaved changes",
+        format!("Importing {} {discards}", name(path)),
+        "Discard changes",
+      ),
+      Replacement::LoadPdfs(pdfs) => {
+        let names: Vec<_> = pdfs.iter().map(|(path, _)| name(path)).collect();
+        let text = format!(
+          "Loading {} replaces the pages. The strokes and highlights stay on the page \
+           at the same position.",
+          names.join(", ")
+        );
+        ("Replace pages", text, "Replace pages")
+      }
+    }
+  }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+struct ImportSettings {
+  /// size of svg drawings relative to their declared size
+  svg_scale: f32,
+}
+#[cfg(not(target_arch = "wasm32"))]
+impl Default for ImportSettings {
+  fn default() -> Self {
+    Self { svg_scale: 1.0 }
+  }
+}
+
+struct ExportSettings {
+  scope: ExportScope,
+  /// whether image exports show the pdf pages below the strokes
+  pdf_background: bool,
+  /// resolution of png exports
+  dpi: f32,
+}
+impl Default for ExportSettings {
+  fn default() -> Self {
+    Self {
+      scope: ExportScope::default(),
+      pdf_background: false,
+      dpi: 150.0,
+    }
+  }
+}
+
+/// the part of the canvas the image exports cover
+#[derive(Default, Clone, Copy, PartialEq, Eq)]
+enum ExportScope {
+  #[default]
+  All,
+  Selection,
+  CurrentPage,
+  /// what is visible on the screen
+  View,
+}
+
+impl ExportScope {
+  fn name(&self) -> &'static str {
+    match self {
+      ExportScope::All => "Everything",
+      ExportScope::Selection => "Selection",
+      ExportScope::CurrentPage => "Current page",
+      ExportScope::View => "Visible area",
+    }
+  }
+
+  /// `None` if there is nothing in the scope
+  fn region(&self, ui_access: &UiAccess) -> Option<ExportRegion> {
+    match self {
+      ExportScope::All => Some(ExportRegion::All),
+      ExportScope::Selection => {
+        let selected = ui_access.tool_manager.select_loop.selected_strokes();
+        (!selected.is_empty()).then(|| ExportRegion::Strokes(selected.to_vec()))
+      }
+      ExportScope::CurrentPage => {
+        navigation::current_page(ui_access.pdf_manager, ui_access.spaces).map(ExportRegion::Page)
+      }
+      ExportScope::View => {
+        let spaces = &ui_access.spaces;
+        Some(ExportRegion::Rect(spaces.transform_rect(
+          spaces.screen_rect_window_logical(),
+          Space::WindowLogical,
+          Space::Canvas,
+        )))
+      }
+    }
+  }
 }
 
 impl SidebarUi {
+  /// Asks first if that discards unsaved changes.
+  fn open_project(&mut self, ui_access: &mut UiAccess, file_path: PathBuf) {
+    #[cfg(not(target_arch = "wasm32"))]
+    if ui_access.content_manager.is_modified() {
+      self.pending_replacement = Some(Replacement::Open(file_path));
+      return;
+    }
+    self.load_project(ui_access, file_path);
+  }
+
+  /// The document stays as it is if loading fails.
+  fn load_project(&mut self, ui_access: &mut UiAccess, file_path: PathBuf) {
+    match file::load(&file_path) {
+      Ok(mut savefile) => {
+        // the pages show placeholders for the pdfs that are not extracted
+        if let Err(error) = savefile.extract_pdfs() {
+          self.file_error = Some(("Could not extract the embedded pdfs", error));
+        }
+        let content_manager = &mut ui_access.content_manager;
+        content_manager.replace(savefile.content, savefile.protocol);
+        content_manager.mark_saved();
+        ui_access.session.set_document(file_path);
+      }
+      Err(error) => {
+        if matches!(&error, FileError::Io(error) if error.kind() == io::ErrorKind::NotFound) {
+          ui_access.session.forget_recent_file(&file_path);
+        }
+        self.file_error = Some(("Could not open the project", error));
+      }
+    }
+  }
+
+  /// Adds the ink to the document in one step, svg drawings centered on `center_canvas`.
+  /// Notebooks replace the document, after asking if that discards unsaved changes.
+  #[cfg(not(target_arch = "wasm32"))]
+  fn import(&mut self, ui_access: &mut UiAccess, file_path: &Path, center_canvas: na::Point2<f32>) {
+    let result = match extension(file_path).as_deref() {
+      Some("inkml" | "ink") => import::import_inkml(file_path),
+      Some("svg") => {
+        let placement = SvgPlacement {
+          center_canvas,
+          scale: self.import_settings.svg_scale,
+        };
+        import::import_svg(file_path, &placement)
+      }
+      _ => {
+        match ui_access.content_manager.is_modified() {
+          true => {
+            let replacement = Replacement::ImportNotebook(file_path.to_owned());
+            self.pending_replacement = Some(replacement);
+          }
+          false => self.import_notebook(ui_access, file_path),
+        }
+        return;
+      }
+    };
+    self.import_result = Some(
+      result
+        .map(|import| {
+          if !import.strokes.is_empty() {
+            let content_manager = &mut ui_access.content_manager;
+            content_manager.run_cmd(AddStrokesCommand::new(import.strokes));
+          }
+          import.report
+        })
+        .map_err(|error| error.to_string()),
+    );
+  }
+
+  /// Replaces the document with the notebook, which starts a new history.
+  #[cfg(not(target_arch = "wasm32"))]
+  fn import_notebook(&mut self, ui_access: &mut UiAccess, file_path: &Path) {
+    let result = import::import_xopp(file_path).map(|import| {
+      ui_access
+        .content_manager
+        .replace(import.content, Protocol::default());
+      ui_access.session.document = None;
+      import.report
+    });
+    self.import_result = Some(result.map_err(|error| error.to_string()));
+  }
+
+  /// Adds the image as a page of its own centered on `center_canvas`, in one undoable step.
+  /// That switches to the free layout, the other pages keep their positions.
+  #[cfg(not(target_arch = "wasm32"))]
+  fn import_image(
+    &mut self,
+    ui_access: &mut UiAccess,
+    file_path: &Path,
+    center_canvas: na::Point2<f32>,
+  ) {
+    let result = import::import_image(file_path, ui_access.pdf_manager).and_then(|pdf| {
+      ui_access.pdf_manager.load_source(pdf.clone())?;
+      Ok(pdf)
+    });
+    let pdf = match result {
+      Ok(pdf) => pdf,
+      Err(error) => {
+        self.import_result = Some(Err(error.to_string()));
+        return;
+      }
+    };
+
+    let index = after_current_page(ui_access);
+    let content = ui_access.content_manager.access();
+    let mut pages = content.pages().clone();
+    pages.insert_pdf(index, pdf, 0..1);
+    let mut page_centers_canvas = ui_access.pdf_manager.free_page_centers_canvas();
+    page_centers_canvas.insert(pages.pages()[index].id, center_canvas);
+    let layout = PdfLayout::Free {
+      page_centers_canvas,
+    };
+    let command = ArrangePagesCommand::new(content, pages, layout, ui_access.pdf_manager);
+    ui_access.content_manager.run_cmd(command);
+    self.import_result = Some(Ok(ImportReport::default()));
+  }
+
+  /// Opens savefiles, imports the rest where it was dropped and makes the pdfs the pages,
+  /// after asking if there are pages already.
+  #[cfg(not(target_arch = "wasm32"))]
+  pub fn open_dropped_files(&mut self, ui_access: &mut UiAccess, files: Vec<DroppedFile>) {
+    let mut pdfs = Vec::new();
+    for file in files {
+      match DropAction::of(&file.path) {
+        DropAction::Open => self.open_project(ui_access, file.path),
+        DropAction::LoadPdf => pdfs.push(file.path),
+        DropAction::Import => {
+          let center_canvas = drop_center_canvas(ui_access, &file);
+          self.import(ui_access, &file.path, center_canvas);
+        }
+        DropAction::Image => {
+          let center_canvas = drop_center_canvas(ui_access, &file);
+          self.import_image(ui_access, &file.path, center_canvas);
+        }
+        DropAction::Unsupported => {
+          self.import_result = Some(Err(DropAction::Unsupported.describe(&file.path)));
+        }
+      }
+    }
+
+    if pdfs.is_empty() {
+      return;
+    }
+    // the ones failing to load are reported by the pdf manager
+    let sources: Vec<_> = pdfs
+      .into_iter()
+      .filter_map(|pdf| {
+        let npages = ui_access
+          .pdf_manager
+          .load_source(pdf.clone())
+          .ok()?
+          .npages();
+        Some((pdf, npages))
+      })
+      .collect();
+    if sources.is_empty() {
+      return;
+    }
+    match ui_access.content_manager.access().pages().npages() {
+      0 => self.load_pdfs(ui_access, sources),
+      _ => self.pending_replacement = Some(Replacement::LoadPdfs(sources)),
+    }
+  }
+
+  #[cfg(not(target_arch = "wasm32"))]
+  fn load_pdfs(&mut self, ui_access: &mut UiAccess, pdfs: Vec<(PathBuf, usize)>) {
+    ui_access.session.pdf = pdfs.first().map(|(pdf, _)| pdf.clone());
+    replace_pdfs(ui_access, pdfs);
+  }
+
+  /// Asks whether the waiting replacement may go ahead.
+  #[cfg(not(target_arch = "wasm32"))]
+  fn replacement_ui(&mut self, ctx: &egui::Context, ui_access: &mut UiAccess) {
+    let Some(replacement) = &self.pending_replacement else {
+      return;
+    };
+    let (title, text, confirm) = replacement.question();
+    let mut is_confirmed = None;
+    egui::Window::new(title)
+      .collapsible(false)
+      .resizable(false)
+      .show(ctx, |ui| {
+        ui.label(text);
+        ui.horizontal(|ui| {
+          if ui.button(confirm).clicked() {
+            is_confirmed = Some(true);
+          }
+          if ui.button("Cancel").clicked() {
+            is_confirmed = Some(false);
+          }
+        });
+      });
+    match is_confirmed {
+      Some(true) => match self.pending_replacement.take() {
+        Some(Replacement::Open(file_path)) => self.load_project(ui_access, file_path),
+        Some(Replacement::ImportNotebook(file_path)) => {
+          self.import_notebook(ui_access, &file_path);
+        }
+        Some(Replacement::LoadPdfs(pdfs)) => self.load_pdfs(ui_access, pdfs),
+        None => {}
+      },
+      Some(false) => self.pending_replacement = None,
+      None => {}
+    }
+  }
+
   pub fn ui(&mut self, ctx: &egui::Context, ui_access: &mut UiAccess) {
+    #[cfg(not(target_arch = "wasm32"))]
+    self.replacement_ui(ctx, ui_access);
+
     if let Some(file_dialog) = &mut self.project_file_dialog {
       file_dialog.show(ctx);
       if file_dialog.selected() {
         let file_path = file_dialog.path().unwrap();
         match file_dialog.dialog_type() {
-          egui_file::DialogType::OpenFile => {
-            let savefile = file::load(&file_path);
-            ui_access
-              .content_manager
-              .replace(savefile.content, savefile.protocol);
-          }
+          egui_file::DialogType::OpenFile => self.open_project(ui_access, file_path),
           egui_file::DialogType::SaveFile => {
             let (content, protocol) = ui_access.content_manager.clone();
-            let savefile = file::Savefile { content, protocol };
-            file::save(&savefile, file_path);
+            let savefile = file::Savefile::new(content, protocol);
+            match file::save(&savefile, file_path, self.save_format) {
+              Ok(file_path) => {
+                ui_access.content_manager.mark_saved();
+                ui_access.session.set_document(file_path);
+              }
+              Err(error) => self.file_error = Some(("Could not save the project", error)),
+            }
           }
           _ => unreachable!(),
         }
       }
     }
 
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(file_dialog) = &mut self.import_file_dialog {
+      file_dialog.show(ctx);
+      if file_dialog.selected() {
+        let file_path = file_dialog.path().unwrap();
+        self.import_file_dialog = None;
+        let center_canvas = view_center_canvas(ui_access);
+        self.import(ui_access, &file_path, center_canvas);
+      }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(result) = &self.import_result {
+      let mut is_open = true;
+      egui::Window::new("Import")
+        .collapsible(false)
+        .resizable(false)
+        .open(&mut is_open)
+        .show(ctx, |ui| match result {
+          Ok(report) if report.is_empty() => {
+            ui.label("Everything was imported.");
+          }
+          Ok(report) => {
+            ui.label("Imported, but not everything could be converted:");
+            for line in report.lines() {
+              ui.label(line);
+            }
+          }
+          Err(error) => {
+            ui.label(format!("Import failed, {error}"));
+          }
+        });
+      if !is_open {
+        self.import_result = None;
+      }
+    }
+
     #[cfg(not(target_arch = "wasm32"))]
     if let Some(file_dialog) = &mut self.pdf_file_dialog {
       file_dialog.show(ctx);
@@ -44,13 +453,141 @@ impl SidebarUi {
         let file_path = file_dialog.path().unwrap();
         match file_dialog.dialog_type() {
           egui_file::DialogType::OpenFile => {
-            *ui_access.pdf_manager = Some(PdfManager::load_document(file_path));
+            let loaded = ui_access
+              .pdf_manager
+              .load_source(file_path.clone())
+              .map(|info| info.npages());
+            // a failure is reported by the pdf manager
+            if let Ok(npages) = loaded {
+              match self.pdf_file_inserted {
+                true => {
+                  let index = after_current_page(ui_access);
+                  edit_pages(ui_access, |pages| {
+                    pages.insert_pdf(index, file_path, 0..npages)
+                  });
+                }
+                false => {
+                  ui_access.session.pdf = Some(file_path.clone());
+                  replace_pdfs(ui_access, vec![(file_path, npages)]);
+                }
+              }
+            }
           }
           _ => unreachable!(),
         }
       }
     }
 
+    if let Some(file_dialog) = &mut self.pdf_export_file_dialog {
+      file_dialog.show(ctx);
+      if file_dialog.selected() {
+        let file_path = file_dialog.path().unwrap();
+        match ui_access
+          .pdf_manager
+          .export(ui_access.content_manager.access())
+        {
+          Ok(data) => {
+            if let Err(error) = file::save_pdf(&data, file_path) {
+              self.file_error = Some(("Could not export the pdf", error));
+            }
+          }
+          Err(error) => self.pdf_errors.push(error),
+        }
+      }
+    }
+
+    if let Some(file_dialog) = &mut self.svg_file_dialog {
+      file_dialog.show(ctx);
+      if file_dialog.selected() {
+        let file_path = file_dialog.path().unwrap();
+        if let Some(region) = self.export_settings.scope.region(ui_access) {
+          let options = SvgOptions {
+            region,
+            pdf_background: self.export_settings.pdf_background,
+          };
+          let content = ui_access.content_manager.access();
+          if let Some(svg) = export::export_svg(&options, content, ui_access.pdf_manager) {
+            if let Err(error) = file::save_svg(&svg, file_path) {
+              self.file_error = Some(("Could not export the svg", error));
+            }
+          }
+        }
+      }
+    }
+
+    if let Some(file_dialog) = &mut self.inkml_file_dialog {
+      file_dialog.show(ctx);
+      if file_dialog.selected() {
+        let file_path = file_dialog.path().unwrap();
+        if let Some(region) = self.export_settings.scope.region(ui_access) {
+          let content = ui_access.content_manager.access();
+          if let Some(inkml) = export::export_inkml(&region, content, ui_access.pdf_manager) {
+            if let Err(error) = file::save_inkml(&inkml, file_path) {
+              self.file_error = Some(("Could not export the InkML", error));
+            }
+          }
+        }
+      }
+    }
+
+    if let Some(file_dialog) = &mut self.png_file_dialog {
+      file_dialog.show(ctx);
+      if file_dialog.selected() {
+        let file_path = file_dialog.path().unwrap();
+        if let Some(region) = self.export_settings.scope.region(ui_access) {
+          let options = PngOptions {
+            region,
+            dpi: self.export_settings.dpi,
+            pdf_background: self.export_settings.pdf_background,
+          };
+          let content = ui_access.content_manager.access();
+          if let Some(png) = export::export_png(&options, content, ui_access.pdf_manager) {
+            if let Err(error) = file::save_png(&png, file_path) {
+              self.file_error = Some(("Could not export the png", error));
+            }
+          }
+        }
+      }
+    }
+
+    if let Some((title, error)) = &self.file_error {
+      let mut is_open = true;
+      egui::Window::new(*title)
+        .collapsible(false)
+        .resizable(false)
+        .open(&mut is_open)
+        .show(ctx, |ui| {
+          ui.label(error.to_string());
+        });
+      if !is_open {
+        self.file_error = None;
+      }
+    }
+
+    self
+      .pdf_errors
+      .extend(ui_access.pdf_manager.take_load_errors());
+    if !self.pdf_errors.is_empty() {
+      let mut is_open = true;
+      egui::Window::new("Pdf errors")
+        .collapsible(false)
+        .resizable(false)
+        .open(&mut is_open)
+        .show(ctx, |ui| {
+          for error in &self.pdf_errors {
+            ui.label(error.to_string());
+          }
+          let is_loading_error =
+            |error: &PdfError| matches!(error, PdfError::Load { .. } | PdfError::Binding(_));
+          if self.pdf_errors.iter().any(is_loading_error) {
+            ui.label("The pages of these pdfs are placeholders, the strokes on them are kept.");
+          }
+        });
+      if !is_open {
+        self.pdf_errors.clear();
+      }
+    }
+
     egui::SidePanel::left("toolbox_panel").show(ctx, |ui| {
       ui.add_space(10.0);
       ui.add(egui::Label::new(
@@ -62,29 +599,259 @@ impl SidebarUi {
         ui.label("Project File");
         ui.horizontal_wrapped(|ui| {
           if ui.button("📂").clicked() {
-            let mut file_dialog =
-              FileDialog::open_file(Some(util::USER_DIRS.home_dir().to_owned()));
+            let mut file_dialog = FileDialog::open_file(Some(ui_access.session.dialog_dir()));
             file_dialog.open();
             self.project_file_dialog = Some(file_dialog);
           }
           if ui.button("🗄").clicked() {
-            let mut file_dialog =
-              FileDialog::save_file(Some(util::USER_DIRS.home_dir().to_owned()));
+            let mut file_dialog = FileDialog::save_file(Some(ui_access.session.dialog_dir()));
             file_dialog.open();
             self.project_file_dialog = Some(file_dialog);
           }
+          let mut recent_file = None;
+          ui.menu_button("🕓", |ui| {
+            let recent_files = &ui_access.session.recent_files;
+            if recent_files.is_empty() {
+              ui.label("No recent projects");
+            }
+            for path in recent_files {
+              let name = path.file_name().unwrap_or_default().to_string_lossy();
+              let button = ui.button(name).on_hover_text(path.display().to_string());
+              if button.clicked() {
+                recent_file = Some(path.clone());
+                ui.close_menu();
+              }
+            }
+          })
+          .response
+          .on_hover_text("Recent projects");
+          if let Some(file_path) = recent_file {
+            self.open_project(ui_access, file_path);
+          }
+          let mut is_compact = self.save_format == SaveFormat::Binary;
+          if ui
+            .checkbox(&mut is_compact, "Compact")
+            .on_hover_text("Save as small binary file with the pdfs embedded")
+            .changed()
+          {
+            self.save_format = match is_compact {
+              true => SaveFormat::Binary,
+              false => SaveFormat::Ron,
+            };
+          }
+          #[cfg(not(target_arch = "wasm32"))]
+          if ui
+            .button("📥")
+            .on_hover_text("Import Xournal++ notebook, InkML ink or svg drawing")
+            .clicked()
+          {
+            let mut file_dialog = FileDialog::open_file(Some(ui_access.session.dialog_dir()));
+            file_dialog.open();
+            self.import_file_dialog = Some(file_dialog);
+          }
+          #[cfg(not(target_arch = "wasm32"))]
+          ui.add(
+            egui::DragValue::new(&mut self.import_settings.svg_scale)
+              .clamp_range(0.01..=100.0)
+              .speed(0.01)
+              .prefix("svg ×"),
+          )
+          .on_hover_text("Scale of imported svg drawings");
         });
 
         ui.separator();
 
         ui.label("PDF File");
         ui.horizontal_wrapped(|ui| {
-          if ui.button("📂").clicked() {
-            let mut file_dialog =
-              FileDialog::open_file(Some(util::USER_DIRS.home_dir().to_owned()));
+          let open = ui
+            .button("📂")
+            .on_hover_text("Open pdf in place of the pages, the strokes stay");
+          let insert = ui
+            .button("➕")
+            .on_hover_text("Insert pdf after the current page");
+          if open.clicked() || insert.clicked() {
+            let mut file_dialog = FileDialog::open_file(Some(ui_access.session.dialog_dir()));
             file_dialog.open();
             self.pdf_file_dialog = Some(file_dialog);
+            self.pdf_file_inserted = insert.clicked();
+          }
+          if ui
+            .button("📤")
+            .on_hover_text("Export pdf with annotations")
+            .clicked()
+          {
+            let mut file_dialog = FileDialog::save_file(Some(ui_access.session.dialog_dir()));
+            file_dialog.open();
+            self.pdf_export_file_dialog = Some(file_dialog);
+          }
+        });
+
+        ui.label("Pages");
+        ui.horizontal_wrapped(|ui| {
+          let current_page = navigation::current_page(ui_access.pdf_manager, ui_access.spaces);
+          if ui
+            .button("🗋")
+            .on_hover_text("Insert blank page after the current page")
+            .clicked()
+          {
+            // as large as the page it follows
+            let info = current_page
+              .and_then(|page| ui_access.pdf_manager.page_info(page))
+              .unwrap_or(PdfPageInfo::A4);
+            let index = after_current_page(ui_access);
+            edit_pages(ui_access, |pages| pages.insert_blank(index, info));
+          }
+          let button = egui::Button::new("🗑");
+          let response = ui
+            .add_enabled(current_page.is_some(), button)
+            .on_hover_text("Remove the current page with its strokes and highlights");
+          if let (true, Some(page)) = (response.clicked(), current_page) {
+            edit_pages(ui_access, |pages| pages.remove(page));
           }
+          let npages = ui_access.pdf_manager.npages();
+          let can_move_up = current_page.map_or(false, |page| page > 0);
+          let response = ui
+            .add_enabled(can_move_up, egui::Button::new("⏶"))
+            .on_hover_text("Move the current page up");
+          if let (true, Some(page)) = (response.clicked(), current_page) {
+            edit_pages(ui_access, |pages| pages.reorder_page(page, page - 1));
+          }
+          let can_move_down = current_page.map_or(false, |page| page + 1 < npages);
+          let response = ui
+            .add_enabled(can_move_down, egui::Button::new("⏷"))
+            .on_hover_text("Move the current page down");
+          if let (true, Some(page)) = (response.clicked(), current_page) {
+            edit_pages(ui_access, |pages| pages.reorder_page(page, page + 1));
+          }
+        });
+
+        ui.label("PDF Layout");
+        let pdf_manager = &mut ui_access.pdf_manager;
+        let mut layout = pdf_manager.layout().clone();
+        egui::ComboBox::from_id_source("pdf_layout")
+          .selected_text(layout.name())
+          .show_ui(ui, |ui| {
+            let page_centers_canvas = pdf_manager.free_page_centers_canvas();
+            let options = [
+              PdfLayout::Vertical,
+              PdfLayout::Horizontal,
+              PdfLayout::Spread { cover: true },
+              PdfLayout::Grid { columns: 4 },
+              PdfLayout::Free {
+                page_centers_canvas,
+              },
+            ];
+            for option in options {
+              let is_selected = util::enum_variant_eq(&layout, &option);
+              if ui.selectable_label(is_selected, option.name()).clicked() && !is_selected {
+                layout = option;
+              }
+            }
+          });
+        match &mut layout {
+          PdfLayout::Spread { cover } => {
+            ui.checkbox(cover, "Cover page");
+          }
+          PdfLayout::Grid { columns } => {
+            ui.add(
+              egui::DragValue::new(columns)
+                .clamp_range(1..=16)
+                .prefix("Columns: "),
+            );
+          }
+          _ => {}
+        }
+        if layout != *pdf_manager.layout() {
+          let pages = ui_access.content_manager.access().pages().clone();
+          let command = ArrangePagesCommand::new(
+            ui_access.content_manager.access(),
+            pages,
+            layout,
+            pdf_manager,
+          );
+          ui_access.content_manager.run_cmd(command);
+        }
+
+        ui.label("PDF Colors");
+        let mut filter = pdf_manager.color_filter();
+        egui::ComboBox::from_id_source("pdf_color_filter")
+          .selected_text(filter.name())
+          .show_ui(ui, |ui| {
+            let options = [
+              PdfColorFilter::None,
+              PdfColorFilter::Dark,
+              PdfColorFilter::Sepia,
+              PdfColorFilter::Grayscale,
+              PdfColorFilter::Tint(palette::LinSrgb::new(1.0, 0.95, 0.8)),
+            ];
+            for option in options {
+              let is_selected = util::enum_variant_eq(&filter, &option);
+              if ui.selectable_label(is_selected, option.name()).clicked() && !is_selected {
+                filter = option;
+              }
+            }
+          });
+        if let PdfColorFilter::Tint(tint) = &mut filter {
+          let mut color = [tint.red, tint.green, tint.blue];
+          ui.color_edit_button_rgb(&mut color);
+          *tint = palette::LinSrgb::new(color[0], color[1], color[2]);
+        }
+        if filter != pdf_manager.color_filter() {
+          pdf_manager.set_color_filter(filter);
+        }
+        let mut filters_ink = pdf_manager.filters_ink();
+        if ui.checkbox(&mut filters_ink, "Apply to ink").changed() {
+          pdf_manager.set_filters_ink(filters_ink);
+        }
+
+        ui.separator();
+
+        ui.label("Export");
+        let has_selection = !ui_access
+          .tool_manager
+          .select_loop
+          .selected_strokes()
+          .is_empty();
+        let settings = &mut self.export_settings;
+        egui::ComboBox::from_id_source("export_scope")
+          .selected_text(settings.scope.name())
+          .show_ui(ui, |ui| {
+            let options = [
+              ExportScope::All,
+              ExportScope::Selection,
+              ExportScope::CurrentPage,
+              ExportScope::View,
+            ];
+            for option in options {
+              let is_enabled = option != ExportScope::Selection || has_selection;
+              let label = egui::SelectableLabel::new(settings.scope == option, option.name());
+              if ui.add_enabled(is_enabled, label).clicked() {
+                settings.scope = option;
+              }
+            }
+          });
+        ui.checkbox(&mut settings.pdf_background, "PDF background");
+        ui.horizontal_wrapped(|ui| {
+          if ui.button("SVG").clicked() {
+            let mut file_dialog = FileDialog::save_file(Some(ui_access.session.dialog_dir()));
+            file_dialog.open();
+            self.svg_file_dialog = Some(file_dialog);
+          }
+          if ui.button("InkML").clicked() {
+            let mut file_dialog = FileDialog::save_file(Some(ui_access.session.dialog_dir()));
+            file_dialog.open();
+            self.inkml_file_dialog = Some(file_dialog);
+          }
+          if ui.button("PNG").clicked() {
+            let mut file_dialog = FileDialog::save_file(Some(ui_access.session.dialog_dir()));
+            file_dialog.open();
+            self.png_file_dialog = Some(file_dialog);
+          }
+          ui.add(
+            egui::DragValue::new(&mut self.export_settings.dpi)
+              .clamp_range(18.0..=1200.0)
+              .suffix(" dpi"),
+          );
         });
       });
 
@@ -127,6 +894,8 @@ impl SidebarUi {
           selectable_tool(ui, selected, ToolEnum::Translate, "✋");
           selectable_tool(ui, selected, ToolEnum::Rotate, "🔄");
           selectable_tool(ui, selected, ToolEnum::Zoom, "🔍");
+          selectable_tool(ui, selected, ToolEnum::Page, "📄");
+          selectable_tool(ui, selected, ToolEnum::Highlighter, "🖍");
         });
 
         ui.separator();
@@ -152,8 +921,13 @@ impl SidebarUi {
             ui.label("Pen width");
             ui.add(egui::Slider::new(&mut pen.width, 0.1..=10.0));
           }
-          ToolEnum::Eraser => {}
-          ToolEnum::SelectLoop => {}
+          ToolEnum::Eraser => {
+            ui.label("Erase strokes and highlights");
+          }
+          ToolEnum::SelectLoop => {
+            let config = &mut ui_access.tool_manager.configs.select_loop;
+            ui.checkbox(&mut config.erase_selection, "Erase selection");
+          }
           ToolEnum::Translate => {
             ui.label("Translate options");
             let position = &mut ui_access.spaces.camera_mut().position_canvas;
@@ -185,6 +959,45 @@ impl SidebarUi {
                 .speed(speed),
             );
           }
+          ToolEnum::Page => {
+            ui.label("Drag pdf pages around");
+          }
+          ToolEnum::Highlighter => {
+            let tool_manager = &mut ui_access.tool_manager;
+            let config = &mut tool_manager.configs.highlighter;
+
+            ui.label("Highlight color");
+            let color = config.color.into_components();
+            let mut color = [color.0, color.1, color.2, color.3];
+            ui.color_edit_button_rgba_unmultiplied(&mut color);
+            config.color = palette::LinSrgba::new(color[0], color[1], color[2], color[3]);
+
+            ui.checkbox(&mut config.highlight_on_release, "Highlight on release");
+
+            let selected_highlight = tool_manager
+              .highlighter
+              .selected_highlight(ui_access.pdf_manager, config.color);
+            ui.horizontal_wrapped(|ui| {
+              let has_selection = selected_highlight.is_some();
+              if ui
+                .add_enabled(has_selection, egui::Button::new("🗐 Copy"))
+                .clicked()
+              {
+                let text = selected_highlight.as_ref().unwrap().text.clone();
+                ui.ctx().output_mut(|output| output.copied_text = text);
+              }
+              if !config.highlight_on_release
+                && ui
+                  .add_enabled(has_selection, egui::Button::new("🖍 Highlight"))
+                  .clicked()
+              {
+                let highlight = selected_highlight.unwrap();
+                ui_access
+                  .content_manager
+                  .run_cmd(AddHighlightCommand::new(highlight));
+              }
+            });
+          }
         }
       });
     });
@@ -199,3 +1012,74 @@ fn selectable_tool(ui: &mut egui::Ui, selected: &mut ToolEnum, selectable: ToolE
     *selected = selectable;
   }
 }
+
+/// changes the page list with an undoable command, which moves or removes the strokes with their pages
+fn edit_pages(ui_access: &mut UiAccess, edit: impl FnOnce(&mut PageList)) {
+  let content = ui_access.content_manager.access();
+  let mut pages = content.pages().clone();
+  edit(&mut pages);
+  if pages != *content.pages() {
+    let layout = content.layout().clone();
+    let command = ArrangePagesCommand::new(content, pages, layout, ui_access.pdf_manager);
+    ui_access.content_manager.run_cmd(command);
+  }
+}
+
+/// Makes the pdfs the pages in one undoable step, keeping the strokes and highlights.
+/// They stay on the page at the same position, pages past the end of the pdfs
+/// are kept as blank pages if they hold any.
+#[cfg(not(target_arch = "wasm32"))]
+fn replace_pdfs(ui_access: &mut UiAccess, pdfs: Vec<(PathBuf, usize)>) {
+  let content = ui_access.content_manager.access();
+  let pdf_manager = &ui_access.pdf_manager;
+  let kept: Vec<_> = ArrangePagesCommand::annotated_pages(content, pdf_manager)
+    .into_iter()
+    .enumerate()
+    .map(|(page, is_annotated)| {
+      let info = pdf_manager.page_info(page).unwrap_or(PdfPageInfo::A4);
+      is_annotated.then_some(info)
+    })
+    .collect();
+  edit_pages(ui_access, |pages| {
+    pages.replace_pdfs(pdfs, |page| kept[page])
+  });
+}
+
+/// where the file was dropped, the middle of the screen if that is unknown
+#[cfg(not(target_arch = "wasm32"))]
+fn drop_center_canvas(ui_access: &UiAccess, file: &DroppedFile) -> na::Point2<f32> {
+  match file.cursor_pos_screen_logical {
+    Some(cursor_screen_logical) => {
+      ui_access
+        .spaces
+        .transform_point(cursor_screen_logical, Space::ScreenLogical, Space::Canvas)
+    }
+    None => view_center_canvas(ui_access),
+  }
+}
+
+/// the point of the canvas in the middle of the screen
+#[cfg(not(target_arch = "wasm32"))]
+fn view_center_canvas(ui_access: &UiAccess) -> na::Point2<f32> {
+  let spaces = &ui_access.spaces;
+  spaces
+    .transform_rect(
+      spaces.screen_rect_window_logical(),
+      Space::WindowLogical,
+      Space::Canvas,
+    )
+    .center
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn extension(path: &Path) -> Option<String> {
+  path
+    .extension()
+    .map(|extension| extension.to_string_lossy().to_lowercase())
+}
+
+/// where pages are inserted, at the end if there is no current page
+fn after_current_page(ui_access: &UiAccess) -> usize {
+  navigation::current_page(ui_access.pdf_manager, ui_access.spaces)
+    .map_or(ui_access.pdf_manager.npages(), |page| page + 1)
+}
@@ -4,6 +4,7 @@ pub const INVALID_U32: u32 = u32::MAX;
 
 pub static APP_NAME: &str = env!("CARGO_PKG_NAME");
 
+#[cfg(feature = "app")]
 pub static USER_DIRS: Lazy<directories::UserDirs> =
   Lazy::new(|| directories::UserDirs::new().unwrap());
 pub static APP_DIRS: Lazy<directories::ProjectDirs> =
@@ -18,12 +19,14 @@ pub fn enum_variant_eq<T>(a: &T, b: &T) -> bool {
   std::mem::discriminant(a) == std::mem::discriminant(b)
 }
 
+#[cfg(feature = "app")]
 #[allow(dead_code)]
 pub fn rgba_palette2egui(palette: palette::LinSrgba) -> egui::Rgba {
   let palette = palette::Blend::into_premultiplied(palette);
   egui::Rgba::from_rgba_premultiplied(palette.red, palette.green, palette.blue, palette.alpha)
 }
 
+#[cfg(feature = "app")]
 #[allow(dead_code)]
 pub fn rgba_egui2palette(egui: egui::Rgba) -> palette::LinSrgba {
   palette::blend::PreAlpha::from(palette::LinSrgba::new(
@@ -35,6 +38,14 @@ pub fn rgba_egui2palette(egui: egui::Rgba) -> palette::LinSrgba {
   .into()
 }
 
+/// srgb bytes with straight alpha, as used by most file formats
+pub fn rgba_palette2srgb8(palette: palette::LinSrgba) -> [u8; 4] {
+  let srgb: palette::Srgba = palette::FromColor::from_color(palette);
+  let srgb: palette::Srgba<u8> = srgb.into_format();
+  [srgb.red, srgb.green, srgb.blue, srgb.alpha]
+}
+
+#[cfg(feature = "app")]
 #[allow(dead_code)]
 pub fn hsva_palette2egui(palette: palette::Hsva) -> egui::ecolor::Hsva {
   egui::ecolor::Hsva::new(
@@ -45,6 +56,7 @@ pub fn hsva_palette2egui(palette: palette::Hsva) -> egui::ecolor::Hsva {
   )
 }
 
+#[cfg(feature = "app")]
 #[allow(dead_code)]
 pub fn hsva_egui2palette(egui: egui::ecolor::Hsva) -> palette::Hsva {
   palette::Hsva::new(egui.h * 360.0, egui.s, egui.v, egui.a)
@@ -56,3 +68,23 @@ macro_rules! natrans {
     na::convert::<_, na::Transform2<f32>>($t)
   };
 }
+
+/// Serializes colors as `(red, green, blue, alpha)`.
+/// The serde impl of palette flattens the color into a map, which ron fails to read back.
+pub mod serde_color {
+  use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+  pub fn serialize<S: Serializer>(
+    color: &palette::LinSrgba,
+    serializer: S,
+  ) -> Result<S::Ok, S::Error> {
+    (color.red, color.green, color.blue, color.alpha).serialize(serializer)
+  }
+
+  pub fn deserialize<'de, D: Deserializer<'de>>(
+    deserializer: D,
+  ) -> Result<palette::LinSrgba, D::Error> {
+    let (red, green, blue, alpha) = Deserialize::deserialize(deserializer)?;
+    Ok(palette::LinSrgba::new(red, green, blue, alpha))
+  }
+}
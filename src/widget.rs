@@ -0,0 +1,247 @@
+//! The canvas as an egui widget, for showing and editing documents inside other egui apps.
+//!
+//! Unlike the app, which owns its window, event loop and surface, a [`Canvas`] draws into the
+//! [`egui::Ui`] it is shown in, with paint callbacks of the wgpu renderer of egui, e.g. the one
+//! eframe gives with `Frame::wgpu_render_state`.
+//! The documents are the ones of [`crate::headless`].
+
+pub use crate::{
+  spaces::Camera,
+  tools::{PenConfig, ToolEnum},
+};
+
+use crate::{
+  gfx::canvas::CanvasRenderer,
+  headless::Document,
+  input::InputManager,
+  pdf::{self, PdfManager},
+  spaces::SpaceManager,
+  stroke::StrokeManager,
+  tools::ToolManager,
+  ui::overlay::{ui_overlay, OverlayAccess},
+};
+
+use std::{
+  collections::HashMap,
+  sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+  },
+};
+
+/// A view of a document with its own camera and tools.
+///
+/// It has to be shown with the egui renderer it was created for.
+/// The tools edit through the undo history of the document and edits made to the document
+/// elsewhere show up the next time the canvas is shown.
+/// A canvas shows a single document, other documents need their own canvas.
+pub struct Canvas {
+  id: egui::Id,
+  render_state: egui_wgpu::RenderState,
+  input_manager: InputManager,
+  space_manager: SpaceManager,
+  tool_manager: ToolManager,
+  pdf_manager: PdfManager,
+  /// the revision of the content the stroke meshes are built for
+  synced_revision: Option<u64>,
+}
+
+/// The gpu side of the canvases, kept with the egui renderer for the paint callbacks.
+#[derive(Default)]
+struct CanvasResources {
+  canvases: HashMap<egui::Id, CanvasGpu>,
+}
+
+struct CanvasGpu {
+  canvas_renderer: CanvasRenderer,
+  stroke_manager: StrokeManager,
+}
+
+impl Canvas {
+  pub fn new(render_state: &egui_wgpu::RenderState) -> Self {
+    static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+    let id = egui::Id::new(("carveout_canvas", NEXT_ID.fetch_add(1, Ordering::Relaxed)));
+
+    let canvas_gpu = CanvasGpu {
+      canvas_renderer: CanvasRenderer::init(
+        &render_state.device,
+        &render_state.queue,
+        render_state.target_format,
+      ),
+      stroke_manager: StrokeManager::default(),
+    };
+    render_state
+      .renderer
+      .write()
+      .paint_callback_resources
+      .entry::<CanvasResources>()
+      .or_insert_with(Default::default)
+      .canvases
+      .insert(id, canvas_gpu);
+
+    Self {
+      id,
+      render_state: render_state.clone(),
+      input_manager: InputManager::default(),
+      space_manager: SpaceManager::default(),
+      tool_manager: ToolManager::default(),
+      pdf_manager: PdfManager::default(),
+      synced_revision: None,
+    }
+  }
+
+  pub fn tool(&self) -> ToolEnum {
+    self.tool_manager.selected
+  }
+
+  pub fn set_tool(&mut self, tool: ToolEnum) {
+    self.tool_manager.selected = tool;
+  }
+
+  pub fn pen_config_mut(&mut self) -> &mut PenConfig {
+    &mut self.tool_manager.configs.pen
+  }
+
+  pub fn camera(&self) -> &Camera {
+    self.space_manager.camera()
+  }
+
+  pub fn camera_mut(&mut self) -> &mut Camera {
+    self.space_manager.camera_mut()
+  }
+
+  /// Shows the document on all the space available in `ui`.
+  pub fn show(&mut self, ui: &mut egui::Ui, document: &mut Document) -> egui::Response {
+    let (rect, response) =
+      ui.allocate_exact_size(ui.available_size(), egui::Sense::click_and_drag());
+    self
+      .space_manager
+      .update_scale_factor(ui.ctx().pixels_per_point());
+    self.space_manager.update_screen_rect(rect);
+
+    let has_focus = (response.hovered() || response.is_pointer_button_down_on())
+      && !ui.ctx().wants_keyboard_input();
+    self.input_manager.reset();
+    ui.input(|input| {
+      self
+        .input_manager
+        .handle_egui_input(input, has_focus, &self.space_manager)
+    });
+    self.input_manager.update();
+
+    let device = &self.render_state.device;
+    let mut renderer = self.render_state.renderer.write();
+    let canvas_gpu = renderer
+      .paint_callback_resources
+      .get_mut::<CanvasResources>()
+      .and_then(|resources| resources.canvases.get_mut(&self.id))
+      .unwrap();
+
+    let content_manager = document.content_manager_mut();
+    // edits of the document outside of the canvas leave no delta behind
+    if self.synced_revision != Some(content_manager.revision()) {
+      canvas_gpu
+        .stroke_manager
+        .rebuild_strokes(content_manager.access(), device);
+      self.pdf_manager.set_pages(content_manager.access().pages());
+      self
+        .pdf_manager
+        .set_layout(content_manager.access().layout().clone());
+    }
+
+    self.tool_manager.update(
+      &mut self.space_manager,
+      &self.input_manager,
+      content_manager,
+      &canvas_gpu.stroke_manager,
+      &mut self.pdf_manager,
+    );
+
+    let delta = content_manager.delta();
+    canvas_gpu
+      .stroke_manager
+      .update_strokes(content_manager.access(), &delta.strokes, device);
+    if delta.pages_changed {
+      self.pdf_manager.set_pages(content_manager.access().pages());
+      self
+        .pdf_manager
+        .set_layout(content_manager.access().layout().clone());
+    }
+    content_manager.reset_delta();
+    self.synced_revision = Some(content_manager.revision());
+
+    self
+      .space_manager
+      .update_camera_controller(&self.input_manager);
+    pdf::navigation::update(
+      &self.pdf_manager,
+      &mut self.space_manager,
+      &self.input_manager,
+    );
+
+    canvas_gpu.canvas_renderer.prepare(
+      device,
+      &self.render_state.queue,
+      &self.space_manager,
+      &mut self.pdf_manager,
+    );
+    // held keys move the camera every frame
+    if canvas_gpu.canvas_renderer.is_waiting_for_tiles()
+      || self.space_manager.is_camera_animating()
+      || !self.input_manager.curr.pressed.is_empty()
+    {
+      ui.ctx().request_repaint();
+    }
+
+    let background = canvas_gpu.canvas_renderer.background_color();
+    let background = egui::Rgba::from_rgb(
+      background.r as f32,
+      background.g as f32,
+      background.b as f32,
+    );
+    ui.painter().rect_filled(rect, 0.0, background);
+
+    let id = self.id;
+    let callback = egui_wgpu::CallbackFn::new().paint(move |_info, render_pass, resources| {
+      // the canvas might have been dropped since
+      let Some(canvas_gpu) = resources
+        .get::<CanvasResources>()
+        .and_then(|resources| resources.canvases.get(&id))
+      else {
+        return;
+      };
+      canvas_gpu
+        .canvas_renderer
+        .draw(render_pass, &canvas_gpu.stroke_manager);
+    });
+    ui.painter().add(egui::PaintCallback {
+      rect,
+      callback: Arc::new(callback),
+    });
+
+    let mut overlay_ui = ui.child_ui(rect, *ui.layout());
+    overlay_ui.set_clip_rect(rect.intersect(ui.clip_rect()));
+    let mut overlay_access = OverlayAccess {
+      spaces: &mut self.space_manager,
+      content_manager: document.content_manager_mut(),
+      tool_manager: &mut self.tool_manager,
+      pdf_manager: &mut self.pdf_manager,
+      stroke_manager: &canvas_gpu.stroke_manager,
+    };
+    ui_overlay(&overlay_ui, &mut overlay_access, rect);
+
+    response
+  }
+}
+
+impl Drop for Canvas {
+  fn drop(&mut self) {
+    let mut renderer = self.render_state.renderer.write();
+    if let Some(resources) = renderer
+      .paint_callback_resources
+      .get_mut::<CanvasResources>()
+    {
+      resources.canvases.remove(&self.id);
+    }
+  }
+}
@@ -0,0 +1,130 @@
+//! InkML written by other tools and by carveout itself.
+//!
+//! `tablet.inkml` is a recording like tablets write them: pressure and timing channels,
+//! difference encoded traces and brushes for trace groups.
+//! `exported.inkml` is what carveout exports from it.
+
+use carveout::headless::{self, Document, ExportOptions, SaveFormat, Stroke};
+use nalgebra as na;
+
+use std::{
+  fs,
+  path::{Path, PathBuf},
+};
+
+/// the canvas is 2 units per 210 pdf points wide, a pdf point is 1/72 inch
+const CANVAS_UNITS_PER_MM: f32 = 2.0 / 210.0 * 72.0 / 25.4;
+
+fn fixture(name: &str) -> PathBuf {
+  Path::new(env!("CARGO_MANIFEST_DIR"))
+    .join("tests/fixtures")
+    .join(name)
+}
+
+fn temp_dir(name: &str) -> PathBuf {
+  let dir = std::env::temp_dir().join(format!("carveout-inkml-{name}-{}", std::process::id()));
+  fs::create_dir_all(&dir).unwrap();
+  dir
+}
+
+/// the document the file converts into and what got lost
+fn import(path: &Path, dir: &Path) -> (Document, Vec<String>) {
+  let savefile = dir.join("imported.co");
+  let report = headless::convert(path, &savefile, SaveFormat::Ron).unwrap();
+  (Document::load(&savefile).unwrap(), report.lines().collect())
+}
+
+fn strokes(document: &Document) -> Vec<Stroke> {
+  let content = document.content();
+  let strokes = content.strokes().map(|(_, stroke)| stroke.clone());
+  strokes.collect()
+}
+
+fn assert_points_mm(stroke: &Stroke, points_mm: &[[f32; 2]]) {
+  assert_eq!(stroke.points().len(), points_mm.len());
+  for (point, [x, y]) in stroke.points().iter().zip(points_mm) {
+    let expected = na::point![*x, *y] * CANVAS_UNITS_PER_MM;
+    assert!((point - expected).norm() < 1e-5, "{point} != {expected}");
+  }
+}
+
+fn assert_same_strokes(strokes: &[Stroke], expected: &[Stroke]) {
+  assert_eq!(strokes.len(), expected.len());
+  for (stroke, expected) in strokes.iter().zip(expected) {
+    assert_eq!(stroke.points().len(), expected.points().len());
+    for (point, expected) in stroke.points().iter().zip(expected.points()) {
+      // the export rounds to micrometers
+      assert!((point - expected).norm() < 1e-5, "{point} != {expected}");
+    }
+    let width_ratio = stroke.width_multiplier() / expected.width_multiplier();
+    assert!((width_ratio - 1.0).abs() < 1e-2, "{width_ratio}");
+    let (color, expected) = (stroke.color(), expected.color());
+    let difference = [
+      color.red - expected.red,
+      color.green - expected.green,
+      color.blue - expected.blue,
+      color.alpha - expected.alpha,
+    ];
+    assert!(difference.iter().all(|d| d.abs() < 1e-3), "{difference:?}");
+  }
+}
+
+#[test]
+fn imports_tablet_recordings() {
+  let dir = temp_dir("tablet");
+  let (document, report) = import(&fixture("tablet.inkml"), &dir);
+  fs::remove_dir_all(&dir).unwrap();
+
+  let strokes = strokes(&document);
+  assert_eq!(strokes.len(), 3);
+  assert_points_mm(&strokes[0], &[[10.0, 20.0], [11.0, 20.0], [12.0, 21.0]]);
+  // difference encoded
+  assert_points_mm(&strokes[1], &[[10.0, 30.0], [11.0, 30.0], [12.0, 31.0]]);
+  assert_points_mm(&strokes[2], &[[50.0, 50.0], [60.0, 50.0]]);
+
+  // the mean pressure scales the width of the brush
+  let [pen, full_pen, marker] = [0, 1, 2].map(|i| strokes[i].width_multiplier());
+  let mean_pressure = (512.0 + 1023.0 + 512.0) / 3.0 / 1023.0;
+  assert!((pen / full_pen - mean_pressure).abs() < 1e-5);
+  assert!((marker / full_pen - 4.0).abs() < 1e-5);
+
+  let color = |[r, g, b, a]: [u8; 4]| {
+    palette::Srgba::new(r, g, b, a)
+      .into_format::<f32, f32>()
+      .into_linear()
+  };
+  assert_eq!(strokes[0].color(), color([0x1a, 0x4d, 0x99, 255]));
+  assert_eq!(strokes[2].color(), color([255, 0, 0, 127]));
+
+  assert_eq!(
+    report,
+    [
+      "1× trace pressures averaged into the width",
+      "3× trace timings dropped"
+    ]
+  );
+}
+
+#[test]
+fn round_trips_exports() {
+  let dir = temp_dir("round-trip");
+  let (document, _) = import(&fixture("tablet.inkml"), &dir);
+  let exported = dir.join("exported.inkml");
+  assert!(document
+    .export(&exported, &ExportOptions::default())
+    .unwrap());
+  let inkml = fs::read_to_string(&exported).unwrap();
+  let (reimported, report) = import(&exported, &dir);
+  let (sample, sample_report) = import(&fixture("exported.inkml"), &dir);
+  fs::remove_dir_all(&dir).unwrap();
+
+  assert!(report.is_empty(), "{report:?}");
+  assert_same_strokes(&strokes(&reimported), &strokes(&document));
+  // the sample imports the same and is still what gets exported
+  assert!(sample_report.is_empty(), "{sample_report:?}");
+  assert_same_strokes(&strokes(&sample), &strokes(&document));
+  assert_eq!(
+    inkml,
+    fs::read_to_string(fixture("exported.inkml")).unwrap()
+  );
+}
@@ -0,0 +1,65 @@
+//! Page edits, which take the strokes and highlights on the pages along.
+//!
+//! `v1.co` has two blank pages with a stroke on each and a highlight on the second one.
+
+use carveout::headless::{Document, PageKind, PdfPageInfo};
+
+use std::path::{Path, PathBuf};
+
+fn fixture(name: &str) -> PathBuf {
+  Path::new(env!("CARGO_MANIFEST_DIR"))
+    .join("tests/fixtures")
+    .join(name)
+}
+
+#[test]
+fn removes_the_annotations_of_removed_pages() {
+  let mut document = Document::load(&fixture("v1.co")).unwrap();
+  let mut pages = document.content().pages().clone();
+  pages.remove(1);
+  document.set_pages(pages);
+  let content = document.content();
+  assert_eq!(content.strokes().count(), 1);
+  assert_eq!(content.highlights().count(), 0);
+
+  assert!(document.undo());
+  let content = document.content();
+  assert_eq!(content.strokes().count(), 2);
+  let (_, highlight) = content.highlights().next().unwrap();
+  assert_eq!(highlight.page, content.pages().pages()[1].id);
+  assert_eq!(highlight.text, "highlighted");
+}
+
+#[test]
+fn keeps_the_annotations_when_replacing_the_pdfs() {
+  let mut document = Document::load(&fixture("v1.co")).unwrap();
+  let old_ids: Vec<_> = document
+    .content()
+    .pages()
+    .pages()
+    .iter()
+    .map(|entry| entry.id)
+    .collect();
+  let mut pages = document.content().pages().clone();
+  // a missing pdf has one A4 page
+  pages.replace_pdfs(vec![(fixture("missing.pdf"), 1)], |_| Some(PdfPageInfo::A4));
+  document.set_pages(pages);
+
+  let content = document.content();
+  assert_eq!(content.strokes().count(), 2);
+  assert_eq!(content.highlights().count(), 1);
+  let entries = content.pages().pages();
+  assert_eq!(entries.len(), 2);
+  assert_eq!(entries[0].id, old_ids[0]);
+  assert!(matches!(
+    entries[0].kind,
+    PageKind::Pdf { source: 0, page: 0 }
+  ));
+  // past the end of the pdf, kept for the stroke and the highlight on it
+  assert_eq!(entries[1].id, old_ids[1]);
+  assert_eq!(entries[1].kind, PageKind::Blank(PdfPageInfo::A4));
+
+  let mut pages = content.pages().clone();
+  pages.replace_pdfs(vec![(fixture("missing.pdf"), 1)], |_| None);
+  assert_eq!(pages.npages(), 1);
+}
@@ -0,0 +1,81 @@
+//! Png exports compared against golden images of the software renderer.
+//!
+//! Set `CARVEOUT_BLESS=1` to write the current renderings as the new golden images,
+//! and check them by eye before committing.
+
+use carveout::headless::{self, Document, ExportOptions, SaveFormat};
+use image::RgbaImage;
+
+use std::{
+  fs,
+  path::{Path, PathBuf},
+};
+
+fn fixture(name: &str) -> PathBuf {
+  Path::new(env!("CARGO_MANIFEST_DIR"))
+    .join("tests/fixtures")
+    .join(name)
+}
+
+fn temp_dir(name: &str) -> PathBuf {
+  let dir = std::env::temp_dir().join(format!("carveout-png-{name}-{}", std::process::id()));
+  fs::create_dir_all(&dir).unwrap();
+  dir
+}
+
+fn render(document: &Document, dpi: f32, dir: &Path) -> RgbaImage {
+  let output = dir.join("rendered.png");
+  let options = ExportOptions {
+    dpi,
+    ..Default::default()
+  };
+  assert!(document.export(&output, &options).unwrap());
+  image::open(&output).unwrap().to_rgba8()
+}
+
+fn assert_golden(image: &RgbaImage, golden: &str) {
+  let golden_path = fixture("golden").join(golden);
+  if std::env::var_os("CARVEOUT_BLESS").is_some() {
+    image.save(&golden_path).unwrap();
+    return;
+  }
+  let expected = image::open(&golden_path).unwrap().to_rgba8();
+  assert_eq!(image.dimensions(), expected.dimensions(), "{golden}");
+  // rounding may differ slightly between platforms
+  let max_difference = image
+    .pixels()
+    .zip(expected.pixels())
+    .flat_map(|(pixel, expected)| pixel.0.into_iter().zip(expected.0))
+    .map(|(channel, expected)| channel.abs_diff(expected))
+    .max()
+    .unwrap_or(0);
+  assert!(max_difference <= 2, "{golden} differs by {max_difference}");
+}
+
+#[test]
+fn renders_pages_and_highlights() {
+  let dir = temp_dir("pages");
+  let document = Document::load(&fixture("v1.co")).unwrap();
+  // blank pages are drawn as paper without pdfium
+  let image = render(&document, 36.0, &dir);
+  fs::remove_dir_all(&dir).unwrap();
+  assert_golden(&image, "v1.png");
+}
+
+#[test]
+fn renders_strokes() {
+  let dir = temp_dir("strokes");
+  let savefile = dir.join("tablet.co");
+  headless::convert(&fixture("tablet.inkml"), &savefile, SaveFormat::Ron).unwrap();
+  let document = Document::load(&savefile).unwrap();
+  let image = render(&document, 150.0, &dir);
+  fs::remove_dir_all(&dir).unwrap();
+  assert_golden(&image, "tablet.png");
+
+  // the triangles of the translucent marker overlap, but it is blended once
+  let mut marker = image.pixels().filter(|pixel| pixel[0] > pixel[2]);
+  assert!(marker.clone().count() > 0);
+  assert!(marker.all(|pixel| pixel[3] <= 127));
+  // anti aliased edges
+  assert!(image.pixels().any(|pixel| 0 < pixel[3] && pixel[3] < 127));
+}
@@ -0,0 +1,118 @@
+//! Savefiles written by older versions, which have to keep loading.
+//!
+//! `v0.co` is from before the savefiles had versions, with just the strokes
+//! and the removal of the first one, undone.
+//! `v1.co` and `v1-compact.co` hold the same document in both formats: two blank pages
+//! in a free layout, a stroke on each, a highlight on the second page and the same history.
+
+use carveout::headless::{Document, PdfLayout, RemoveHighlightCommand, SaveFormat};
+use nalgebra as na;
+
+use std::path::{Path, PathBuf};
+
+fn fixture(name: &str) -> PathBuf {
+  Path::new(env!("CARGO_MANIFEST_DIR"))
+    .join("tests/fixtures")
+    .join(name)
+}
+
+#[test]
+fn loads_the_current_version() {
+  for name in ["v1.co", "v1-compact.co"] {
+    let mut document =
+      Document::load(&fixture(name)).unwrap_or_else(|error| panic!("{name}: {error}"));
+    assert_strokes(name, &document);
+    let content = document.content();
+    assert_eq!(content.highlights().count(), 1, "{name}");
+    let page_ids: Vec<_> = content
+      .pages()
+      .pages()
+      .iter()
+      .map(|entry| entry.id)
+      .collect();
+    assert_eq!(page_ids.len(), 2, "{name}");
+    let (_, highlight) = content.highlights().next().unwrap();
+    assert_eq!(highlight.page, page_ids[1], "{name}");
+    assert_eq!(highlight.text, "highlighted", "{name}");
+
+    let PdfLayout::Free {
+      page_centers_canvas,
+    } = document.pdf_layout()
+    else {
+      panic!("{name}: not a free layout: {:?}", document.pdf_layout());
+    };
+    let centers: Vec<_> = page_ids.iter().map(|id| page_centers_canvas[id]).collect();
+    assert_eq!(
+      centers,
+      [na::point![0.0, 0.0], na::point![3.0, 9.0]],
+      "{name}"
+    );
+
+    assert_history(name, &mut document, 5);
+  }
+}
+
+#[test]
+fn saves_removed_highlights() {
+  let dir = std::env::temp_dir().join(format!("carveout-savefiles-{}", std::process::id()));
+  std::fs::create_dir_all(&dir).unwrap();
+  for format in [SaveFormat::Ron, SaveFormat::Binary] {
+    let mut document = Document::load(&fixture("v1.co")).unwrap();
+    let (id, _) = document.content().highlights().next().unwrap();
+    document.run(RemoveHighlightCommand::new(id));
+    assert_eq!(document.content().highlights().count(), 0, "{format:?}");
+
+    let path = document.save(&dir.join("removed.co"), format).unwrap();
+    let mut document = Document::load(&path).unwrap();
+    assert_eq!(document.content().highlights().count(), 0, "{format:?}");
+    assert!(document.undo(), "{format:?}");
+    let content = document.content();
+    let (_, highlight) = content.highlights().next().unwrap();
+    assert_eq!(highlight.text, "highlighted", "{format:?}");
+  }
+  std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn loads_the_baseline() {
+  let name = "v0.co";
+  let mut document = Document::load(&fixture(name)).unwrap();
+  assert_strokes(name, &document);
+  assert_eq!(document.content().highlights().count(), 0);
+  assert_eq!(document.content().pages().npages(), 0);
+  assert_eq!(*document.pdf_layout(), PdfLayout::Vertical);
+  assert_history(name, &mut document, 3);
+}
+
+fn assert_strokes(name: &str, document: &Document) {
+  let content = document.content();
+  let mut strokes: Vec<_> = content.strokes().map(|(_, stroke)| stroke).collect();
+  strokes.sort_by(|a, b| a.points()[0].y.total_cmp(&b.points()[0].y));
+  let first_points: Vec<_> = strokes.iter().map(|stroke| stroke.points()[0]).collect();
+  assert_eq!(
+    first_points,
+    [na::point![0.0, 1.0], na::point![0.0, 10.0]],
+    "{name}"
+  );
+  let npoints: Vec<_> = strokes.iter().map(|stroke| stroke.points().len()).collect();
+  assert_eq!(npoints, [3, 2], "{name}");
+  let widths: Vec<_> = strokes
+    .iter()
+    .map(|stroke| stroke.width_multiplier())
+    .collect();
+  assert_eq!(widths, [1.0, 2.0], "{name}");
+  let color = palette::LinSrgba::new(0.2, 0.4, 0.6, 1.0);
+  assert!(
+    strokes.iter().all(|stroke| stroke.color() == color),
+    "{name}"
+  );
+}
+
+/// the removal of the first stroke was undone, so it can be redone
+fn assert_history(name: &str, document: &mut Document, nsteps: usize) {
+  assert_eq!(document.nhistory_steps(), nsteps, "{name}");
+  assert!(document.redo(), "{name}");
+  assert_eq!(document.content().strokes().count(), 1, "{name}");
+  assert!(document.undo(), "{name}");
+  assert_eq!(document.content().strokes().count(), 2, "{name}");
+}
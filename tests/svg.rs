@@ -0,0 +1,148 @@
+//! Svg drawings imported as new documents.
+//!
+//! `drawing.svg` is 200×100 user units shown at twice the size, framed by a rect,
+//! with nested transforms, a circle, an arc, a filled polygon, a gradient stroke and
+//! the elements that are skipped.
+
+use carveout::headless::{self, Document, SaveFormat, Stroke};
+use nalgebra as na;
+
+use std::{
+  fs,
+  path::{Path, PathBuf},
+};
+
+/// the canvas is 2 units per 210 pdf points wide
+const CANVAS_UNITS_PER_POINT: f32 = 2.0 / 210.0;
+/// the view box doubles the user units, a px is 0.75 points
+const POINTS_PER_UNIT: f32 = 2.0 * 0.75;
+
+fn fixture(name: &str) -> PathBuf {
+  Path::new(env!("CARGO_MANIFEST_DIR"))
+    .join("tests/fixtures")
+    .join(name)
+}
+
+/// the document the drawing converts into and what got lost
+fn import(name: &str) -> (Document, Vec<String>) {
+  let dir = std::env::temp_dir().join(format!("carveout-svg-{name}-{}", std::process::id()));
+  fs::create_dir_all(&dir).unwrap();
+  let savefile = dir.join("imported.co");
+  let report = headless::convert(&fixture(name), &savefile, SaveFormat::Ron).unwrap();
+  let document = Document::load(&savefile).unwrap();
+  fs::remove_dir_all(&dir).unwrap();
+  (document, report.lines().collect())
+}
+
+/// the points in user units, the drawing is centered on the origin
+fn user_points(stroke: &Stroke) -> Vec<na::Point2<f32>> {
+  let center = na::vector![100.0, 50.0];
+  let points = stroke.points().iter();
+  points
+    .map(|point| point / (POINTS_PER_UNIT * CANVAS_UNITS_PER_POINT) + center)
+    .collect()
+}
+
+fn assert_points(stroke: &Stroke, expected: &[[f32; 2]]) {
+  let points = user_points(stroke);
+  assert_eq!(points.len(), expected.len());
+  for (point, [x, y]) in points.iter().zip(expected) {
+    let expected = na::point![*x, *y];
+    assert!((point - expected).norm() < 1e-3, "{point} != {expected}");
+  }
+}
+
+/// the points lie on the circle, up to the curves lyon approximates arcs with,
+/// and the shape ends at `last`
+fn assert_arc(stroke: &Stroke, center: [f32; 2], radius: f32, last: [f32; 2]) {
+  let points = user_points(stroke);
+  let center = na::Point2::from(center);
+  for point in &points {
+    let distance = (point - center).norm();
+    assert!((distance - radius).abs() < 0.1, "{point} off the arc");
+  }
+  let end = points.last().unwrap();
+  assert!(
+    (end - na::Point2::from(last)).norm() < 1e-3,
+    "{end} != {last:?}"
+  );
+}
+
+fn color([r, g, b, a]: [u8; 4]) -> palette::LinSrgba {
+  palette::Srgba::new(r, g, b, a)
+    .into_format::<f32, f32>()
+    .into_linear()
+}
+
+#[test]
+fn imports_drawings() {
+  let (document, report) = import("drawing.svg");
+  let content = document.content();
+  assert!(content.pages().pages().is_empty());
+
+  let strokes: Vec<_> = content
+    .strokes()
+    .map(|(_, stroke)| stroke.clone())
+    .collect();
+  // the reused and the defined circle are not drawn
+  assert_eq!(strokes.len(), 6);
+
+  // the frame is closed
+  let frame = [[0.0, 0.0], [200.0, 0.0], [200.0, 100.0], [0.0, 100.0]];
+  assert_points(
+    &strokes[0],
+    &[frame[0], frame[1], frame[2], frame[3], frame[0]],
+  );
+  // translated by the outer group, scaled by the inner one
+  assert_points(&strokes[1], &[[50.0, 20.0], [70.0, 20.0]]);
+  // the circle closes where it starts
+  let circle = user_points(&strokes[2]);
+  assert!(circle.len() > 8);
+  assert_arc(&strokes[2], [150.0, 50.0], 20.0, circle[0].into());
+  // the half circle bulges up
+  assert_eq!(user_points(&strokes[3])[0], na::point![100.0, 80.0]);
+  assert_arc(&strokes[3], [110.0, 80.0], 10.0, [120.0, 80.0]);
+  let top = user_points(&strokes[3])
+    .iter()
+    .map(|point| point.y)
+    .fold(f32::INFINITY, f32::min);
+  assert!((top - 70.0).abs() < 0.1);
+  // the fill is outlined
+  assert_points(
+    &strokes[4],
+    &[[10.0, 60.0], [30.0, 60.0], [20.0, 80.0], [10.0, 60.0]],
+  );
+  assert_points(&strokes[5], &[[10.0, 90.0], [40.0, 90.0]]);
+
+  assert_eq!(strokes[0].color(), color([0, 0, 0, 255]));
+  assert_eq!(strokes[1].color(), color([255, 0, 0, 255]));
+  // the opacity is kept exactly
+  assert_eq!(
+    strokes[2].color(),
+    palette::LinSrgba::new(0.0, 0.0, 1.0, 0.5)
+  );
+  assert_eq!(strokes[3].color(), color([0, 255, 0, 255]));
+  // filled only, so outlined in the fill color
+  assert_eq!(strokes[4].color(), color([128, 128, 128, 255]));
+  // gradients are drawn black
+  assert_eq!(strokes[5].color(), color([0, 0, 0, 255]));
+
+  // the stroke width 2 grows with the scale 2 of the inner group
+  let width = |i: usize| strokes[i].width_multiplier() / strokes[0].width_multiplier();
+  assert!((width(1) - 4.0).abs() < 1e-4);
+  for i in 2..6 {
+    assert!((width(i) - 1.0).abs() < 1e-4);
+  }
+
+  assert_eq!(
+    report,
+    [
+      "1× fills drawn as outlines",
+      "1× fills dropped",
+      "1× gradients and patterns drawn black",
+      "1× images skipped",
+      "1× reused elements skipped",
+      "1× texts skipped",
+    ]
+  );
+}
@@ -0,0 +1,148 @@
+//! Xournal++ notebooks imported as new documents.
+//!
+//! `notebook.xopp` is gzipped like Xournal++ writes it: two pages on `background.pdf`,
+//! which moved next to the notebook, with layers, pressure widths, texts and images,
+//! and a ruled page.
+//! `attached.xopp` is plain xml with an attached pdf and one that is missing.
+
+use carveout::headless::{self, Document, PageKind, PdfPageInfo, SaveFormat, Stroke};
+use nalgebra as na;
+
+use std::{
+  fs,
+  path::{Path, PathBuf},
+};
+
+/// the canvas is 2 units per 210 pdf points wide
+const CANVAS_UNITS_PER_POINT: f32 = 2.0 / 210.0;
+
+fn fixture(name: &str) -> PathBuf {
+  Path::new(env!("CARGO_MANIFEST_DIR"))
+    .join("tests/fixtures")
+    .join(name)
+}
+
+/// the document the notebook converts into and what got lost
+fn import(name: &str) -> (Document, Vec<String>) {
+  let dir = std::env::temp_dir().join(format!("carveout-xopp-{name}-{}", std::process::id()));
+  fs::create_dir_all(&dir).unwrap();
+  let savefile = dir.join("imported.co");
+  let report = headless::convert(&fixture(name), &savefile, SaveFormat::Ron).unwrap();
+  let document = Document::load(&savefile).unwrap();
+  fs::remove_dir_all(&dir).unwrap();
+  (document, report.lines().collect())
+}
+
+/// the points relative to the first one, in points
+fn shape_points(stroke: &Stroke) -> Vec<na::Vector2<f32>> {
+  let first = stroke.points()[0];
+  let points = stroke.points().iter();
+  points
+    .map(|point| (point - first) / CANVAS_UNITS_PER_POINT)
+    .collect()
+}
+
+fn assert_shape(stroke: &Stroke, expected: &[[f32; 2]]) {
+  let shape = shape_points(stroke);
+  assert_eq!(shape.len(), expected.len());
+  for (point, [x, y]) in shape.iter().zip(expected) {
+    let expected = na::vector![*x, *y];
+    assert!((point - expected).norm() < 1e-3, "{point} != {expected}");
+  }
+}
+
+fn color([r, g, b, a]: [u8; 4]) -> palette::LinSrgba {
+  palette::Srgba::new(r, g, b, a)
+    .into_format::<f32, f32>()
+    .into_linear()
+}
+
+#[test]
+fn imports_notebooks() {
+  let (document, report) = import("notebook.xopp");
+  let content = document.content();
+
+  // the pdf moved next to the notebook
+  assert_eq!(content.pages().sources(), [fixture("background.pdf")]);
+  let kinds: Vec<_> = content
+    .pages()
+    .pages()
+    .iter()
+    .map(|entry| entry.kind)
+    .collect();
+  assert_eq!(
+    kinds,
+    [
+      PageKind::Pdf { source: 0, page: 1 },
+      PageKind::Pdf { source: 0, page: 0 },
+      PageKind::Blank(PdfPageInfo {
+        width_points: 150.0,
+        height_points: 150.0
+      }),
+    ]
+  );
+
+  let strokes: Vec<_> = content
+    .strokes()
+    .map(|(_, stroke)| stroke.clone())
+    .collect();
+  assert_eq!(strokes.len(), 4);
+  // both layers of the first page
+  assert_shape(&strokes[0], &[[0.0, 0.0], [10.0, 0.0]]);
+  assert_shape(&strokes[1], &[[0.0, 0.0], [10.0, 0.0], [20.0, 0.0]]);
+  assert!((strokes[1].points()[0] - strokes[0].points()[0]).y > 0.0);
+  // a dot
+  assert_shape(&strokes[2], &[[0.0, 0.0], [0.0, 0.0]]);
+  assert_shape(&strokes[3], &[[0.0, 0.0], [150.0, 150.0]]);
+
+  assert_eq!(strokes[0].color(), color([255, 0, 0, 255]));
+  assert_eq!(strokes[1].color(), color([0, 255, 0, 128]));
+  assert_eq!(strokes[2].color(), color([0, 0, 0, 255]));
+
+  // the pressure widths 1 and 3 average to the nominal width 2
+  let width = |i: usize| strokes[i].width_multiplier() / strokes[0].width_multiplier();
+  assert!((width(1) - 1.0).abs() < 1e-5);
+  assert!((width(2) - 2.0).abs() < 1e-5);
+  assert!((width(3) - 0.5).abs() < 1e-5);
+
+  assert_eq!(
+    report,
+    [
+      "1× colored backgrounds drawn white",
+      "1× empty strokes skipped",
+      "1× images skipped",
+      "1× latex formulas skipped",
+      "1× pages with layers merged into one",
+      "1× pressure sensitive strokes with averaged width",
+      "1× ruled backgrounds drawn plain",
+      "1× stroke colors unknown, drawn black",
+      "1× stroke fills dropped",
+      "1× texts skipped",
+    ]
+  );
+}
+
+#[test]
+fn finds_attached_pdfs() {
+  let (document, report) = import("attached.xopp");
+  let content = document.content();
+
+  assert_eq!(
+    content.pages().sources(),
+    [fixture("attached.xopp.slides.pdf")]
+  );
+  let kinds: Vec<_> = content
+    .pages()
+    .pages()
+    .iter()
+    .map(|entry| entry.kind)
+    .collect();
+  let blank = PageKind::Blank(PdfPageInfo {
+    width_points: 200.0,
+    height_points: 100.0,
+  });
+  // the missing pdf leaves its pages blank, also the ones without file name after it
+  assert_eq!(kinds, [PageKind::Pdf { source: 0, page: 0 }, blank, blank]);
+  assert_eq!(content.strokes().count(), 1);
+  assert_eq!(report, ["1× pdf backgrounds missing, pages left blank"]);
+}